@@ -0,0 +1,275 @@
+//! A lock-free chained Hash-Map, whose Nodes point to more than one other
+//! Node.
+//!
+//! Every Entry in a Bucket points to the next Entry of the Bucket and to a
+//! separate Value-Node, which is replaced when the Value of the Entry is
+//! updated. The old Value-Nodes are then reclaimed by the Allocator.
+
+use std::sync::{atomic, Arc};
+
+const BUCKETS: usize = 16;
+
+pub struct MapNode {
+    key: usize,
+    value: usize,
+    /// The next Entry in the same Bucket
+    next: atomic::AtomicPtr<Self>,
+    /// The Value-Node of an Entry, this is always Null for Value-Nodes
+    value_node: atomic::AtomicPtr<Self>,
+}
+
+impl MapNode {
+    fn entry(key: usize, value_node: *mut Self) -> Self {
+        Self {
+            key,
+            value: 0,
+            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            value_node: atomic::AtomicPtr::new(value_node),
+        }
+    }
+
+    fn value(value: usize) -> Self {
+        Self {
+            key: 0,
+            value,
+            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            value_node: atomic::AtomicPtr::new(std::ptr::null_mut()),
+        }
+    }
+}
+
+pub struct MapGlobal {
+    buckets: Arc<Vec<atomic::AtomicPtr<MapNode>>>,
+}
+
+pub struct HashMap {
+    allocator: free_access::Allocator<MapNode, MapGlobal>,
+    buckets: Arc<Vec<atomic::AtomicPtr<MapNode>>>,
+}
+
+impl free_access::DataStructureGlobals<MapNode> for MapGlobal {
+    fn get_globals(&self) -> Vec<*mut MapNode> {
+        self.buckets
+            .iter()
+            .map(|b| b.load(atomic::Ordering::Acquire))
+            .collect()
+    }
+}
+
+impl free_access::DataStructureNode for MapNode {
+    fn pointer_count() -> usize {
+        2
+    }
+
+    fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+        out.push(self.next.load(atomic::Ordering::Acquire));
+        out.push(self.value_node.load(atomic::Ordering::Acquire));
+    }
+
+    fn untag_ptr(ptr: *mut Self) -> *mut Self {
+        ptr
+    }
+}
+
+/// The Result of looking up a Key in its Bucket
+struct Lookup {
+    head: *mut MapNode,
+    entry: *mut MapNode,
+    value_node: *mut MapNode,
+}
+
+impl HashMap {
+    pub fn new() -> Self {
+        let buckets: Arc<Vec<_>> = Arc::new(
+            (0..BUCKETS)
+                .map(|_| atomic::AtomicPtr::new(std::ptr::null_mut()))
+                .collect(),
+        );
+
+        Self {
+            allocator: free_access::Allocator::builder(MapGlobal {
+                buckets: buckets.clone(),
+            })
+            .initial_pages(4)
+            .build(),
+            buckets,
+        }
+    }
+
+    fn bucket(&self, key: usize) -> &atomic::AtomicPtr<MapNode> {
+        &self.buckets[key % BUCKETS]
+    }
+
+    /// Searches the Bucket of the Key during a Read-Only Period, every Read
+    /// is validated before the next Node is visited, as the Nodes may have
+    /// been reclaimed in the meantime
+    fn lookup(&self, key: usize) -> Result<Lookup, ()> {
+        let head = self.bucket(key).load(atomic::Ordering::Acquire);
+
+        let mut current = head;
+        while !current.is_null() {
+            let node = unsafe { &*current };
+            let node_key = node.key;
+            let next = node.next.load(atomic::Ordering::Acquire);
+            let value_node = node.value_node.load(atomic::Ordering::Acquire);
+            self.allocator.validate_read()?;
+
+            if node_key == key {
+                return Ok(Lookup {
+                    head,
+                    entry: current,
+                    value_node,
+                });
+            }
+            current = next;
+        }
+
+        Ok(Lookup {
+            head,
+            entry: std::ptr::null_mut(),
+            value_node: std::ptr::null_mut(),
+        })
+    }
+
+    /// Releases the Hazard-Ptrs of the finished Operation
+    fn finish(&self) {
+        self.allocator.restart(&mut []);
+    }
+
+    /// Inserts the Key with the given Value or updates the Value of the Key,
+    /// if it is already in the Map
+    pub fn insert(&self, key: usize, value: usize) {
+        'restart: loop {
+            // Both Nodes are allocated again after every Restart, as a
+            // Collection may have reclaimed them before they were protected
+            let mut value_node = self.allocator.allocate(MapNode::value(value)).into_raw();
+            let mut entry = self
+                .allocator
+                .allocate(MapNode::entry(key, value_node))
+                .into_raw();
+
+            loop {
+                // Read-Only Period
+                let mut found = match self.lookup(key) {
+                    Ok(l) => l,
+                    Err(_) => {
+                        self.allocator.restart(&mut [&mut value_node, &mut entry]);
+                        continue 'restart;
+                    }
+                };
+
+                // Write-Only Period
+                let protected = [found.head, found.entry, found.value_node, entry, value_node];
+                if self.allocator.begin_write_only(&protected).is_err() {
+                    self.allocator.restart(&mut [
+                        &mut found.head,
+                        &mut found.entry,
+                        &mut found.value_node,
+                        &mut value_node,
+                        &mut entry,
+                    ]);
+                    continue 'restart;
+                }
+
+                let result = if found.entry.is_null() {
+                    unsafe { &*entry }
+                        .next
+                        .store(found.head, atomic::Ordering::Release);
+                    self.bucket(key).compare_exchange(
+                        found.head,
+                        entry,
+                        atomic::Ordering::SeqCst,
+                        atomic::Ordering::SeqCst,
+                    )
+                } else {
+                    // The old Value-Node is now unreachable and the unused
+                    // Entry was never reachable, so both will be reclaimed
+                    unsafe { &*found.entry }.value_node.compare_exchange(
+                        found.value_node,
+                        value_node,
+                        atomic::Ordering::SeqCst,
+                        atomic::Ordering::SeqCst,
+                    )
+                };
+
+                if result.is_ok() {
+                    self.finish();
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, key: usize) -> Option<usize> {
+        loop {
+            let found = match self.lookup(key) {
+                Ok(l) => l,
+                Err(_) => {
+                    self.allocator.restart(&mut []);
+                    continue;
+                }
+            };
+            if found.entry.is_null() {
+                return None;
+            }
+
+            let value = unsafe { &*found.value_node }.value;
+            if self.allocator.validate_read().is_err() {
+                self.allocator.restart(&mut []);
+                continue;
+            }
+
+            return Some(value);
+        }
+    }
+}
+
+impl Default for HashMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn main() {
+    tracing_subscriber::fmt::init();
+
+    const THREADS: usize = 4;
+    const KEYS: usize = 32;
+    const ROUNDS: usize = 20;
+
+    let map = Arc::new(HashMap::new());
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let map = map.clone();
+            std::thread::spawn(move || {
+                // Every Thread owns its own Keys, so their final Values are
+                // known, while the Buckets are still shared between Threads
+                for round in 0..ROUNDS {
+                    for key in (thread * KEYS)..((thread + 1) * KEYS) {
+                        map.insert(key, round);
+                        assert_eq!(Some(round), map.get(key));
+                    }
+
+                    map.allocator.force_gc();
+                }
+
+                map.allocator.flush_local();
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    for key in 0..(THREADS * KEYS) {
+        assert_eq!(Some(ROUNDS - 1), map.get(key));
+    }
+
+    // Only the Entries and their current Value-Nodes are still reachable
+    map.allocator.force_gc();
+    let stats = map.allocator.collect_stats();
+    println!("{:?}", stats);
+    assert_eq!(2 * THREADS * KEYS, stats.live_nodes);
+}
@@ -27,8 +27,8 @@ impl<T> free_access::DataStructureNode for ListNode<T> {
     fn pointer_count() -> usize {
         1
     }
-    fn pointers(&self) -> Vec<*mut Self> {
-        vec![self.next.load(atomic::Ordering::Acquire)]
+    fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+        out.push(self.next.load(atomic::Ordering::Acquire));
     }
 
     fn untag_ptr(ptr: *mut Self) -> *mut Self {
@@ -52,14 +52,14 @@ impl<T> LinkedList<T> {
             data,
             next: atomic::AtomicPtr::new(std::ptr::null_mut()),
         };
-        let allocated = self.allocator.allocate(new_node);
-        tracing::debug!("New-Node: {:p}", allocated.ptr());
+        let allocated = self.allocator.allocate(new_node).into_raw();
+        tracing::debug!("New-Node: {:p}", allocated);
 
         let mut head = self.head.load(atomic::Ordering::Acquire);
         if head.is_null() {
             match self.head.compare_exchange(
                 std::ptr::null_mut(),
-                allocated.ptr(),
+                allocated,
                 atomic::Ordering::SeqCst,
                 atomic::Ordering::SeqCst,
             ) {
@@ -78,7 +78,7 @@ impl<T> LinkedList<T> {
             if next.is_null() {
                 match current.next.compare_exchange(
                     std::ptr::null_mut(),
-                    allocated.ptr(),
+                    allocated,
                     atomic::Ordering::SeqCst,
                     atomic::Ordering::SeqCst,
                 ) {
@@ -103,6 +103,9 @@ fn main() {
     let handle = std::thread::spawn(move || {
         std::thread::sleep(Duration::from_millis(500));
         c_l.append(15);
+
+        // Return the free Nodes of this Thread before it exits
+        c_l.allocator.flush_local();
     });
 
     list.append(13);
@@ -0,0 +1,64 @@
+//! Shares a single Allocator between multiple Threads, which all allocate
+//! Nodes from it, while one of them also runs a Collection
+
+use std::sync::{atomic, Arc};
+
+pub struct Node {
+    value: usize,
+    next: atomic::AtomicPtr<Self>,
+}
+
+pub struct Root(Arc<atomic::AtomicPtr<Node>>);
+
+impl free_access::DataStructureGlobals<Node> for Root {
+    fn get_globals(&self) -> Vec<*mut Node> {
+        vec![self.0.load(atomic::Ordering::Acquire)]
+    }
+}
+
+impl free_access::DataStructureNode for Node {
+    fn pointer_count() -> usize {
+        1
+    }
+    fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+        out.push(self.next.load(atomic::Ordering::Acquire));
+    }
+
+    fn untag_ptr(ptr: *mut Self) -> *mut Self {
+        ptr
+    }
+}
+
+fn main() {
+    tracing_subscriber::fmt::init();
+
+    let root = Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+    let allocator = Arc::new(free_access::Allocator::new(Root(root.clone())));
+
+    let handles: Vec<_> = (0..2)
+        .map(|thread| {
+            let allocator = allocator.clone();
+            std::thread::spawn(move || {
+                for value in 0..16 {
+                    let node = allocator.allocate(Node {
+                        value: thread * 100 + value,
+                        next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+                    });
+                    tracing::info!("Allocated {} at {:p}", node.value, node.ptr());
+                }
+
+                if thread == 0 {
+                    allocator.force_gc();
+                }
+
+                allocator.flush_local();
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    println!("{:?}", allocator.collect_stats());
+}
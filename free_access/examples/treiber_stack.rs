@@ -0,0 +1,202 @@
+//! A lock-free Treiber-Stack, whose popped Nodes are reclaimed by the
+//! Allocator.
+//!
+//! The Head of the Stack carries a Tag in its low Bits, which is incremented
+//! on every Update, to show how tagged Ptrs interact with the Allocator.
+
+use std::sync::{atomic, Arc};
+
+use free_access::tagging::{tag_mask, tag_ptr, untag};
+
+pub struct StackNode {
+    value: usize,
+    next: atomic::AtomicPtr<Self>,
+}
+
+pub struct StackGlobal {
+    head: Arc<atomic::AtomicPtr<StackNode>>,
+}
+
+pub struct Stack {
+    allocator: free_access::Allocator<StackNode, StackGlobal>,
+    head: Arc<atomic::AtomicPtr<StackNode>>,
+}
+
+impl free_access::DataStructureGlobals<StackNode> for StackGlobal {
+    fn get_globals(&self) -> Vec<*mut StackNode> {
+        // The Globals are handed to the Allocator as they are, so the Tag has
+        // to be removed here
+        let (head, _) = untag(self.head.load(atomic::Ordering::Acquire));
+        vec![head]
+    }
+}
+
+impl free_access::DataStructureNode for StackNode {
+    fn pointer_count() -> usize {
+        1
+    }
+
+    fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+        out.push(self.next.load(atomic::Ordering::Acquire));
+    }
+
+    fn untag_ptr(ptr: *mut Self) -> *mut Self {
+        untag(ptr).0
+    }
+}
+
+/// The Tag of the next Head, following the given one
+fn next_tag(tag: usize) -> usize {
+    (tag + 1) & tag_mask::<StackNode>()
+}
+
+impl Stack {
+    pub fn new() -> Self {
+        let head = Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+
+        Self {
+            // Enough Pages for all the Nodes that are on the Stack at once,
+            // so Allocations rarely need to start a Collection
+            allocator: free_access::Allocator::builder(StackGlobal { head: head.clone() })
+                .initial_pages(8)
+                .build(),
+            head,
+        }
+    }
+
+    /// Releases the Hazard-Ptrs of the finished Operation, otherwise the
+    /// Nodes would stay protected until the next Restart
+    fn finish(&self) {
+        self.allocator.restart(&mut []);
+    }
+
+    pub fn push(&self, value: usize) {
+        'restart: loop {
+            // A Collection, that started before the new Node is protected, may
+            // reclaim it again, so it is allocated again after every Restart
+            let mut node = self
+                .allocator
+                .allocate(StackNode {
+                    value,
+                    next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+                })
+                .into_raw();
+
+            loop {
+                let mut head = self.head.load(atomic::Ordering::Acquire);
+                let (head_ptr, tag) = untag(head);
+
+                if self.allocator.begin_write_only(&[head, node]).is_err() {
+                    self.allocator.restart(&mut [&mut head, &mut node]);
+                    continue 'restart;
+                }
+
+                unsafe { &*node }
+                    .next
+                    .store(head_ptr, atomic::Ordering::Release);
+
+                if self
+                    .head
+                    .compare_exchange(
+                        head,
+                        tag_ptr(node, next_tag(tag)),
+                        atomic::Ordering::SeqCst,
+                        atomic::Ordering::SeqCst,
+                    )
+                    .is_ok()
+                {
+                    self.finish();
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<usize> {
+        loop {
+            // Read-Only Period
+            let mut head = self.head.load(atomic::Ordering::Acquire);
+            let (head_ptr, tag) = untag(head);
+            if head_ptr.is_null() {
+                return None;
+            }
+
+            let head_node = unsafe { &*head_ptr };
+            let mut next = head_node.next.load(atomic::Ordering::Acquire);
+            let value = head_node.value;
+            if self.allocator.validate_read().is_err() {
+                self.allocator.restart(&mut [&mut head, &mut next]);
+                continue;
+            }
+
+            // Write-Only Period
+            if self.allocator.begin_write_only(&[head, next]).is_err() {
+                self.allocator.restart(&mut [&mut head, &mut next]);
+                continue;
+            }
+
+            if self
+                .head
+                .compare_exchange(
+                    head,
+                    tag_ptr(next, next_tag(tag)),
+                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::SeqCst,
+                )
+                .is_ok()
+            {
+                // The old Head is now unreachable and will be reclaimed by
+                // one of the next Collections
+                self.finish();
+                return Some(value);
+            }
+        }
+    }
+}
+
+impl Default for Stack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn main() {
+    tracing_subscriber::fmt::init();
+
+    const THREADS: usize = 2;
+    const OPERATIONS: usize = 1000;
+
+    let stack = Arc::new(Stack::new());
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let stack = stack.clone();
+            std::thread::spawn(move || {
+                let mut popped = 0;
+                for value in 0..OPERATIONS {
+                    stack.push(thread * OPERATIONS + value);
+                    if value % 2 == 0 && stack.pop().is_some() {
+                        popped += 1;
+                    }
+
+                    if value % 100 == 0 {
+                        stack.allocator.force_gc();
+                    }
+                }
+
+                stack.allocator.flush_local();
+                popped
+            })
+        })
+        .collect();
+
+    let popped: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+    let remaining = std::iter::from_fn(|| stack.pop()).count();
+    assert_eq!(THREADS * OPERATIONS, popped + remaining);
+
+    // Every Node was popped, so all of them can be reclaimed
+    stack.allocator.force_gc();
+    let stats = stack.allocator.collect_stats();
+    println!("{:?}", stats);
+    assert_eq!(0, stats.live_nodes);
+}
@@ -1,11 +1,32 @@
-use std::{cell::UnsafeCell, sync::atomic};
+//! The Allocation-Buffers used to hand out free Nodes to the Threads.
+//!
+//! # Lifecycle
+//! During a Sweep every unmarked Node is freed into the [`LocalAllocator`] of
+//! the sweeping Thread, which moves its Buffer into the [`GlobalAllocPool`]
+//! for the current Phase once it is full. Allocating Threads then take whole
+//! Buffers out of the Pool again.
+//!
+//! When a new Phase starts, the Pool is cleared and all the Buffers of older
+//! Phases are dropped. The Ptrs in these Buffers are not lost, as the Nodes
+//! behind them are still unmarked and will therefore be freed again by the
+//! Sweep of the new Phase. Returning them to the Pool directly would instead
+//! hand out the same Node twice.
+
+use alloc::vec::Vec;
+
+use crate::sync::atomic;
+#[cfg(feature = "std")]
+use crate::Phase;
 
 mod pool;
+pub use pool::{Pool, PopError};
 
+#[cfg(feature = "std")]
 pub struct GlobalAllocPool<T> {
     pool: pool::Pool<AllocationBuffer<T>>,
 }
 
+#[cfg(feature = "std")]
 impl<T> GlobalAllocPool<T> {
     pub fn new() -> Self {
         Self {
@@ -16,17 +37,38 @@ impl<T> GlobalAllocPool<T> {
     pub fn pop(&self, phase: u64) -> Result<AllocationBuffer<T>, pool::PopError> {
         self.pool.pop(phase)
     }
-    pub fn insert(&self, phase: u64, data: AllocationBuffer<T>) -> Result<(), ()> {
+    pub fn insert(&self, phase: u64, data: AllocationBuffer<T>) -> Result<(), pool::InvalidPhase> {
         self.pool.insert(data, phase)
     }
 
-    pub fn clear(&self, n_phase: u64) -> Result<(), ()> {
+    /// The Number of free Buffers that are available for the given Phase.
+    ///
+    /// # Concurrency
+    /// This is only a racy Estimate, as other Threads may take or return
+    /// Buffers while they are being counted, see [`Pool::len`]
+    pub fn available(&self, phase: u64) -> usize {
+        self.pool.len(phase)
+    }
+
+    /// Removes all the Buffers for the given Phase from the Pool at once
+    pub fn drain(&self, phase: u64) -> Vec<AllocationBuffer<T>> {
+        self.pool.drain(phase)
+    }
+
+    /// Moves the Pool into the given Phase and drops all the Buffers of older
+    /// Phases, see the [Lifecycle](self#lifecycle) for why their Nodes are
+    /// not reinserted
+    pub fn clear(&self, n_phase: u64) -> Result<(), pool::InvalidPhase> {
         self.pool.update_phase(n_phase)?;
 
+        let removed = self.pool.remove_stale();
+        tracing::debug!(removed, "Removed stale Buffers");
+
         Ok(())
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> Default for GlobalAllocPool<T> {
     fn default() -> Self {
         Self {
@@ -35,62 +77,127 @@ impl<T> Default for GlobalAllocPool<T> {
     }
 }
 
+#[cfg(feature = "std")]
 unsafe impl<T> Send for GlobalAllocPool<T> {}
 
+/// The Buffer of free Nodes of a single Thread.
+///
+/// The Buffer itself is never replaced, instead its Ptrs are moved in and out
+/// of it using the atomic Operations of the [`AllocationBuffer`], so another
+/// Thread accessing the LocalAllocator at the same Time never observes a
+/// Buffer that is being replaced or dropped
+#[cfg(feature = "std")]
 pub struct LocalAllocator<T> {
-    buffer: UnsafeCell<AllocationBuffer<T>>,
+    buffer: AllocationBuffer<T>,
+    buffer_size: usize,
+    /// The Phase in which the Nodes of the current Buffer were freed
+    phase: atomic::AtomicU64,
 }
 
+#[cfg(feature = "std")]
 impl<T> LocalAllocator<T> {
-    pub fn new() -> Self {
+    /// Creates a new LocalAllocator, whose Buffers have the given Size
+    pub fn new(buffer_size: usize) -> Self {
         Self {
-            buffer: UnsafeCell::new(AllocationBuffer::new()),
+            buffer: AllocationBuffer::new(buffer_size),
+            buffer_size,
+            phase: atomic::AtomicU64::new(0),
         }
     }
 
+    /// The Phase in which the Nodes of the current Buffer were freed
+    pub fn phase(&self) -> u64 {
+        self.phase.load(atomic::Ordering::Acquire)
+    }
+
+    /// Drops the current Buffer, if its Nodes were freed in a Phase older
+    /// than the given one.
+    ///
+    /// The Sweep of every Phase frees all the unmarked Nodes again, including
+    /// the ones that are still stored in an older Buffer, so these Buffers
+    /// must not be used anymore, as they would otherwise hand out the same
+    /// Node twice
+    pub fn discard_outdated(&self, phase: u64) {
+        if Phase::from(self.phase()) >= Phase::from(phase) {
+            return;
+        }
+
+        self.buffer.drain_into(|_| {});
+        self.phase.store(phase, atomic::Ordering::Release);
+    }
+
     pub fn is_empty(&self) -> bool {
-        let buffer = unsafe { &*self.buffer.get() };
-        buffer.is_empty()
+        self.buffer.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
     }
 
     pub fn pop(&self) -> Option<*mut T> {
-        let buffer = unsafe { &*self.buffer.get() };
-        buffer.pop()
+        self.buffer.pop()
     }
 
     pub fn insert(&self, data: *mut T) -> Result<(), *mut T> {
-        let buffer = unsafe { &*self.buffer.get() };
-        buffer.insert(data)
+        self.buffer.insert(data)
     }
 
+    /// Moves all the Ptrs of the current Buffer into a new Buffer, leaving
+    /// the current one empty
     pub fn take(&self) -> AllocationBuffer<T> {
-        let ptr = self.buffer.get();
-        unsafe { std::ptr::replace(ptr, AllocationBuffer::new()) }
+        let taken = AllocationBuffer::new(self.buffer_size);
+        self.buffer.drain_into(|ptr| {
+            // Both Buffers have the same Size, so this only fails if other
+            // Threads inserted into the current Buffer while draining it
+            if taken.insert(ptr).is_err() {
+                tracing::debug!("Dropped a free Node while taking the Buffer");
+            }
+        });
+        taken
     }
 
-    pub fn new_buffer(&self, n_buffer: AllocationBuffer<T>) {
-        let ptr = self.buffer.get();
-        unsafe { std::ptr::replace(ptr, n_buffer) };
+    /// Replaces the Ptrs of the current Buffer with the Ptrs of the given
+    /// Buffer, whose Nodes were freed in the given Phase
+    pub fn new_buffer(&self, n_buffer: AllocationBuffer<T>, phase: u64) {
+        self.buffer.drain_into(|_| {});
+        n_buffer.drain_into(|ptr| {
+            if self.buffer.insert(ptr).is_err() {
+                tracing::debug!("Dropped a free Node while replacing the Buffer");
+            }
+        });
+        self.phase.store(phase, atomic::Ordering::Release);
     }
 }
 
-unsafe impl<T> Sync for LocalAllocator<T> {}
-
 mod page;
 pub use page::*;
 
-const BUFFER_SIZE: usize = 128;
+#[cfg(feature = "std")]
+mod overflow;
+#[cfg(feature = "std")]
+pub use overflow::*;
+
+/// The default Size of an AllocationBuffer
+#[cfg(feature = "std")]
+pub const DEFAULT_BUFFER_SIZE: usize = 128;
+
+/// How often [`AllocationBuffer::pop`] checks a reserved Slot, whose Ptr has
+/// not been written yet, before it skips the Slot
+const POP_SPIN_LIMIT: usize = 128;
 
+/// A fixed-size Buffer of free Nodes, which can be filled and emptied
+/// concurrently
 pub struct AllocationBuffer<T> {
     buffer: Vec<atomic::AtomicPtr<T>>,
     head: atomic::AtomicUsize,
 }
 
 impl<T> AllocationBuffer<T> {
-    pub fn new() -> Self {
-        let mut buffer = Vec::with_capacity(BUFFER_SIZE);
-        for _ in 0..BUFFER_SIZE {
-            buffer.push(atomic::AtomicPtr::new(std::ptr::null_mut()));
+    /// Creates a new empty Buffer, with room for `size` Ptrs
+    pub fn new(size: usize) -> Self {
+        let mut buffer = Vec::with_capacity(size);
+        for _ in 0..size {
+            buffer.push(atomic::AtomicPtr::new(core::ptr::null_mut()));
         }
 
         Self {
@@ -99,53 +206,178 @@ impl<T> AllocationBuffer<T> {
         }
     }
 
+    /// The Number of Ptrs currently stored in the Buffer, which is only a
+    /// Snapshot while other Threads use the Buffer concurrently
+    pub fn len(&self) -> usize {
+        self.head.load(atomic::Ordering::Acquire)
+    }
+
+    /// Whether or not the Buffer currently contains no Ptrs
     pub fn is_empty(&self) -> bool {
         let current = self.head.load(atomic::Ordering::Acquire);
         current < 1
     }
 
+    /// Inserts the given Ptr into the Buffer, or returns it again if the
+    /// Buffer is already full. A Buffer created with `size` holds exactly
+    /// `size` Ptrs, `head` is never advanced by a failed Insert.
+    ///
+    /// A Slot is first reserved by advancing `head` using a CAS, only then the
+    /// Ptr is written into the reserved Slot. If a concurrent `pop` reserved
+    /// the same Slot before and has not yet taken its Ptr out of it, we wait
+    /// for it to do so, to never overwrite a Ptr. If a `pop` gave up on the
+    /// Slot and marked it as skipped, the Slot is cleared again and the
+    /// Insert starts over by reserving a new Slot
     pub fn insert(&self, ptr: *mut T) -> Result<(), *mut T> {
-        let current = self.head.load(atomic::Ordering::Acquire);
-        let next = current + 1;
-        if next >= BUFFER_SIZE {
-            return Err(ptr);
-        }
-
-        self.head.store(next, atomic::Ordering::Release);
-
-        let bucket = unsafe { self.buffer.get_unchecked(current) };
-        match bucket.compare_exchange(
-            std::ptr::null_mut(),
-            ptr,
-            atomic::Ordering::SeqCst,
-            atomic::Ordering::SeqCst,
-        ) {
-            Ok(_) => Ok(()),
-            Err(_) => Err(ptr),
+        'reserve: loop {
+            let mut current = self.head.load(atomic::Ordering::Acquire);
+            loop {
+                if current >= self.buffer.len() {
+                    return Err(ptr);
+                }
+                let next = current + 1;
+
+                match self.head.compare_exchange(
+                    current,
+                    next,
+                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::SeqCst,
+                ) {
+                    Ok(_) => break,
+                    Err(cur) => {
+                        current = cur;
+                    }
+                };
+            }
+
+            let bucket = unsafe { self.buffer.get_unchecked(current) };
+            loop {
+                match bucket.compare_exchange(
+                    core::ptr::null_mut(),
+                    ptr,
+                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::SeqCst,
+                ) {
+                    Ok(_) => return Ok(()),
+                    Err(existing) if existing == Self::skipped() => {
+                        let _ = bucket.compare_exchange(
+                            existing,
+                            core::ptr::null_mut(),
+                            atomic::Ordering::SeqCst,
+                            atomic::Ordering::SeqCst,
+                        );
+                        continue 'reserve;
+                    }
+                    Err(_) => core::hint::spin_loop(),
+                };
+            }
         }
     }
 
+    /// Takes the most recently inserted Ptr out of the Buffer, or returns
+    /// `None` if the Buffer is empty.
+    ///
+    /// Like `insert`, this first reserves the Slot by moving `head` back using
+    /// a CAS and then takes the Ptr out of the Slot. If the `insert` that
+    /// reserved the Slot has not yet written its Ptr, we wait for it instead
+    /// of returning a stale Value.
+    ///
+    /// The Wait is bounded by [`POP_SPIN_LIMIT`], as the Insert may be stalled
+    /// for an arbitrary Time. Afterwards the Slot is marked as skipped and the
+    /// Pop moves on to the next Slot, the stalled Insert then notices the Mark
+    /// and inserts its Ptr into another Slot instead
     pub fn pop(&self) -> Option<*mut T> {
-        let current = self.head.load(atomic::Ordering::Acquire);
-        if current < 1 {
-            return None;
+        'reserve: loop {
+            let mut current = self.head.load(atomic::Ordering::Acquire);
+            loop {
+                if current < 1 {
+                    return None;
+                }
+
+                match self.head.compare_exchange(
+                    current,
+                    current - 1,
+                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::SeqCst,
+                ) {
+                    Ok(_) => break,
+                    Err(cur) => {
+                        current = cur;
+                    }
+                };
+            }
+
+            let bucket = unsafe { self.buffer.get_unchecked(current - 1) };
+            for _ in 0..POP_SPIN_LIMIT {
+                let ptr = bucket.load(atomic::Ordering::Acquire);
+                if !ptr.is_null()
+                    && ptr != Self::skipped()
+                    && bucket
+                        .compare_exchange(
+                            ptr,
+                            core::ptr::null_mut(),
+                            atomic::Ordering::SeqCst,
+                            atomic::Ordering::SeqCst,
+                        )
+                        .is_ok()
+                {
+                    return Some(ptr);
+                }
+
+                core::hint::spin_loop();
+            }
+
+            match bucket.compare_exchange(
+                core::ptr::null_mut(),
+                Self::skipped(),
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                // The Insert wrote its Ptr right before the Slot was skipped
+                Err(ptr) if ptr != Self::skipped() => {
+                    if bucket
+                        .compare_exchange(
+                            ptr,
+                            core::ptr::null_mut(),
+                            atomic::Ordering::SeqCst,
+                            atomic::Ordering::SeqCst,
+                        )
+                        .is_ok()
+                    {
+                        return Some(ptr);
+                    }
+                    continue 'reserve;
+                }
+                _ => continue 'reserve,
+            };
         }
+    }
 
-        let next = current - 1;
-        self.head.store(next, atomic::Ordering::Release);
-
-        let bucket = unsafe { self.buffer.get_unchecked(next) };
-        let ptr = bucket.load(atomic::Ordering::Acquire);
+    /// The Value stored in a Slot that was skipped by a `pop`, because its
+    /// Insert did not write its Ptr in Time
+    fn skipped() -> *mut T {
+        usize::MAX as *mut T
+    }
 
-        match bucket.compare_exchange(
-            ptr,
-            std::ptr::null_mut(),
-            atomic::Ordering::SeqCst,
-            atomic::Ordering::SeqCst,
-        ) {
-            Ok(_) => Some(ptr),
-            Err(_) => None,
+    /// Takes all the Ptrs out of the Buffer and passes them to the given
+    /// `sink`, leaving the Buffer empty.
+    ///
+    /// Every Ptr is taken out using [`pop`](Self::pop), so concurrent
+    /// Operations on the Buffer never cause a Ptr to be passed on twice.
+    ///
+    /// # Returns
+    /// The Number of Ptrs that were passed to the `sink`
+    pub fn drain_into<F>(&self, mut sink: F) -> usize
+    where
+        F: FnMut(*mut T),
+    {
+        let mut drained = 0;
+        while let Some(ptr) = self.pop() {
+            sink(ptr);
+            drained += 1;
         }
+
+        drained
     }
 }
 
@@ -153,22 +385,103 @@ impl<T> AllocationBuffer<T> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn local_discard_outdated() {
+        let local = LocalAllocator::<usize>::new(4);
+        local.insert(0x10 as *mut usize).unwrap();
+
+        local.discard_outdated(0);
+        assert!(!local.is_empty());
+
+        local.discard_outdated(1);
+        assert!(local.is_empty());
+        assert_eq!(1, local.phase());
+
+        let buffer = AllocationBuffer::new(4);
+        buffer.insert(0x20 as *mut usize).unwrap();
+        local.new_buffer(buffer, 2);
+        assert_eq!(2, local.phase());
+        assert_eq!(Some(0x20 as *mut usize), local.pop());
+    }
+
+    #[test]
+    fn buffer_capacity() {
+        let buffer = AllocationBuffer::<usize>::new(4);
+        for i in 1..=4 {
+            buffer.insert((i * 0x10) as *mut usize).unwrap();
+        }
+        assert_eq!(Err(0x50 as *mut usize), buffer.insert(0x50 as *mut usize));
+
+        // The failed Insert did not reserve a Slot
+        for i in (1..=4).rev() {
+            assert_eq!(Some((i * 0x10) as *mut usize), buffer.pop());
+        }
+        assert_eq!(None, buffer.pop());
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn global_pool_available() {
+        let pool = GlobalAllocPool::<usize>::new();
+        assert_eq!(0, pool.available(0));
+
+        pool.insert(0, AllocationBuffer::new(4)).unwrap();
+        pool.insert(0, AllocationBuffer::new(4)).unwrap();
+        assert_eq!(2, pool.available(0));
+
+        assert!(pool.pop(0).is_ok());
+        assert_eq!(1, pool.available(0));
+        // Buffers are only ever available for the Phase they were inserted in
+        assert_eq!(0, pool.available(1));
+    }
+
+    #[test]
+    fn global_pool_clear() {
+        let pool = GlobalAllocPool::<usize>::new();
+        pool.insert(0, AllocationBuffer::new(4)).unwrap();
+        pool.insert(0, AllocationBuffer::new(4)).unwrap();
+        assert_eq!(2, pool.available(0));
+
+        pool.clear(1).unwrap();
+        assert_eq!(0, pool.available(0));
+        assert_eq!(0, pool.available(1));
+
+        // Clearing for an older Phase does nothing
+        assert!(pool.clear(1).is_err());
+    }
+
+    #[test]
+    fn buffer_drain_into() {
+        let buffer = AllocationBuffer::<usize>::new(DEFAULT_BUFFER_SIZE);
+        for raw in 1..=4 {
+            buffer.insert((raw * 0x10) as *mut usize).unwrap();
+        }
+
+        let mut drained = Vec::new();
+        assert_eq!(4, buffer.drain_into(|ptr| drained.push(ptr as usize)));
+        drained.sort_unstable();
+        assert_eq!(vec![0x10, 0x20, 0x30, 0x40], drained);
+
+        assert!(buffer.is_empty());
+        assert_eq!(0, buffer.drain_into(|_| unreachable!()));
+    }
+
     #[test]
     fn buffer_new() {
-        let buffer = AllocationBuffer::<usize>::new();
+        let buffer = AllocationBuffer::<usize>::new(DEFAULT_BUFFER_SIZE);
         drop(buffer);
     }
 
     #[test]
     fn buffer_insert() {
-        let buffer = AllocationBuffer::<usize>::new();
+        let buffer = AllocationBuffer::<usize>::new(DEFAULT_BUFFER_SIZE);
 
         buffer.insert(123 as *mut usize).unwrap();
     }
 
     #[test]
     fn buffer_insert_pop() {
-        let buffer = AllocationBuffer::<usize>::new();
+        let buffer = AllocationBuffer::<usize>::new(DEFAULT_BUFFER_SIZE);
 
         buffer.insert(123 as *mut usize).unwrap();
 
@@ -178,14 +491,14 @@ mod tests {
 
     #[test]
     fn buffer_pop_empty() {
-        let buffer = AllocationBuffer::<usize>::new();
+        let buffer = AllocationBuffer::<usize>::new(DEFAULT_BUFFER_SIZE);
 
         assert_eq!(None, buffer.pop());
     }
 
     #[test]
     fn buffer_multiple_inserts() {
-        let buffer = AllocationBuffer::<usize>::new();
+        let buffer = AllocationBuffer::<usize>::new(DEFAULT_BUFFER_SIZE);
 
         buffer.insert(123 as *mut usize).unwrap();
         assert_eq!(Some(123 as *mut usize), buffer.pop());
@@ -196,11 +509,113 @@ mod tests {
 
     #[test]
     fn buffer_is_empty() {
-        let buffer = AllocationBuffer::<usize>::new();
+        let buffer = AllocationBuffer::<usize>::new(DEFAULT_BUFFER_SIZE);
 
         assert_eq!(true, buffer.is_empty());
 
         buffer.insert(123 as *mut usize).unwrap();
         assert_eq!(false, buffer.is_empty());
     }
+
+    #[test]
+    fn buffer_concurrent() {
+        use std::sync::Arc;
+
+        const THREADS: usize = 4;
+        const ITERATIONS: usize = 10_000;
+
+        let buffer = Arc::new(AllocationBuffer::<usize>::new(DEFAULT_BUFFER_SIZE));
+
+        let handles: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let buffer = buffer.clone();
+                std::thread::spawn(move || {
+                    let mut popped = Vec::with_capacity(ITERATIONS);
+                    for i in 0..ITERATIONS {
+                        let value = thread * ITERATIONS + i + 1;
+                        buffer.insert(value as *mut usize).unwrap();
+
+                        // The Pop may skip the Slot of a stalled Insert and
+                        // find the Buffer empty, until that Insert is retried
+                        let ptr = loop {
+                            if let Some(ptr) = buffer.pop() {
+                                break ptr;
+                            }
+                        };
+                        popped.push(ptr as usize);
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut popped: Vec<usize> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        assert_eq!(None, buffer.pop());
+
+        popped.sort_unstable();
+        let expected: Vec<usize> = (1..=THREADS * ITERATIONS).collect();
+        assert_eq!(expected, popped);
+    }
+
+    #[test]
+    fn buffer_pop_skips_unfilled_slot() {
+        let buffer = AllocationBuffer::<usize>::new(4);
+
+        // An Insert that reserved the first Slot, but never wrote its Ptr
+        buffer.head.store(1, atomic::Ordering::SeqCst);
+        assert_eq!(None, buffer.pop());
+        assert_eq!(
+            AllocationBuffer::skipped(),
+            buffer.buffer[0].load(atomic::Ordering::SeqCst)
+        );
+
+        // The next Insert clears the skipped Slot and uses the next one, so
+        // the stalled Insert can still write into the cleared Slot
+        buffer.insert(0x10 as *mut usize).unwrap();
+        assert_eq!(2, buffer.len());
+        buffer.buffer[0].store(0x20 as *mut usize, atomic::Ordering::SeqCst);
+
+        assert_eq!(Some(0x10 as *mut usize), buffer.pop());
+        assert_eq!(Some(0x20 as *mut usize), buffer.pop());
+        assert_eq!(None, buffer.pop());
+    }
+
+    #[test]
+    fn local_take_concurrent() {
+        use std::sync::Arc;
+
+        const COUNT: usize = 2_000;
+
+        let local = Arc::new(LocalAllocator::<usize>::new(64));
+        let done = Arc::new(atomic::AtomicBool::new(false));
+
+        let taker = {
+            let local = local.clone();
+            let done = done.clone();
+            std::thread::spawn(move || {
+                let mut taken = Vec::new();
+                while !done.load(atomic::Ordering::Acquire) {
+                    local.take().drain_into(|ptr| taken.push(ptr as usize));
+                }
+                taken
+            })
+        };
+
+        for value in 1..=COUNT {
+            while local.insert(value as *mut usize).is_err() {
+                std::thread::yield_now();
+            }
+        }
+        done.store(true, atomic::Ordering::Release);
+
+        let mut taken = taker.join().unwrap();
+        local.take().drain_into(|ptr| taken.push(ptr as usize));
+
+        taken.sort_unstable();
+        let expected: Vec<usize> = (1..=COUNT).collect();
+        assert_eq!(expected, taken);
+    }
 }
@@ -0,0 +1,504 @@
+//! The Overflow-List keeps track of all the Nodes that could not be stored in
+//! the normal Pages and were instead allocated using the System-Allocator.
+//!
+//! # Strucure
+//! The List consists of a singly-linked List of Entries, which will never be
+//! deallocated while the List is alive, similiar to the Hazard-Ptr-Frame.
+//! Each Entry either stores a Ptr to an overflow Node or is empty, in which
+//! case it can be reused by the next Insert.
+//!
+//! The overflow Nodes themselves are also [`PageNode`]s, so they carry the
+//! same Marks as every other Node and can be traced like any other Node.
+//!
+//! # Freeing
+//! Several Threads may sweep the List at the same Time, while others are
+//! still clearing the Marks for the next Phase. A Node is only reclaimed by
+//! the Thread that removes it from its Entry, but the other Threads may have
+//! loaded it just before that and still read its Marks. So the removed Nodes
+//! are only retired and the Memory of a Node is only freed once no Thread is
+//! walking the List anymore.
+
+use alloc::boxed::Box;
+
+use crate::{sync::atomic, Phase};
+
+use super::{NodeMarks, PageNode};
+
+/// Nodes, whose Data is larger than this, are considered oversized and will
+/// not be stored in the Pages if the overflow Allocation is enabled
+pub const MAX_PAGE_NODE_SIZE: usize = 4096;
+
+/// Checks if the given Type is too large to be stored in the Pages
+pub const fn is_oversized<T>() -> bool {
+    core::mem::size_of::<T>() > MAX_PAGE_NODE_SIZE
+}
+
+struct Entry<T> {
+    node: atomic::AtomicPtr<PageNode<T>>,
+    next: atomic::AtomicPtr<Self>,
+}
+
+impl<T> Entry<T> {
+    pub fn new(node: *mut PageNode<T>) -> Self {
+        Self {
+            node: atomic::AtomicPtr::new(node),
+            next: atomic::AtomicPtr::new(core::ptr::null_mut()),
+        }
+    }
+}
+
+pub struct OverflowList<T> {
+    start: *mut Entry<T>,
+    /// The Number of Threads currently walking the List and reading Nodes
+    readers: atomic::AtomicUsize,
+    /// The Nodes that were removed from the List and whose Data was already
+    /// dropped, but that may still be read by one of the `readers`. Every one
+    /// of them is stored in a separate Entry, linked using `next`
+    retired: atomic::AtomicPtr<Entry<T>>,
+    /// The latest Phase whose limited Sweep was claimed by a Thread
+    sweep_phase: atomic::AtomicU64,
+    /// The Index of the Entry the next limited Sweep starts at
+    sweep_cursor: atomic::AtomicUsize,
+}
+
+impl<T> OverflowList<T> {
+    pub fn new() -> Self {
+        let initial = Box::into_raw(Box::new(Entry::new(core::ptr::null_mut())));
+        Self {
+            start: initial,
+            readers: atomic::AtomicUsize::new(0),
+            retired: atomic::AtomicPtr::new(core::ptr::null_mut()),
+            sweep_phase: atomic::AtomicU64::new(0),
+            sweep_cursor: atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Allocates a new Node using the System-Allocator and stores the given
+    /// Data in it. The Node starts out as marked in the given Phase, so that
+    /// it survives a Sweep that is currently in progress
+    pub fn allocate(&self, data: T, phase: u64) -> *mut T {
+        let node = PageNode::<T>::new();
+        let unmarked = NodeMarks {
+            phase: 0,
+            marked: false,
+        };
+        let marked = NodeMarks {
+            phase,
+            marked: true,
+        };
+        node.update_marks(unmarked, marked)
+            .expect("A new Node always starts out unmarked");
+
+        let node_ptr = Box::into_raw(Box::new(node));
+        let data_ptr = unsafe { (*node_ptr).get_data_ptr() };
+        unsafe { data_ptr.write(data) };
+
+        self.insert(node_ptr);
+
+        data_ptr
+    }
+
+    fn insert(&self, node: *mut PageNode<T>) {
+        let mut latest_ptr = self.start;
+        for current_ptr in self.iter() {
+            let current = unsafe { &*current_ptr };
+
+            if current
+                .node
+                .compare_exchange(
+                    core::ptr::null_mut(),
+                    node,
+                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::SeqCst,
+                )
+                .is_ok()
+            {
+                return;
+            }
+            latest_ptr = current_ptr;
+        }
+
+        let mut current = unsafe { &*latest_ptr };
+
+        let new_entry_ptr = Box::into_raw(Box::new(Entry::new(node)));
+        loop {
+            match current.next.compare_exchange(
+                core::ptr::null_mut(),
+                new_entry_ptr,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                Ok(_) => return,
+                Err(next) => {
+                    current = unsafe { &*next };
+                }
+            };
+        }
+    }
+
+    /// The Number of overflow Nodes that are currently allocated
+    pub fn len(&self) -> usize {
+        self.iter()
+            .filter(|e| {
+                let entry = unsafe { &**e };
+                !entry.node.load(atomic::Ordering::Acquire).is_null()
+            })
+            .count()
+    }
+
+    /// Clears the Marks of all the overflow Nodes for the new Phase
+    #[tracing::instrument(skip(self))]
+    pub fn update_marks(&self, n_phase: u64) {
+        let _reading = self.read();
+        for entry_ptr in self.iter() {
+            let entry = unsafe { &*entry_ptr };
+
+            let node_ptr = entry.node.load(atomic::Ordering::SeqCst);
+            if node_ptr.is_null() {
+                continue;
+            }
+
+            let node = unsafe { &*node_ptr };
+            node.clear_marks(n_phase);
+        }
+    }
+
+    /// Reclaims all the overflow Nodes that were not marked in the given
+    /// Phase, `on_reclaim` is called with the Data of every Node right before
+    /// it is dropped. The Memory of the Nodes is freed once no other Thread
+    /// may still be reading them.
+    ///
+    /// With a `budget`, only that many Entries are checked, starting where the
+    /// limited Sweep of the previous Phase stopped, and only by the first
+    /// Thread that sweeps the Phase, so the Budget is not multiplied by the
+    /// Number of Threads. The Nodes that are skipped stay unmarked and are
+    /// freed by the Sweep of a later Phase instead.
+    ///
+    /// # Returns
+    /// The Number of Nodes that were freed
+    #[tracing::instrument(skip(self, on_reclaim))]
+    pub fn sweep<F>(&self, phase: u64, budget: Option<usize>, mut on_reclaim: F) -> usize
+    where
+        F: FnMut(*mut T),
+    {
+        let _reading = self.read();
+        let entries: Box<dyn Iterator<Item = *mut Entry<T>>> = match budget {
+            None => Box::new(self.iter()),
+            Some(budget) => {
+                let previous = self.sweep_phase.fetch_max(phase, atomic::Ordering::SeqCst);
+                if Phase::from(previous) >= Phase::from(phase) {
+                    return 0;
+                }
+
+                let len = self.iter().count();
+                let start = self.sweep_cursor.load(atomic::Ordering::Acquire) % len;
+                self.sweep_cursor
+                    .store((start + budget) % len, atomic::Ordering::Release);
+
+                Box::new(
+                    self.iter()
+                        .skip(start)
+                        .chain(self.iter().take(start))
+                        .take(budget),
+                )
+            }
+        };
+
+        let mut freed = 0;
+
+        for entry_ptr in entries {
+            let entry = unsafe { &*entry_ptr };
+
+            // Pairs with the Check for Readers in `free_retired`, so either the
+            // removed Nodes are not loaded or they are not freed yet
+            let node_ptr = entry.node.load(atomic::Ordering::SeqCst);
+            if node_ptr.is_null() {
+                continue;
+            }
+
+            let marks = unsafe { &*node_ptr }.load_marks();
+            if marks.marked || marks.phase != phase {
+                continue;
+            }
+
+            // Only the Thread that removes the Node from its Entry is allowed
+            // to actually reclaim it
+            if entry
+                .node
+                .compare_exchange(
+                    node_ptr,
+                    core::ptr::null_mut(),
+                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::SeqCst,
+                )
+                .is_err()
+            {
+                continue;
+            }
+
+            let data_ptr = unsafe { (*node_ptr).get_data_ptr() };
+            on_reclaim(data_ptr);
+            unsafe { core::ptr::drop_in_place(data_ptr) };
+            self.retire(node_ptr);
+
+            freed += 1;
+        }
+
+        freed
+    }
+
+    /// Registers the current Thread as a Reader of the Nodes, until the
+    /// returned Guard is dropped
+    fn read(&self) -> ReadGuard<'_, T> {
+        self.readers.fetch_add(1, atomic::Ordering::SeqCst);
+        ReadGuard { list: self }
+    }
+
+    /// Adds the removed Node, whose Data was already dropped, to the retired
+    /// Nodes
+    fn retire(&self, node: *mut PageNode<T>) {
+        let entry_ptr = Box::into_raw(Box::new(Entry::new(node)));
+        self.push_retired(entry_ptr, entry_ptr);
+    }
+
+    /// Pushes the Chain of Entries from `first` to `last` onto the retired
+    /// Nodes
+    fn push_retired(&self, first: *mut Entry<T>, last: *mut Entry<T>) {
+        let last = unsafe { &*last };
+        let mut head = self.retired.load(atomic::Ordering::Acquire);
+        loop {
+            last.next.store(head, atomic::Ordering::Relaxed);
+            match self.retired.compare_exchange(
+                head,
+                first,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::Acquire,
+            ) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            };
+        }
+    }
+
+    /// Frees the retired Nodes, unless a Thread is still walking the List, in
+    /// which case they are put back and freed by the last of these Threads
+    fn free_retired(&self) {
+        // The Nodes are taken out first, so every Thread that could still
+        // load one of them is counted by the Check below
+        let first = self
+            .retired
+            .swap(core::ptr::null_mut(), atomic::Ordering::SeqCst);
+        if first.is_null() {
+            return;
+        }
+
+        if self.readers.load(atomic::Ordering::SeqCst) != 0 {
+            let mut last = first;
+            loop {
+                let next = unsafe { &*last }.next.load(atomic::Ordering::Acquire);
+                if next.is_null() {
+                    break;
+                }
+                last = next;
+            }
+            self.push_retired(first, last);
+            return;
+        }
+
+        Self::free_entries(first);
+    }
+
+    /// Frees the Chain of retired Entries starting at `current`, together
+    /// with their Nodes
+    fn free_entries(mut current: *mut Entry<T>) {
+        while !current.is_null() {
+            let entry = unsafe { Box::from_raw(current) };
+            current = entry.next.load(atomic::Ordering::Acquire);
+
+            // The Data was already dropped when the Node was retired
+            drop(unsafe { Box::from_raw(entry.node.load(atomic::Ordering::Acquire)) });
+        }
+    }
+
+    fn iter(&self) -> EntryIter<T> {
+        EntryIter {
+            current: self.start,
+        }
+    }
+}
+
+impl<T> Drop for OverflowList<T> {
+    fn drop(&mut self) {
+        let mut current_ptr = self.start;
+        while !current_ptr.is_null() {
+            let current = unsafe { Box::from_raw(current_ptr) };
+            current_ptr = current.next.load(atomic::Ordering::Acquire);
+
+            // Every Node still in the List is allocated and stores Data
+            let node_ptr = current.node.load(atomic::Ordering::Acquire);
+            if !node_ptr.is_null() {
+                let node = unsafe { Box::from_raw(node_ptr) };
+                unsafe { core::ptr::drop_in_place(node.get_data_ptr()) };
+            }
+        }
+
+        Self::free_entries(self.retired.load(atomic::Ordering::Acquire));
+    }
+}
+
+/// See [`OverflowList::read`]
+struct ReadGuard<'a, T> {
+    list: &'a OverflowList<T>,
+}
+
+impl<'a, T> Drop for ReadGuard<'a, T> {
+    fn drop(&mut self) {
+        if self.list.readers.fetch_sub(1, atomic::Ordering::SeqCst) == 1 {
+            self.list.free_retired();
+        }
+    }
+}
+
+unsafe impl<T> Send for OverflowList<T> {}
+unsafe impl<T> Sync for OverflowList<T> {}
+
+struct EntryIter<T> {
+    current: *mut Entry<T>,
+}
+
+impl<T> Iterator for EntryIter<T> {
+    type Item = *mut Entry<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current.is_null() {
+            return None;
+        }
+
+        let ptr = self.current;
+        let current = unsafe { &*self.current };
+        self.current = current.next.load(atomic::Ordering::Acquire);
+
+        Some(ptr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized() {
+        assert!(!is_oversized::<usize>());
+        assert!(is_oversized::<[u8; MAX_PAGE_NODE_SIZE + 1]>());
+    }
+
+    #[test]
+    fn allocate_sweep() {
+        let list = OverflowList::<usize>::new();
+
+        let first = list.allocate(13, 1);
+        let second = list.allocate(14, 1);
+        assert_eq!(2, list.len());
+        assert_eq!(13, unsafe { *first });
+        assert_eq!(14, unsafe { *second });
+
+        // Newly allocated Nodes survive the Sweep of their Phase
+        assert_eq!(0, list.sweep(1, None, |_| {}));
+
+        list.update_marks(2);
+        let node = unsafe { PageNode::from_data_ptr(first) };
+        node.update_marks(
+            NodeMarks {
+                phase: 2,
+                marked: false,
+            },
+            NodeMarks {
+                phase: 2,
+                marked: true,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(1, list.sweep(2, None, |_| {}));
+        assert_eq!(1, list.len());
+
+        // The freed Entry gets reused
+        list.allocate(15, 2);
+        assert_eq!(2, list.len());
+    }
+
+    #[test]
+    fn sweep_budget() {
+        let list = OverflowList::<usize>::new();
+        for value in 0..4 {
+            list.allocate(value, 1);
+        }
+
+        // Every limited Sweep continues where the previous one stopped and
+        // only the first Thread sweeping a Phase checks any Entries
+        let mut freed = Vec::new();
+        for phase in 2..=4 {
+            list.update_marks(phase);
+            freed.push(list.sweep(phase, Some(2), |_| {}));
+            assert_eq!(0, list.sweep(phase, Some(2), |_| {}));
+        }
+
+        assert_eq!(vec![2, 2, 0], freed);
+        assert_eq!(0, list.len());
+    }
+
+    #[test]
+    fn sweep_while_reading() {
+        let list = OverflowList::<usize>::new();
+        for value in 0..4 {
+            list.allocate(value, 1);
+        }
+
+        // Another Thread, that is still clearing the Marks, may read the Nodes
+        let reading = list.read();
+        list.update_marks(2);
+        assert_eq!(4, list.sweep(2, None, |_| {}));
+        assert_eq!(0, list.len());
+        assert!(!list.retired.load(atomic::Ordering::SeqCst).is_null());
+
+        drop(reading);
+        assert!(list.retired.load(atomic::Ordering::SeqCst).is_null());
+    }
+
+    #[test]
+    fn sweep_concurrent() {
+        let list = std::sync::Arc::new(OverflowList::<usize>::new());
+        let reclaimed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        for phase in 2..50 {
+            for value in 0..16 {
+                list.allocate(value, phase - 1);
+            }
+
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let list = list.clone();
+                    let reclaimed = reclaimed.clone();
+                    std::thread::spawn(move || {
+                        list.update_marks(phase);
+                        list.sweep(phase, None, |ptr| {
+                            reclaimed.lock().unwrap().push(ptr as usize);
+                        })
+                    })
+                })
+                .collect();
+            let freed: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+            // Every Node is reclaimed by exactly one of the Threads
+            assert_eq!(16, freed);
+            assert_eq!(0, list.len());
+            let mut reclaimed = reclaimed.lock().unwrap();
+            reclaimed.sort_unstable();
+            reclaimed.dedup();
+            assert_eq!(16, reclaimed.len());
+            reclaimed.clear();
+        }
+
+        assert!(list.retired.load(atomic::Ordering::SeqCst).is_null());
+    }
+}
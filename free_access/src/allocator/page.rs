@@ -1,34 +1,72 @@
-use std::sync::atomic;
+#[cfg(feature = "std")]
+use alloc::{boxed::Box, vec::Vec};
 
+#[cfg(feature = "std")]
+use crate::sync::atomic;
+use crate::Phase;
+
+/// The Metadata stored alongside every allocated Node, which is used by the
+/// Collector to determine whether or not a Node is still reachable
+///
+/// # Encoding
+/// The Marks are stored in a single `u64`, with the lowest Bit storing
+/// `marked` and the remaining 61 Bits, above the Reclaimed- and
+/// Occupied-Bit, storing the Phase, so the Phase must never exceed
+/// [`NodeMarks::MAX_PHASE`].
+///
+/// The second Bit is set once the Node was reclaimed by a Sweep, see
+/// [`PageNode::try_reclaim`]. It is not part of the NodeMarks themselves, so
+/// a reclaimed Node simply appears as unmarked, and it is cleared again once
+/// the Marks are moved into the next Phase.
+///
+/// The third Bit is set while the Node holds Data that was written by an
+/// Allocation and is kept across Phases, see [`PageNode::take_occupied`]
 #[derive(Debug, Clone, PartialEq)]
 pub struct NodeMarks {
+    /// Whether or not the Node was marked as reachable
     pub marked: bool,
+    /// The Phase in which these Marks were last updated
     pub phase: u64,
 }
 
+impl NodeMarks {
+    /// The Number of Bits the Phase is shifted by in the Encoding
+    const PHASE_SHIFT: u32 = 3;
+
+    /// The Bit in the Encoding that marks a Node as reclaimed in its Phase
+    pub(crate) const RECLAIMED_BIT: u64 = 0x02;
+
+    /// The Bit in the Encoding that marks a Node as holding Data
+    pub(crate) const OCCUPIED_BIT: u64 = 0x04;
+
+    /// The largest Phase that can be stored in the Marks
+    pub const MAX_PHASE: u64 = u64::MAX >> Self::PHASE_SHIFT;
+}
+
 impl From<u64> for NodeMarks {
     fn from(raw: u64) -> Self {
         let marked = raw & 0x01 == 0x01;
-        let phase = raw >> 8;
+        let phase = Phase::unpack(raw, Self::PHASE_SHIFT).get();
         Self { marked, phase }
     }
 }
 impl Into<u64> for NodeMarks {
     fn into(self) -> u64 {
         let marked_mask = if self.marked { 0x01 } else { 0x00 };
-        let result = ((self.phase << 8) & 0xffffffffffffff00) | marked_mask;
-        result
+        Phase::from(self.phase).pack(NodeMarks::PHASE_SHIFT) | marked_mask
     }
 }
 
 mod node;
 pub use node::PageNode;
 
+#[cfg(feature = "std")]
 pub struct Page<T> {
     pub nodes: Vec<PageNode<T>>,
     next: atomic::AtomicPtr<Self>,
 }
 
+#[cfg(feature = "std")]
 impl<T> Page<T> {
     pub fn new(size: usize) -> Self {
         let mut nodes = Vec::with_capacity(size);
@@ -38,7 +76,7 @@ impl<T> Page<T> {
 
         Self {
             nodes,
-            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            next: atomic::AtomicPtr::new(core::ptr::null_mut()),
         }
     }
 
@@ -51,12 +89,21 @@ impl<T> Page<T> {
     }
 }
 
+#[cfg(feature = "std")]
 pub struct PageList<T> {
     page_size: usize,
     head: *mut Page<T>,
+    /// A Hint for the last Page in the List, this always points to a Page
+    /// that is already linked into the List, but there may be more Pages
+    /// after it
+    tail: atomic::AtomicPtr<Page<T>>,
     page_count: atomic::AtomicU64,
+    /// The Page at which the Sweep of a Phase starts, in the Format
+    /// (Phase << 32 | Page-Index)
+    sweep_cursor: atomic::AtomicU64,
 }
 
+#[cfg(feature = "std")]
 impl<T> PageList<T> {
     pub fn new(page_size: usize) -> Self {
         let initial_page = Box::into_raw(Box::new(Page::new(page_size)));
@@ -64,36 +111,258 @@ impl<T> PageList<T> {
         Self {
             page_size,
             head: initial_page,
+            tail: atomic::AtomicPtr::new(initial_page),
             page_count: atomic::AtomicU64::new(1),
+            sweep_cursor: atomic::AtomicU64::new(0),
+        }
+    }
+
+    /// The Number of Nodes in every Page of the List
+    #[cfg(test)]
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// The Number of Pages currently in the List
+    pub fn page_count(&self) -> u64 {
+        self.page_count.load(atomic::Ordering::Acquire)
+    }
+
+    /// Appends a new empty Page to the End of the List and returns it.
+    ///
+    /// This can safely be called by multiple Threads at the same Time, every
+    /// new Page is linked in using a CAS on the `next` Ptr of the current
+    /// last Page, so no Page can get lost, and every Call increments the
+    /// `page_count` exactly once after its Page was linked in.
+    ///
+    /// # Tail-Hint
+    /// To avoid walking the entire List for every new Page, the search for
+    /// the last Page starts at the `tail` Hint. The Hint is only ever set to
+    /// Pages that are already linked in, so it can lag behind the real last
+    /// Page but never skip past it. If it is stale, the CAS on its `next` Ptr
+    /// fails and the remaining Pages are walked from there, like the full
+    /// Walk from the `head`, until the real last Page is found
+    #[tracing::instrument(skip(self))]
+    pub fn grow(&self) -> &Page<T> {
+        let new_page_ptr = Box::into_raw(Box::new(Page::new(self.page_size)));
+
+        let tail_hint = self.tail.load(atomic::Ordering::Acquire);
+        let mut current = unsafe { &*tail_hint };
+        loop {
+            match current.next.compare_exchange(
+                core::ptr::null_mut(),
+                new_page_ptr,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                Ok(_) => break,
+                Err(next) => {
+                    current = unsafe { &*next };
+                }
+            };
         }
+
+        // If another Thread already moved the Hint, it is either already
+        // further along or will be updated by that Thread
+        let _ = self.tail.compare_exchange(
+            tail_hint,
+            new_page_ptr,
+            atomic::Ordering::SeqCst,
+            atomic::Ordering::SeqCst,
+        );
+
+        // The Count is only increased after the Page was linked into the
+        // List, so there are always at least `page_count` Pages in the List
+        self.page_count.fetch_add(1, atomic::Ordering::AcqRel);
+
+        unsafe { &*new_page_ptr }
     }
 
-    fn get_page_index<'a>(&self, index: u64) -> Option<&'a Page<T>> {
+    /// Unlinks and frees the longest Run of Pages at the End of the List, for
+    /// which `is_free` returns true, and returns how many Pages were freed.
+    /// The first Page is never freed, so the List always contains at least
+    /// one Page.
+    ///
+    /// The `page_count` is decreased accordingly and the start of the current
+    /// Sweep is moved back to the first Page, if it pointed to a freed Page.
+    ///
+    /// # Safety
+    /// Unlike every other Operation on the List, this actually frees Memory,
+    /// so it must not run concurrently with `grow`, `get_page`, `claim_pages`
+    /// or any other Access to the Pages. Additionally no Ptr to a Node in the
+    /// freed Pages may be used afterwards, which also includes the Ptrs still
+    /// stored in any Allocation-Buffer
+    pub unsafe fn shrink<F>(&self, mut is_free: F) -> usize
+    where
+        F: FnMut(&Page<T>) -> bool,
+    {
+        let pages: Vec<*mut Page<T>> = {
+            let mut pages = Vec::new();
+            let mut current = self.head;
+            while !current.is_null() {
+                pages.push(current);
+                current = (*current).next.load(atomic::Ordering::Acquire);
+            }
+            pages
+        };
+
+        let mut keep = pages.len();
+        while keep > 1 && is_free(&*pages[keep - 1]) {
+            keep -= 1;
+        }
+        if keep == pages.len() {
+            return 0;
+        }
+
+        let last = pages[keep - 1];
+        (*last)
+            .next
+            .store(core::ptr::null_mut(), atomic::Ordering::Release);
+        self.tail.store(last, atomic::Ordering::Release);
+        self.page_count
+            .store(keep as u64, atomic::Ordering::Release);
+
+        let (phase, start) = Self::index_data(self.sweep_cursor.load(atomic::Ordering::Acquire));
+        if start >= keep as u64 {
+            self.sweep_cursor
+                .store(Self::index_value(phase, 0), atomic::Ordering::Release);
+        }
+
+        for page in pages[keep..].iter() {
+            drop(Box::from_raw(*page));
+        }
+
+        pages.len() - keep
+    }
+
+    /// Creates a new Cursor, which starts at the first Page
+    pub fn cursor(&self) -> PageCursor<T> {
+        PageCursor {
+            index: 0,
+            page: self.head,
+        }
+    }
+
+    /// Moves the Cursor to the Page at the given Index and returns it.
+    ///
+    /// Moving the Cursor forward only follows the `next` Ptrs from its current
+    /// Page, only moving it backwards requires walking the List from the
+    /// start again
+    fn seek<'a>(&self, cursor: &mut PageCursor<T>, index: u64) -> Option<&'a Page<T>> {
         if index >= self.page_count.load(atomic::Ordering::Acquire) {
             return None;
         }
 
-        let mut current = unsafe { &*self.head };
-        for _ in 0..index {
-            let next = current.next.load(atomic::Ordering::Acquire);
-            current = unsafe { &*next };
+        if index < cursor.index {
+            *cursor = self.cursor();
         }
 
-        Some(current)
+        while cursor.index < index {
+            let next = unsafe { &*cursor.page }
+                .next
+                .load(atomic::Ordering::Acquire);
+            if next.is_null() {
+                return None;
+            }
+
+            cursor.page = next;
+            cursor.index += 1;
+        }
+
+        Some(unsafe { &*cursor.page })
     }
 
     /// Returns the information about the Index in the Format (Phase, Index)
     fn index_data(index: u64) -> (u64, u64) {
-        ((index >> 32), (index & 0x00000000ffffffff))
+        (Phase::unpack(index, 32).get(), (index & 0x00000000ffffffff))
     }
 
-    #[tracing::instrument(skip(self, sweep_chunk_index))]
+    /// The inverse of [`index_data`](Self::index_data), the Phase is stored
+    /// in the upper 32 Bits, which is what limits [`Phase::MAX`]
+    fn index_value(phase: u64, index: u64) -> u64 {
+        debug_assert!(
+            Phase::fits(phase),
+            "The Phase {} does not fit into the Sweep-Index",
+            phase
+        );
+        Phase::from(phase).pack(32) | index
+    }
+
+    /// The Number of Pages that should be swept in a single Phase
+    fn sweep_limit(num_pages: u64, budget: Option<usize>) -> u64 {
+        match budget {
+            Some(budget) => core::cmp::min(num_pages, budget as u64),
+            None => num_pages,
+        }
+    }
+
+    /// Returns the Index of the Page at which the Sweep for the given Phase
+    /// should start.
+    ///
+    /// Without a Budget every Sweep starts at the first Page. With a Budget,
+    /// every Phase starts where the Sweep of the previous Phase stopped, so
+    /// that over enough Phases every Page will eventually be swept
+    #[tracing::instrument(skip(self))]
+    pub fn sweep_start(&self, local_phase: u64, budget: Option<usize>) -> u64 {
+        let budget = match budget {
+            Some(b) => b as u64,
+            None => return 0,
+        };
+
+        let mut current = self.sweep_cursor.load(atomic::Ordering::Acquire);
+        loop {
+            let (phase, start) = Self::index_data(current);
+            if Phase::from(phase) >= Phase::from(local_phase) {
+                return start;
+            }
+
+            let num_pages = self.page_count();
+            let n_start = (start + budget) % num_pages;
+            let new = Self::index_value(local_phase, n_start);
+
+            match self.sweep_cursor.compare_exchange(
+                current,
+                new,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                Ok(_) => return n_start,
+                Err(cur) => {
+                    current = cur;
+                }
+            };
+        }
+    }
+
+    /// Moves the Sweep-Chunk-Index into the given Phase, starting at the first
+    /// Page, which has to happen before any Page is handed out for that Phase.
+    ///
+    /// The Index is only ever moved forward, so a Thread that starts sweeping
+    /// late does not reset the Progress of the other Threads in the same
+    /// Phase and a Thread that is still in an older Phase can not move the
+    /// Index back
+    pub fn reset_sweep_chunks(&self, sweep_chunk_index: &atomic::AtomicU64, phase: u64) {
+        sweep_chunk_index.fetch_max(Self::index_value(phase, 0), atomic::Ordering::SeqCst);
+    }
+
+    /// Hands out the next Page that should be swept in the given Phase.
+    ///
+    /// # Parameters
+    /// * `cursor`: The Cursor of the current Thread for this Sweep, which is
+    /// used to find the claimed Page without walking the entire List again
+    /// * `start`: The Index of the first Page to sweep, see `sweep_start`
+    /// * `budget`: The maximum Number of Pages to sweep in this Phase
+    #[tracing::instrument(skip(self, cursor, sweep_chunk_index))]
     pub fn get_page<'a>(
         &self,
+        cursor: &mut PageCursor<T>,
         sweep_chunk_index: &atomic::AtomicU64,
         local_phase: u64,
+        start: u64,
+        budget: Option<usize>,
     ) -> Option<&'a Page<T>> {
-        let num_sweep_pages = self.page_count.load(atomic::Ordering::Acquire);
+        let num_pages = self.page_count();
+        let num_sweep_pages = Self::sweep_limit(num_pages, budget);
 
         let mut old;
         let mut new;
@@ -107,7 +376,7 @@ impl<T> PageList<T> {
                 return None;
             }
 
-            new = old + 1;
+            new = Self::index_value(phase, index + 1);
 
             if let Ok(_) = sweep_chunk_index.compare_exchange(
                 old,
@@ -115,9 +384,80 @@ impl<T> PageList<T> {
                 atomic::Ordering::SeqCst,
                 atomic::Ordering::SeqCst,
             ) {
-                return self.get_page_index(index);
+                return self.seek(cursor, (start + index) % num_pages);
+            }
+        }
+    }
+
+    /// Claims all the remaining Pages for the given Phase at once, in the
+    /// Order in which they are stored in the List, together with their Index
+    /// in the List. After this, `get_page` will not hand out any more Pages
+    /// for this Phase
+    #[tracing::instrument(skip(self, sweep_chunk_index))]
+    pub fn claim_pages<'a>(
+        &self,
+        sweep_chunk_index: &atomic::AtomicU64,
+        local_phase: u64,
+        start: u64,
+        budget: Option<usize>,
+    ) -> Vec<(u64, &'a Page<T>)> {
+        let num_pages = self.page_count();
+        let num_sweep_pages = Self::sweep_limit(num_pages, budget);
+
+        loop {
+            let old = sweep_chunk_index.load(atomic::Ordering::Acquire);
+            let (phase, index) = Self::index_data(old);
+            if index >= num_sweep_pages {
+                return Vec::new();
             }
+            if phase != local_phase {
+                return Vec::new();
+            }
+
+            let new = Self::index_value(phase, num_sweep_pages);
+
+            if sweep_chunk_index
+                .compare_exchange(old, new, atomic::Ordering::SeqCst, atomic::Ordering::SeqCst)
+                .is_ok()
+            {
+                let mut cursor = self.cursor();
+                return (index..num_sweep_pages)
+                    .filter_map(|i| {
+                        let page_index = (start + i) % num_pages;
+                        self.seek(&mut cursor, page_index)
+                            .map(|page| (page_index, page))
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    /// The total Number of Nodes the Pages can hold
+    pub fn node_capacity(&self) -> usize {
+        self.page_count() as usize * self.page_size
+    }
+
+    /// The Number of Nodes that are currently marked in the given Phase
+    pub fn marked_nodes(&self, phase: u64) -> usize {
+        let mut count = 0;
+
+        let mut current = unsafe { &*self.head };
+        loop {
+            count += current
+                .nodes
+                .iter()
+                .map(|n| n.load_marks())
+                .filter(|m| m.marked && m.phase == phase)
+                .count();
+
+            let next = current.next.load(atomic::Ordering::Acquire);
+            if next.is_null() {
+                break;
+            }
+            current = unsafe { &*next };
         }
+
+        count
     }
 
     #[tracing::instrument(skip(self))]
@@ -135,9 +475,72 @@ impl<T> PageList<T> {
     }
 }
 
+#[cfg(feature = "std")]
+impl<T> PageList<T> {
+    /// Creates an Iterator over all the Pages in the List
+    pub fn iter(&self) -> PageIter<'_, T> {
+        PageIter {
+            current: self.head,
+            _marker: core::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Drop for PageList<T> {
+    fn drop(&mut self) {
+        // The Data stored in the Nodes is not dropped here, because the List
+        // does not know which Nodes are currently in use
+        let mut current_ptr = self.head;
+        while !current_ptr.is_null() {
+            let current = unsafe { Box::from_raw(current_ptr) };
+            current_ptr = current.next.load(atomic::Ordering::Acquire);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 unsafe impl<T> Send for PageList<T> {}
+#[cfg(feature = "std")]
 unsafe impl<T> Sync for PageList<T> {}
 
+/// The Position of a single Thread in the PageList, see `PageList::seek`
+#[cfg(feature = "std")]
+pub struct PageCursor<T> {
+    index: u64,
+    page: *mut Page<T>,
+}
+
+#[cfg(feature = "std")]
+impl<T> PageCursor<T> {
+    /// The Index of the Page the Cursor currently points to
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+}
+
+#[cfg(feature = "std")]
+pub struct PageIter<'a, T> {
+    current: *mut Page<T>,
+    _marker: core::marker::PhantomData<&'a Page<T>>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> Iterator for PageIter<'a, T> {
+    type Item = &'a Page<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current.is_null() {
+            return None;
+        }
+
+        let current = unsafe { &*self.current };
+        self.current = current.next.load(atomic::Ordering::Acquire);
+
+        Some(current)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,6 +556,37 @@ mod tests {
 
         assert_eq!(marked, NodeMarks::from(serialized));
     }
+    #[test]
+    fn marks_max_phase() {
+        for marked in [false, true] {
+            let marks = NodeMarks {
+                marked,
+                phase: NodeMarks::MAX_PHASE,
+            };
+
+            let serialized: u64 = marks.clone().into();
+            assert_eq!(marks, NodeMarks::from(serialized));
+        }
+
+        let marks = NodeMarks {
+            marked: false,
+            phase: NodeMarks::MAX_PHASE - 1,
+        };
+        let serialized: u64 = marks.clone().into();
+        assert_eq!(marks, NodeMarks::from(serialized));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "does not fit")]
+    fn marks_phase_too_large() {
+        let marks = NodeMarks {
+            marked: false,
+            phase: NodeMarks::MAX_PHASE + 1,
+        };
+        let _: u64 = marks.into();
+    }
+
     #[test]
     fn marks_marked() {
         let marked = NodeMarks {
@@ -164,4 +598,213 @@ mod tests {
 
         assert_eq!(marked, NodeMarks::from(serialized));
     }
+
+    #[test]
+    fn claim_pages() {
+        let list = PageList::<usize>::new(4);
+        let sweep_chunk_index = atomic::AtomicU64::new(0);
+
+        assert_eq!(1, list.claim_pages(&sweep_chunk_index, 0, 0, None).len());
+        assert_eq!(0, list.claim_pages(&sweep_chunk_index, 0, 0, None).len());
+        assert!(list
+            .get_page(&mut list.cursor(), &sweep_chunk_index, 0, 0, None)
+            .is_none());
+    }
+
+    /// Creates a PageList that already contains `count` Pages
+    fn list_with_pages(page_size: usize, count: u64) -> PageList<usize> {
+        let list = PageList::new(page_size);
+        for _ in 1..count {
+            list.grow();
+        }
+
+        list
+    }
+
+    #[test]
+    fn sweep_budget() {
+        let list = list_with_pages(4, 4);
+        let sweep_chunk_index = atomic::AtomicU64::new(0);
+
+        let mut swept = Vec::new();
+        for phase in 1..=4 {
+            list.reset_sweep_chunks(&sweep_chunk_index, phase);
+            let start = list.sweep_start(phase, Some(1));
+            assert_eq!(start, list.sweep_start(phase, Some(1)));
+
+            let mut pages = 0;
+            let mut cursor = list.cursor();
+            while let Some(page) =
+                list.get_page(&mut cursor, &sweep_chunk_index, phase, start, Some(1))
+            {
+                swept.push(page as *const Page<usize>);
+                pages += 1;
+            }
+            assert_eq!(1, pages);
+        }
+
+        swept.sort();
+        swept.dedup();
+        assert_eq!(4, swept.len());
+    }
+
+    #[test]
+    fn sweep_each_page_once() {
+        let list = list_with_pages(4, 5);
+        let sweep_chunk_index = atomic::AtomicU64::new(0);
+
+        for phase in 1..=3 {
+            list.reset_sweep_chunks(&sweep_chunk_index, phase);
+
+            // Two Threads sweeping the same Phase, taking turns
+            let mut first = list.cursor();
+            let mut second = list.cursor();
+
+            let mut swept = Vec::new();
+            loop {
+                let mut progress = false;
+                for cursor in [&mut first, &mut second] {
+                    if let Some(page) = list.get_page(cursor, &sweep_chunk_index, phase, 0, None) {
+                        swept.push(page as *const Page<usize>);
+                        progress = true;
+                    }
+                }
+                if !progress {
+                    break;
+                }
+            }
+
+            let expected: Vec<_> = list.iter().map(|p| p as *const Page<usize>).collect();
+            assert_eq!(expected.len(), swept.len());
+            swept.sort();
+            swept.dedup();
+            let mut expected = expected;
+            expected.sort();
+            assert_eq!(expected, swept);
+        }
+    }
+
+    #[test]
+    fn reset_sweep_chunks() {
+        let list = list_with_pages(4, 3);
+        let sweep_chunk_index = atomic::AtomicU64::new(0);
+        let expected: Vec<_> = list.iter().map(|p| p as *const Page<usize>).collect();
+
+        for phase in 1..=2 {
+            list.reset_sweep_chunks(&sweep_chunk_index, phase);
+
+            let mut cursor = list.cursor();
+            let first = list
+                .get_page(&mut cursor, &sweep_chunk_index, phase, 0, None)
+                .unwrap();
+
+            // A Thread joining the Sweep late does not hand out the first
+            // Page again, neither does one that is still in the old Phase
+            list.reset_sweep_chunks(&sweep_chunk_index, phase);
+            list.reset_sweep_chunks(&sweep_chunk_index, phase - 1);
+
+            let mut swept = vec![first as *const Page<usize>];
+            while let Some(page) = list.get_page(&mut cursor, &sweep_chunk_index, phase, 0, None) {
+                swept.push(page as *const Page<usize>);
+            }
+
+            assert_eq!(expected, swept);
+        }
+    }
+
+    #[test]
+    fn grow() {
+        let list = PageList::<usize>::new(4);
+        assert_eq!(4, list.node_capacity());
+
+        let page = list.grow() as *const Page<usize>;
+        assert_eq!(2, list.page_count());
+        assert_eq!(8, list.node_capacity());
+        assert_eq!(page, list.iter().nth(1).unwrap() as *const Page<usize>);
+    }
+
+    #[test]
+    fn grow_concurrent() {
+        let list = std::sync::Arc::new(PageList::<usize>::new(4));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let list = list.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..16 {
+                        list.grow();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(1 + 8 * 16, list.page_count());
+
+        let mut linked = 1;
+        let mut current = unsafe { &*list.head };
+        while !current.next.load(atomic::Ordering::Acquire).is_null() {
+            current = unsafe { &*current.next.load(atomic::Ordering::Acquire) };
+            linked += 1;
+        }
+        assert_eq!(list.page_count(), linked);
+    }
+
+    #[test]
+    fn shrink() {
+        let list = list_with_pages(4, 5);
+        let pages: Vec<_> = list.iter().map(|p| p as *const Page<usize>).collect();
+
+        // Only the trailing Run of free Pages is freed
+        let free = [pages[1], pages[3], pages[4]];
+        let freed = unsafe { list.shrink(|page| free.contains(&(page as *const Page<usize>))) };
+        assert_eq!(2, freed);
+        assert_eq!(3, list.page_count());
+        assert_eq!(3, list.iter().count());
+        assert_eq!(
+            pages[2] as *mut Page<usize>,
+            list.tail.load(atomic::Ordering::Acquire)
+        );
+
+        // New Pages are appended after the new last Page
+        let page = list.grow() as *const Page<usize>;
+        assert_eq!(4, list.page_count());
+        assert_eq!(page, list.iter().last().unwrap() as *const Page<usize>);
+
+        // The first Page is always kept
+        assert_eq!(3, unsafe { list.shrink(|_| true) });
+        assert_eq!(1, list.page_count());
+        assert_eq!(1, list.iter().count());
+    }
+
+    #[test]
+    fn shrink_resets_sweep_start() {
+        let list = list_with_pages(4, 4);
+        list.sweep_cursor.store(
+            PageList::<usize>::index_value(2, 3),
+            atomic::Ordering::Release,
+        );
+
+        assert_eq!(3, unsafe { list.shrink(|_| true) });
+        assert_eq!(
+            PageList::<usize>::index_value(2, 0),
+            list.sweep_cursor.load(atomic::Ordering::Acquire)
+        );
+    }
+
+    #[test]
+    fn grow_stale_tail() {
+        let list = list_with_pages(4, 3);
+        let last = list.iter().last().unwrap() as *const Page<usize> as *mut Page<usize>;
+        assert_eq!(last, list.tail.load(atomic::Ordering::Acquire));
+
+        // A stale Hint still appends the new Page at the real End
+        list.tail.store(list.head, atomic::Ordering::Release);
+        let page = list.grow() as *const Page<usize>;
+
+        assert_eq!(4, list.iter().count());
+        assert_eq!(page, list.iter().last().unwrap() as *const Page<usize>);
+    }
 }
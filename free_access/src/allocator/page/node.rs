@@ -1,16 +1,48 @@
-use std::{mem::MaybeUninit, sync::atomic};
+use core::{cell::UnsafeCell, mem::MaybeUninit};
+
+use crate::{sync::atomic, Phase};
 
 use memoffset::offset_of;
 
 use super::NodeMarks;
 
+/// A single Slot in a Page, which stores the Marks of the Node right in
+/// front of its Data
+///
+/// # Alignment
+/// As the Node is `repr(C)`, the Data is padded to its own Alignment, so it is
+/// always aligned correctly for `T`, even if `T` requires a stronger Alignment
+/// than the Marks. To keep Nodes on separate Cache-Lines, the Data can simply
+/// be wrapped in a `#[repr(align(64))]` Type, which then also aligns the
+/// entire Node to 64 Bytes
+///
+/// # Miri
+/// The Ptrs to the Data are derived from Ptrs to the whole Node, without
+/// going through Integers, so converting between them keeps the Provenance
+/// and the Tests prefixed with `miri_` can be run under Miri
+/// ```text
+/// cargo +nightly miri test miri_
+/// ```
 #[repr(C)]
 pub struct PageNode<T> {
     marker: atomic::AtomicU64,
-    data: MaybeUninit<T>,
+    /// The Data is written through the Ptrs handed out by the Allocator,
+    /// while the Node itself is only ever shared
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+// The Data is only ever written by the single Thread that obtained the Node
+// from the Allocator, so sharing the Node is as safe as sharing the Data
+unsafe impl<T: Sync> Sync for PageNode<T> {}
+
+impl<T> Default for PageNode<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T> PageNode<T> {
+    /// Creates a new unmarked Node in Phase 0, without any Data
     pub fn new() -> Self {
         let marks = NodeMarks {
             phase: 0,
@@ -20,7 +52,7 @@ impl<T> PageNode<T> {
 
         Self {
             marker: atomic::AtomicU64::new(mark_value),
-            data: MaybeUninit::uninit(),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
         }
     }
 
@@ -29,67 +61,175 @@ impl<T> PageNode<T> {
     }
 
     /// Gets a Ptr to the data field of this node
+    ///
+    /// # Safety
+    /// The Data may not be initialized, so it must only be read after it was
+    /// written through this Ptr
     pub unsafe fn get_data_ptr(&self) -> *mut T {
-        let base_ptr = self as *const Self as usize;
-        (base_ptr + Self::data_offset()) as *mut T
+        let base_ptr = self as *const Self;
+        core::ptr::addr_of!((*base_ptr).data) as *mut T
     }
 
     /// Converts the given DataPtr back to a Reference to the underlying
-    /// PageNode
+    /// PageNode.
+    ///
+    /// # Safety
+    /// The Ptr must have been obtained from [`get_data_ptr`](Self::get_data_ptr)
+    /// and must not carry a Tag, otherwise the calculated Position of the
+    /// PageNode would be off by the Tag
     pub unsafe fn from_data_ptr<'a>(ptr: *mut T) -> &'a Self {
-        let base_ptr = ((ptr as usize) - Self::data_offset()) as *mut T;
-        &*(base_ptr as *mut Self)
+        debug_assert!(
+            (ptr as usize) & crate::tagging::tag_mask::<T>() == 0,
+            "The Data-Ptr {:p} is misaligned or still tagged",
+            ptr
+        );
+
+        let base_ptr = (ptr as *mut u8).sub(Self::data_offset()) as *mut Self;
+        debug_assert!(
+            (base_ptr as usize) & (core::mem::align_of::<Self>() - 1) == 0,
+            "The PageNode for the Data-Ptr {:p} is misaligned",
+            ptr
+        );
+
+        &*base_ptr
     }
 
+    /// Loads the current Marks of the Node
     pub fn load_marks(&self) -> NodeMarks {
         let raw_marks = self.marker.load(atomic::Ordering::Acquire);
         raw_marks.into()
     }
 
+    /// Atomically replaces the Marks of the Node, if they are still equal to
+    /// `expected`, otherwise the Marks that were observed instead are returned.
+    ///
+    /// The Reclaimed-Bit is not part of the Marks, so it is ignored when
+    /// comparing them and kept as long as the Phase stays the same, otherwise
+    /// a Node that was reclaimed and handed out again could never be marked.
+    /// The Occupied-Bit is ignored as well and always kept
     #[tracing::instrument(skip(self))]
-    pub fn update_marks(&self, expected: NodeMarks, n_marks: NodeMarks) -> Result<(), ()> {
-        let current: u64 = expected.into();
+    pub fn update_marks(&self, expected: NodeMarks, n_marks: NodeMarks) -> Result<(), NodeMarks> {
+        let same_phase = expected.phase == n_marks.phase;
+        let expected: u64 = expected.into();
         let new: u64 = n_marks.into();
 
-        match self.marker.compare_exchange(
-            current,
-            new,
-            atomic::Ordering::SeqCst,
-            atomic::Ordering::SeqCst,
-        ) {
-            Ok(_) => Ok(()),
-            Err(_) => Err(()),
+        let mut current = self.marker.load(atomic::Ordering::Acquire);
+        loop {
+            if current & !(NodeMarks::RECLAIMED_BIT | NodeMarks::OCCUPIED_BIT) != expected {
+                return Err(current.into());
+            }
+
+            let reclaimed = if same_phase {
+                current & NodeMarks::RECLAIMED_BIT
+            } else {
+                0
+            };
+            let occupied = current & NodeMarks::OCCUPIED_BIT;
+
+            match self.marker.compare_exchange(
+                current,
+                new | reclaimed | occupied,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(actual) => {
+                    current = actual;
+                }
+            };
         }
     }
 
-    #[tracing::instrument(skip(self))]
-    pub fn clear_marks(&self, n_phase: u64) {
-        let previous_marks_raw = self.marker.load(atomic::Ordering::Acquire);
-        let previous_marks = NodeMarks::from(previous_marks_raw);
-        if previous_marks.phase >= n_phase {
-            return;
+    /// Attempts to reclaim this Node in the given Phase, which only succeeds
+    /// for a single Caller and only if the Node is unmarked in that Phase.
+    ///
+    /// This atomically sets the Reclaimed-Bit of the Marks, so even if
+    /// multiple Threads end up sweeping the same Page, every Node is only
+    /// freed once per Phase. The Bit is cleared once the Marks are moved into
+    /// the next Phase
+    ///
+    /// # Returns
+    /// * Some(ptr): The Ptr to the Data of the Node, which is now free
+    /// * None: The Node is marked, belongs to another Phase or was already
+    ///   reclaimed
+    pub fn try_reclaim(&self, phase: u64) -> Option<*mut T> {
+        let mut current = self.marker.load(atomic::Ordering::Acquire);
+        loop {
+            let marks = NodeMarks::from(current);
+            if current & NodeMarks::RECLAIMED_BIT != 0 || marks.marked || marks.phase != phase {
+                return None;
+            }
+
+            match self.marker.compare_exchange(
+                current,
+                current | NodeMarks::RECLAIMED_BIT,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                Ok(_) => return Some(unsafe { self.get_data_ptr() }),
+                Err(actual) => {
+                    current = actual;
+                }
+            };
         }
+    }
+
+    /// Marks the Node as holding Data, which has to be done right after the
+    /// Data was written by an Allocation
+    pub fn set_occupied(&self) {
+        self.marker
+            .fetch_or(NodeMarks::OCCUPIED_BIT, atomic::Ordering::SeqCst);
+    }
+
+    /// Marks the Node as no longer holding Data.
+    ///
+    /// Free Nodes are reclaimed again by every Sweep, so this is used to tell
+    /// apart the Nodes that actually held Data from the ones that were free
+    /// already and only a single Caller ever observes the Data of a Node.
+    ///
+    /// # Returns
+    /// Whether or not the Node held Data
+    pub fn take_occupied(&self) -> bool {
+        let previous = self
+            .marker
+            .fetch_and(!NodeMarks::OCCUPIED_BIT, atomic::Ordering::SeqCst);
+        previous & NodeMarks::OCCUPIED_BIT != 0
+    }
 
+    /// Moves the Marks into the given Phase and unmarks the Node, unless the
+    /// Marks already belong to that or a newer Phase
+    #[tracing::instrument(skip(self))]
+    pub fn clear_marks(&self, n_phase: u64) {
         let new_marks = NodeMarks {
             phase: n_phase,
             marked: false,
         };
-
         let new_mark_value: u64 = new_marks.into();
 
-        match self.marker.compare_exchange(
-            previous_marks_raw,
-            new_mark_value,
-            atomic::Ordering::SeqCst,
-            atomic::Ordering::SeqCst,
-        ) {
-            Ok(_) => {}
-            Err(previous) => {
-                tracing::debug!("Failed clearing Marker");
-                tracing::debug!("Current: {:#064b}", previous);
-                tracing::debug!("Expected: {:#064b}", previous_marks_raw);
+        // The Occupied-Bit may be set concurrently by an Allocation, which
+        // must neither be lost nor keep the Marks in the old Phase
+        let mut previous_marks_raw = self.marker.load(atomic::Ordering::Acquire);
+        loop {
+            let previous_marks = NodeMarks::from(previous_marks_raw);
+            if Phase::from(previous_marks.phase) >= Phase::from(n_phase) {
+                return;
             }
-        };
+
+            match self.marker.compare_exchange(
+                previous_marks_raw,
+                new_mark_value | (previous_marks_raw & NodeMarks::OCCUPIED_BIT),
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                Ok(_) => return,
+                Err(previous) => {
+                    tracing::debug!("Retrying to clear Marker");
+                    tracing::debug!("Current: {:#064b}", previous);
+                    tracing::debug!("Expected: {:#064b}", previous_marks_raw);
+                    previous_marks_raw = previous;
+                }
+            };
+        }
     }
 }
 
@@ -97,6 +237,155 @@ impl<T> PageNode<T> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn zero_sized_data_ptr() {
+        let node = PageNode::<()>::new();
+
+        let data_ptr = unsafe { node.get_data_ptr() };
+        unsafe { data_ptr.write(()) };
+
+        let from_data = unsafe { PageNode::from_data_ptr(data_ptr) };
+        assert!(std::ptr::eq(&node, from_data));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "misaligned or still tagged")]
+    fn from_tagged_data_ptr() {
+        let node = PageNode::<u64>::new();
+
+        let data_ptr = unsafe { node.get_data_ptr() };
+        let tagged = crate::tagging::tag_ptr(data_ptr, 0b1);
+
+        unsafe { PageNode::from_data_ptr(tagged) };
+    }
+
+    #[test]
+    fn try_reclaim() {
+        let node = PageNode::<u64>::new();
+        let data_ptr = unsafe { node.get_data_ptr() };
+
+        // Only unmarked Nodes of the same Phase can be reclaimed
+        assert_eq!(None, node.try_reclaim(1));
+        node.clear_marks(1);
+        assert_eq!(Some(data_ptr), node.try_reclaim(1));
+        assert_eq!(None, node.try_reclaim(1));
+
+        // The reclaimed Node still appears as unmarked
+        assert_eq!(
+            NodeMarks {
+                phase: 1,
+                marked: false,
+            },
+            node.load_marks()
+        );
+
+        // A reclaimed Node, that was handed out again, can still be marked,
+        // but is not reclaimed a second Time in the same Phase
+        node.update_marks(
+            NodeMarks {
+                phase: 1,
+                marked: false,
+            },
+            NodeMarks {
+                phase: 1,
+                marked: true,
+            },
+        )
+        .unwrap();
+        node.update_marks(
+            NodeMarks {
+                phase: 1,
+                marked: true,
+            },
+            NodeMarks {
+                phase: 1,
+                marked: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(None, node.try_reclaim(1));
+
+        node.clear_marks(2);
+        node.update_marks(
+            NodeMarks {
+                phase: 2,
+                marked: false,
+            },
+            NodeMarks {
+                phase: 2,
+                marked: true,
+            },
+        )
+        .unwrap();
+        assert_eq!(None, node.try_reclaim(2));
+
+        node.clear_marks(3);
+        assert_eq!(Some(data_ptr), node.try_reclaim(3));
+    }
+
+    #[test]
+    fn try_reclaim_concurrent() {
+        let nodes: std::sync::Arc<Vec<_>> =
+            std::sync::Arc::new((0..64).map(|_| PageNode::<u64>::new()).collect());
+        for node in nodes.iter() {
+            node.clear_marks(1);
+        }
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let nodes = nodes.clone();
+                std::thread::spawn(move || {
+                    nodes
+                        .iter()
+                        .filter_map(|n| n.try_reclaim(1))
+                        .map(|p| p as usize)
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut reclaimed: Vec<_> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        reclaimed.sort_unstable();
+        reclaimed.dedup();
+        assert_eq!(64, reclaimed.len());
+    }
+
+    #[test]
+    fn occupied() {
+        let node = PageNode::<usize>::new();
+        assert!(!node.take_occupied());
+
+        node.set_occupied();
+        node.clear_marks(1);
+        node.update_marks(
+            NodeMarks {
+                phase: 1,
+                marked: false,
+            },
+            NodeMarks {
+                phase: 1,
+                marked: true,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            NodeMarks {
+                phase: 1,
+                marked: true,
+            },
+            node.load_marks()
+        );
+
+        node.clear_marks(2);
+        assert!(node.try_reclaim(2).is_some());
+        assert!(node.take_occupied());
+        assert!(!node.take_occupied());
+    }
+
     #[test]
     fn ptr_stuff() {
         let node = PageNode::<usize>::new();
@@ -117,4 +406,45 @@ mod tests {
             loaded_node.marker.load(atomic::Ordering::SeqCst)
         );
     }
+
+    #[test]
+    fn miri_data_ptr_round_trip() {
+        let nodes: Vec<PageNode<usize>> = (0..4).map(|_| PageNode::new()).collect();
+
+        for (value, node) in nodes.iter().enumerate() {
+            let data_ptr = unsafe { node.get_data_ptr() };
+            unsafe { data_ptr.write(value) };
+
+            let loaded_node = unsafe { PageNode::from_data_ptr(data_ptr) };
+            assert!(core::ptr::eq(node, loaded_node));
+            assert_eq!(Some(data_ptr), loaded_node.try_reclaim(0));
+        }
+
+        for (value, node) in nodes.iter().enumerate() {
+            assert_eq!(value, unsafe { node.get_data_ptr().read() });
+        }
+    }
+
+    #[test]
+    fn data_alignment() {
+        #[repr(align(64))]
+        struct CacheLine(#[allow(dead_code)] u8);
+
+        assert_eq!(64, PageNode::<CacheLine>::data_offset());
+        assert_eq!(128, core::mem::size_of::<PageNode<CacheLine>>());
+        assert_eq!(
+            0,
+            PageNode::<u128>::data_offset() % core::mem::align_of::<u128>()
+        );
+
+        let nodes: Vec<PageNode<CacheLine>> = (0..4).map(|_| PageNode::new()).collect();
+        for node in nodes.iter() {
+            let data_ptr = unsafe { node.get_data_ptr() };
+            assert_eq!(0, data_ptr as usize % 64);
+
+            // The Padding in front of the Data is skipped again
+            let loaded_node = unsafe { PageNode::from_data_ptr(data_ptr) };
+            assert!(core::ptr::eq(node, loaded_node));
+        }
+    }
 }
@@ -2,9 +2,23 @@
 //!
 //! # Strucure
 //! The Pool consists of a doubly-linked List of Nodes, which will never be
-//! deallocated to make sure that we never access a removed Node.
+//! deallocated while the Pool is alive to make sure that we never access a
+//! removed Node. Only once the Pool itself is dropped, all the Nodes and the
+//! Data still stored in them are freed.
 //! Instead a Node can be in one of three Stages
 //!
+//! ## Lifecycle
+//! Elements are always inserted for the current Phase of the Pool. Once the
+//! Phase of the Pool is updated, all the Elements of older Phases are stale
+//! and will never be handed out again. They are either overwritten by the
+//! next Insert, dropped by the next Pop that encounters them or dropped
+//! eagerly by [`Pool::remove_stale`].
+//!
+//! Every Element is dropped exactly once, either by the Caller after it was
+//! popped or drained, or by the Pool once it is overwritten, found to be stale
+//! or the Pool itself is dropped. Empty Nodes never hold an Element and are
+//! therefore never dropped.
+//!
 //! ## Stages
 //! * Empty: The Node contains no Data
 //! * Accessed: The Node is currently being modified, this prevents multiple
@@ -53,6 +67,11 @@
 //!             Node.Phase = local_phase
 //!             Node.State = SET
 //!             return
+//!
+//!     if any Node was skipped because of a failed CAS or ACCESSED:
+//!         scan again (up to a few Times)
+//!     append new Node with data
+//!         if the Tail changed in the meantime: scan again first
 //! ```
 //!
 //! ### Pop
@@ -76,8 +95,34 @@
 //!             Node.State = EMPTY
 //!             return Data
 //! ```
+//!
+//! ### Drain
+//! ```pseudo
+//! drain(local_phase):
+//!     if pool.Phase != local_phase:
+//!         return []
+//!
+//!     for each Node in the Stack:
+//!         if Node.State == SET && Node.Phase == local_phase:
+//!             if !CAS(Node.State, SET, ACCESSED):
+//!                 continue
+//!             if Node.Phase != local_phase:
+//!                 Node.State = SET
+//!                 continue
+//!             if pool.Phase != local_phase:
+//!                 Node.State = SET
+//!                 return result
+//!
+//!             result.push(Node.Data)
+//!             Node.State = EMPTY
+//!     return result
+//! ```
+
+use core::{cell::UnsafeCell, mem::MaybeUninit};
 
-use std::{cell::UnsafeCell, mem::MaybeUninit, sync::atomic};
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{sync::atomic, Phase};
 
 enum State {
     Empty,
@@ -103,25 +148,73 @@ impl State {
     }
 }
 
+/// How often [`Pool::insert`] scans the Pool again, if it could not reuse any
+/// Node because of Contention, before it appends a new Node anyway
+const MAX_RESCANS: usize = 4;
+
+/// The Outcome of a single Scan of the Pool for a reusable Node
+enum Reuse<T> {
+    /// The Insert finished, either with the Data stored in a Node or because
+    /// the Pool moved to a different Phase
+    Done(Result<(), InvalidPhase>),
+    /// No Node could be reused
+    Full {
+        data: T,
+        /// The last Node of the Pool at the Time of the Scan
+        last: *mut Node<T>,
+        /// Whether any Node was skipped, because it was accessed by another
+        /// Thread at the same Time
+        contended: bool,
+    },
+}
+
+/// Aligns and pads the wrapped Value to an entire Cache-Line, so it never
+/// shares a Cache-Line with any other Value
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+impl<T> core::ops::Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// A single Node of the Pool.
+///
+/// # Layout
+/// The Atomics of every Node are padded to their own Cache-Line, so Threads
+/// accessing different Nodes at the same Time do not contend on a shared
+/// Cache-Line, while the Data of the Node is stored unpadded after them
+#[repr(C)]
 struct Node<T> {
+    atomics: CachePadded<NodeAtomics<T>>,
     data: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// The Atomics of a [`Node`], which are accessed by every Thread scanning the
+/// Pool
+struct NodeAtomics<T> {
     state: atomic::AtomicU8,
-    next: atomic::AtomicPtr<Self>,
+    next: atomic::AtomicPtr<Node<T>>,
     phase: atomic::AtomicU64,
 }
 
 impl<T> Node<T> {
     pub fn new() -> Self {
         Self {
+            atomics: CachePadded(NodeAtomics {
+                state: atomic::AtomicU8::new(State::Empty.to_u8()),
+                next: atomic::AtomicPtr::new(core::ptr::null_mut()),
+                phase: atomic::AtomicU64::new(0),
+            }),
             data: UnsafeCell::new(MaybeUninit::uninit()),
-            state: atomic::AtomicU8::new(State::Empty.to_u8()),
-            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
-            phase: atomic::AtomicU64::new(0),
         }
     }
 
     pub fn load_state(&self, order: atomic::Ordering) -> State {
-        let raw = self.state.load(order);
+        let raw = self.atomics.state.load(order);
         State::from_u8(raw).unwrap()
     }
 }
@@ -135,30 +228,69 @@ pub struct Pool<T> {
     phase: atomic::AtomicU64,
     /// The First Element of the List of Nodes
     start: *mut Node<T>,
+    /// Incremented whenever new Data or a new Phase becomes visible, which
+    /// lets waiting Poppers skip rescanning a Pool that did not change
+    version: atomic::AtomicU64,
+    /// Whether the Pool supports [`Pool::pop_blocking`], only then `version`
+    /// is maintained, so the other Pools don't pay for it on every Insert
+    blocking: bool,
 }
 
+/// The Reason why no Element could be popped from a [`Pool`]
 #[derive(Debug, PartialEq)]
 pub enum PopError {
+    /// The Pool contains no Element for the requested Phase
     Empty,
+    /// The Pool is in a different Phase than the requested one
     InvalidPhase,
 }
 
+/// Returned by an Operation that requires the [`Pool`] to be in a specific
+/// Phase, once it is in a different one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidPhase;
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Pool<T> {
+    /// Creates a new empty Pool in Phase 0
     pub fn new() -> Self {
+        Self::with_blocking(false)
+    }
+
+    /// Creates a new empty Pool in Phase 0, that supports waiting for new
+    /// Elements using [`Pool::pop_blocking`].
+    ///
+    /// Every successful Insert and [`Pool::update_phase_notify`] has to
+    /// signal the waiting Threads, which is an additional atomic Operation
+    /// that a Pool created with [`Pool::new`] skips
+    pub fn new_blocking() -> Self {
+        Self::with_blocking(true)
+    }
+
+    fn with_blocking(blocking: bool) -> Self {
         let initial_node_ptr = Box::into_raw(Box::new(Node::new()));
 
         Self {
             phase: atomic::AtomicU64::new(0),
             start: initial_node_ptr,
+            version: atomic::AtomicU64::new(0),
+            blocking,
         }
     }
 
+    /// Moves the Pool into the given Phase, which fails if the Pool is
+    /// already in that or a newer Phase
     #[tracing::instrument(skip(self))]
-    pub fn update_phase(&self, n_phase: u64) -> Result<(), ()> {
+    pub fn update_phase(&self, n_phase: u64) -> Result<(), InvalidPhase> {
         let mut previous = self.phase.load(atomic::Ordering::Acquire);
         loop {
-            if previous >= n_phase {
-                return Err(());
+            if Phase::from(previous) >= Phase::from(n_phase) {
+                return Err(InvalidPhase);
             }
 
             match self.phase.compare_exchange(
@@ -177,60 +309,196 @@ impl<T> Pool<T> {
         }
     }
 
-    pub fn insert(&self, data: T, phase: u64) -> Result<(), ()> {
+    /// Moves the Pool into the given Phase, like [`Pool::update_phase`], and
+    /// wakes up any Thread waiting in [`Pool::pop_blocking`] so it can check
+    /// the new Phase
+    pub fn update_phase_notify(&self, n_phase: u64) -> Result<(), InvalidPhase> {
+        let result = self.update_phase(n_phase);
+        if result.is_ok() {
+            self.notify();
+        }
+        result
+    }
+
+    /// Inserts the Data for the given Phase, which fails if the Pool is not
+    /// in that Phase (anymore)
+    pub fn insert(&self, data: T, phase: u64) -> Result<(), InvalidPhase> {
+        let result = self.insert_data(data, phase);
+        if result.is_ok() {
+            self.notify();
+        }
+        result
+    }
+
+    /// Signals Threads waiting in [`Pool::pop_blocking`] that the Pool changed,
+    /// which is skipped if the Pool does not support blocking
+    fn notify(&self) {
+        if self.blocking {
+            self.version.fetch_add(1, atomic::Ordering::SeqCst);
+        }
+    }
+
+    fn insert_data(&self, data: T, phase: u64) -> Result<(), InvalidPhase> {
         if self.phase.load(atomic::Ordering::Acquire) != phase {
-            return Err(());
+            return Err(InvalidPhase);
         }
 
-        let mut latest = unsafe { &*self.start };
+        let mut data = data;
+        let mut rescans = 0;
+        let mut latest = loop {
+            match self.try_reuse(data, phase) {
+                Reuse::Done(result) => return result,
+                Reuse::Full {
+                    data: returned,
+                    last,
+                    contended,
+                } => {
+                    data = returned;
+
+                    // A contended Node may become reusable in a Moment, so
+                    // the Pool only grows once a Scan found nothing without
+                    // any Contention or we already rescanned a few Times
+                    if !contended || rescans >= MAX_RESCANS {
+                        break unsafe { &*last };
+                    }
+                    rescans += 1;
+                }
+            };
+        };
+
+        let next_node = Node::new();
+        next_node
+            .atomics
+            .state
+            .store(State::Accessed.to_u8(), atomic::Ordering::Release);
+        next_node
+            .atomics
+            .phase
+            .store(phase, atomic::Ordering::Release);
+        unsafe { (next_node.data.get() as *mut T).write(data) };
+        let next_ptr = Box::into_raw(Box::new(next_node));
+
+        loop {
+            match latest.atomics.next.compare_exchange(
+                core::ptr::null_mut(),
+                next_ptr,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                Ok(_) => {
+                    let next_node = unsafe { &*next_ptr };
+                    if self.phase.load(atomic::Ordering::Acquire) != phase {
+                        let data_ptr = next_node.data.get();
+                        let old = unsafe { data_ptr.replace(MaybeUninit::uninit()) };
+                        drop(unsafe { old.assume_init() });
+
+                        next_node
+                            .atomics
+                            .state
+                            .store(State::Empty.to_u8(), atomic::Ordering::Release);
+                        return Err(InvalidPhase);
+                    }
+
+                    next_node
+                        .atomics
+                        .state
+                        .store(State::Set.to_u8(), atomic::Ordering::Release);
+                    return Ok(());
+                }
+                Err(_) => {
+                    // Another Thread appended a Node in the meantime, which
+                    // may also have freed up a Node, so we scan again before
+                    // appending our unpublished Node after the new Tail
+                    let data = unsafe { (*next_ptr).data.get().read().assume_init() };
+                    match self.try_reuse(data, phase) {
+                        Reuse::Done(result) => {
+                            // The Node was never published, so we can simply
+                            // free it again
+                            drop(unsafe { Box::from_raw(next_ptr) });
+                            return result;
+                        }
+                        Reuse::Full { data, last, .. } => {
+                            unsafe { ((*next_ptr).data.get() as *mut T).write(data) };
+                            latest = unsafe { &*last };
+                        }
+                    };
+                }
+            };
+        }
+    }
+
+    /// Scans the entire Pool once for a Node that can be reused for the Data,
+    /// either because it is Empty or because it holds stale Data from an older
+    /// Phase.
+    ///
+    /// If no Node could be reused, the Data is returned together with the last
+    /// Node of the Pool and whether any Node could not be checked, because
+    /// another Thread was accessing it at the same Time
+    fn try_reuse(&self, data: T, phase: u64) -> Reuse<T> {
+        if self.phase.load(atomic::Ordering::Acquire) != phase {
+            drop(data);
+            return Reuse::Done(Err(InvalidPhase));
+        }
+
+        let mut last = self.start;
+        let mut contended = false;
 
-        // Attempt to find
         for current_ptr in self.iter() {
+            last = current_ptr;
             let current = unsafe { &*current_ptr };
 
             match current.load_state(atomic::Ordering::Acquire) {
                 State::Empty => {
-                    if let Err(_) = current.state.compare_exchange(
+                    if let Err(_) = current.atomics.state.compare_exchange(
                         State::Empty.to_u8(),
                         State::Accessed.to_u8(),
                         atomic::Ordering::SeqCst,
                         atomic::Ordering::SeqCst,
                     ) {
+                        contended = true;
                         continue;
                     }
 
                     if self.phase.load(atomic::Ordering::Acquire) != phase {
                         current
+                            .atomics
                             .state
                             .store(State::Empty.to_u8(), atomic::Ordering::Release);
-                        return Err(());
+                        drop(data);
+                        return Reuse::Done(Err(InvalidPhase));
                     }
 
                     let data_ptr = current.data.get() as *mut T;
                     unsafe { data_ptr.write(data) };
 
-                    current.phase.store(phase, atomic::Ordering::Release);
+                    current
+                        .atomics
+                        .phase
+                        .store(phase, atomic::Ordering::Release);
 
                     current
+                        .atomics
                         .state
                         .store(State::Set.to_u8(), atomic::Ordering::Release);
-                    return Ok(());
+                    return Reuse::Done(Ok(()));
                 }
                 State::Set => {
-                    let node_phase = current.phase.load(atomic::Ordering::Acquire);
-                    if node_phase >= phase {
+                    let node_phase = current.atomics.phase.load(atomic::Ordering::Acquire);
+                    if Phase::from(node_phase) >= Phase::from(phase) {
                         continue;
                     }
-                    if let Err(_) = current.state.compare_exchange(
+                    if let Err(_) = current.atomics.state.compare_exchange(
                         State::Set.to_u8(),
                         State::Accessed.to_u8(),
                         atomic::Ordering::SeqCst,
                         atomic::Ordering::SeqCst,
                     ) {
+                        contended = true;
                         continue;
                     }
                     if self.phase.load(atomic::Ordering::Acquire) != phase {
                         current
+                            .atomics
                             .state
                             .store(State::Set.to_u8(), atomic::Ordering::Release);
                         continue;
@@ -240,49 +508,33 @@ impl<T> Pool<T> {
                     let old = unsafe { data_ptr.replace(MaybeUninit::new(data)) };
                     drop(unsafe { old.assume_init() });
 
-                    current.phase.store(phase, atomic::Ordering::Release);
                     current
+                        .atomics
+                        .phase
+                        .store(phase, atomic::Ordering::Release);
+                    current
+                        .atomics
                         .state
                         .store(State::Set.to_u8(), atomic::Ordering::Release);
 
-                    return Ok(());
+                    return Reuse::Done(Ok(()));
+                }
+                State::Accessed => {
+                    contended = true;
+                    continue;
                 }
-                _ => continue,
             };
         }
 
-        let next_node = Node::new();
-        next_node
-            .state
-            .store(State::Accessed.to_u8(), atomic::Ordering::Release);
-        next_node.phase.store(phase, atomic::Ordering::Release);
-        let next_ptr = Box::into_raw(Box::new(next_node));
-
-        loop {
-            match latest.next.compare_exchange(
-                std::ptr::null_mut(),
-                next_ptr,
-                atomic::Ordering::SeqCst,
-                atomic::Ordering::SeqCst,
-            ) {
-                Ok(_) => {
-                    let next_node = unsafe { &*next_ptr };
-                    if self.phase.load(atomic::Ordering::Acquire) != phase {
-                        next_node
-                            .state
-                            .store(State::Empty.to_u8(), atomic::Ordering::Release);
-                        return Err(());
-                    }
-
-                    return Ok(());
-                }
-                Err(next) => {
-                    latest = unsafe { &*next };
-                }
-            };
+        Reuse::Full {
+            data,
+            last,
+            contended,
         }
     }
 
+    /// Removes any Element that was inserted in the given Phase, as long as
+    /// the Pool is still in that Phase
     pub fn pop(&self, phase: u64) -> Result<T, PopError> {
         if self.phase.load(atomic::Ordering::Acquire) != phase {
             return Err(PopError::InvalidPhase);
@@ -292,7 +544,7 @@ impl<T> Pool<T> {
             let current = unsafe { &*current_ptr };
 
             if let State::Set = current.load_state(atomic::Ordering::Acquire) {
-                if let Err(_) = current.state.compare_exchange(
+                if let Err(_) = current.atomics.state.compare_exchange(
                     State::Set.to_u8(),
                     State::Accessed.to_u8(),
                     atomic::Ordering::SeqCst,
@@ -302,13 +554,14 @@ impl<T> Pool<T> {
                 }
 
                 let pool_phase = self.phase.load(atomic::Ordering::Acquire);
-                let node_phase = current.phase.load(atomic::Ordering::Acquire);
+                let node_phase = current.atomics.phase.load(atomic::Ordering::Acquire);
                 if node_phase != pool_phase {
                     let data_ptr = current.data.get();
                     let old = unsafe { data_ptr.replace(MaybeUninit::uninit()) };
                     drop(unsafe { old.assume_init() });
 
                     current
+                        .atomics
                         .state
                         .store(State::Empty.to_u8(), atomic::Ordering::Release);
                     continue;
@@ -316,6 +569,7 @@ impl<T> Pool<T> {
 
                 if pool_phase != phase {
                     current
+                        .atomics
                         .state
                         .store(State::Set.to_u8(), atomic::Ordering::Release);
                     return Err(PopError::InvalidPhase);
@@ -327,6 +581,7 @@ impl<T> Pool<T> {
                 unsafe { data_ptr.write(MaybeUninit::uninit()) };
 
                 current
+                    .atomics
                     .state
                     .store(State::Empty.to_u8(), atomic::Ordering::Release);
 
@@ -337,6 +592,207 @@ impl<T> Pool<T> {
         Err(PopError::Empty)
     }
 
+    /// Removes an Element for the given Phase like [`Pool::pop`], but waits
+    /// for up to `timeout` for an Element to be inserted or for the Pool to
+    /// reach the Phase, if it is still in an older Phase.
+    ///
+    /// The Pool is only scanned again once an Insert or
+    /// [`Pool::update_phase_notify`] changed it, in between the Thread backs
+    /// off and eventually yields. A Phase updated using the plain
+    /// [`Pool::update_phase`] is only noticed by the next Insert or once the
+    /// Timeout expires.
+    ///
+    /// Fails with [`PopError::InvalidPhase`] as soon as the Pool moved past
+    /// the Phase and with [`PopError::Empty`] once the Timeout expired.
+    ///
+    /// # Panics
+    /// If the Pool was not created using [`Pool::new_blocking`]
+    #[cfg(feature = "std")]
+    pub fn pop_blocking(&self, phase: u64, timeout: std::time::Duration) -> Result<T, PopError> {
+        assert!(
+            self.blocking,
+            "pop_blocking requires a Pool created using Pool::new_blocking"
+        );
+
+        let deadline = std::time::Instant::now() + timeout;
+        let mut backoff = crate::backoff::Backoff::new(6);
+
+        loop {
+            let version = self.version.load(atomic::Ordering::Acquire);
+
+            match self.pop(phase) {
+                Ok(data) => return Ok(data),
+                Err(PopError::InvalidPhase)
+                    if Phase::from(self.phase.load(atomic::Ordering::Acquire))
+                        > Phase::from(phase) =>
+                {
+                    return Err(PopError::InvalidPhase);
+                }
+                Err(_) => {}
+            };
+
+            // A Pool that keeps changing never gets to the Check below, so the
+            // Deadline is checked after every failed Attempt as well
+            if std::time::Instant::now() >= deadline {
+                return Err(PopError::Empty);
+            }
+
+            while self.version.load(atomic::Ordering::Acquire) == version {
+                if std::time::Instant::now() >= deadline {
+                    return Err(PopError::Empty);
+                }
+                backoff.snooze();
+            }
+            backoff.reset();
+        }
+    }
+
+    /// Removes all the Elements that are stored in the Pool for the given
+    /// Phase at once, instead of popping them one at a time. Elements of other
+    /// Phases are left untouched.
+    ///
+    /// If the Phase of the Pool changes while draining, the Elements that were
+    /// already removed are returned and the rest is left in the Pool
+    pub fn drain(&self, phase: u64) -> Vec<T> {
+        let mut result = Vec::new();
+        if self.phase.load(atomic::Ordering::Acquire) != phase {
+            return result;
+        }
+
+        for current_ptr in self.iter() {
+            let current = unsafe { &*current_ptr };
+
+            if !matches!(current.load_state(atomic::Ordering::Acquire), State::Set)
+                || current.atomics.phase.load(atomic::Ordering::Acquire) != phase
+            {
+                continue;
+            }
+
+            if current
+                .atomics
+                .state
+                .compare_exchange(
+                    State::Set.to_u8(),
+                    State::Accessed.to_u8(),
+                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::SeqCst,
+                )
+                .is_err()
+            {
+                continue;
+            }
+
+            // The Node could have been refilled in between
+            if current.atomics.phase.load(atomic::Ordering::Acquire) != phase {
+                current
+                    .atomics
+                    .state
+                    .store(State::Set.to_u8(), atomic::Ordering::Release);
+                continue;
+            }
+
+            if self.phase.load(atomic::Ordering::Acquire) != phase {
+                current
+                    .atomics
+                    .state
+                    .store(State::Set.to_u8(), atomic::Ordering::Release);
+                return result;
+            }
+
+            let data_ptr = current.data.get();
+            result.push(unsafe { data_ptr.read().assume_init() });
+            unsafe { data_ptr.write(MaybeUninit::uninit()) };
+
+            current
+                .atomics
+                .state
+                .store(State::Empty.to_u8(), atomic::Ordering::Release);
+        }
+
+        result
+    }
+
+    /// Drops all the Elements that were inserted in a Phase older than the
+    /// current Phase of the Pool, instead of waiting for later Inserts or Pops
+    /// to encounter them.
+    ///
+    /// # Returns
+    /// The Number of Elements that were dropped
+    pub fn remove_stale(&self) -> usize {
+        let pool_phase = Phase::from(self.phase.load(atomic::Ordering::Acquire));
+
+        let mut removed = 0;
+        for current_ptr in self.iter() {
+            let current = unsafe { &*current_ptr };
+
+            if !matches!(current.load_state(atomic::Ordering::Acquire), State::Set)
+                || Phase::from(current.atomics.phase.load(atomic::Ordering::Acquire)) >= pool_phase
+            {
+                continue;
+            }
+
+            if current
+                .atomics
+                .state
+                .compare_exchange(
+                    State::Set.to_u8(),
+                    State::Accessed.to_u8(),
+                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::SeqCst,
+                )
+                .is_err()
+            {
+                continue;
+            }
+
+            // The Node could have been refilled in between
+            if Phase::from(current.atomics.phase.load(atomic::Ordering::Acquire)) >= pool_phase {
+                current
+                    .atomics
+                    .state
+                    .store(State::Set.to_u8(), atomic::Ordering::Release);
+                continue;
+            }
+
+            let data_ptr = current.data.get();
+            let old = unsafe { data_ptr.replace(MaybeUninit::uninit()) };
+            drop(unsafe { old.assume_init() });
+
+            current
+                .atomics
+                .state
+                .store(State::Empty.to_u8(), atomic::Ordering::Release);
+            removed += 1;
+        }
+
+        removed
+    }
+
+    /// The Number of Elements that are stored in the Pool for the given Phase.
+    ///
+    /// # Concurrency
+    /// The Count is only a Snapshot, because other Threads may insert or pop
+    /// Elements while the Nodes are being counted, so it can be outdated by
+    /// the time it is returned and should only be used as a Hint
+    pub fn len(&self, phase: u64) -> usize {
+        self.iter()
+            .filter(|node_ptr| {
+                let node = unsafe { &**node_ptr };
+                matches!(node.load_state(atomic::Ordering::Acquire), State::Set)
+                    && node.atomics.phase.load(atomic::Ordering::Acquire) == phase
+            })
+            .count()
+    }
+
+    /// Checks if the Pool contains no Elements for the given Phase.
+    ///
+    /// # Concurrency
+    /// Like [`Pool::len`], this is only a Snapshot
+    #[cfg(test)]
+    pub fn is_empty(&self, phase: u64) -> bool {
+        self.len(phase) == 0
+    }
+
     fn iter(&self) -> ListIter<T> {
         ListIter {
             current: self.start,
@@ -344,6 +800,21 @@ impl<T> Pool<T> {
     }
 }
 
+impl<T> Drop for Pool<T> {
+    fn drop(&mut self) {
+        let mut current_ptr = self.start;
+        while !current_ptr.is_null() {
+            let current = unsafe { Box::from_raw(current_ptr) };
+            current_ptr = current.atomics.next.load(atomic::Ordering::Acquire);
+
+            if let State::Set = current.load_state(atomic::Ordering::Acquire) {
+                let data = unsafe { current.data.get().read().assume_init() };
+                drop(data);
+            }
+        }
+    }
+}
+
 unsafe impl<T> Send for Pool<T> {}
 unsafe impl<T> Sync for Pool<T> {}
 
@@ -361,7 +832,7 @@ impl<T> Iterator for ListIter<T> {
         let current_ptr = self.current;
         let current = unsafe { &*current_ptr };
 
-        self.current = current.next.load(atomic::Ordering::Acquire);
+        self.current = current.atomics.next.load(atomic::Ordering::Acquire);
         Some(current_ptr)
     }
 }
@@ -370,6 +841,8 @@ impl<T> Iterator for ListIter<T> {
 mod tests {
     use super::*;
 
+    use std::sync::{atomic::AtomicUsize, Arc};
+
     #[test]
     fn pool_new() {
         let pool = Pool::<usize>::new();
@@ -389,7 +862,7 @@ mod tests {
         assert_eq!(Ok(()), pool.insert(13, 0));
 
         pool.update_phase(1);
-        assert_eq!(Err(()), pool.insert(13, 0));
+        assert_eq!(Err(InvalidPhase), pool.insert(13, 0));
     }
     #[test]
     fn pool_insert_multiple() {
@@ -409,6 +882,177 @@ mod tests {
         assert_eq!(Ok(13), pool.pop(0));
     }
 
+    #[test]
+    fn pool_insert_multiple_pop() {
+        let pool = Pool::<usize>::new();
+
+        // Every Insert after the first one appends a new Node to the Pool
+        assert_eq!(Ok(()), pool.insert(13, 0));
+        assert_eq!(Ok(()), pool.insert(14, 0));
+        assert_eq!(Ok(()), pool.insert(15, 0));
+
+        let mut popped = vec![
+            pool.pop(0).unwrap(),
+            pool.pop(0).unwrap(),
+            pool.pop(0).unwrap(),
+        ];
+        popped.sort_unstable();
+        assert_eq!(vec![13, 14, 15], popped);
+        assert_eq!(Err(PopError::Empty), pool.pop(0));
+    }
+
+    #[test]
+    fn pool_len() {
+        let pool = Pool::<usize>::new();
+        assert!(pool.is_empty(0));
+
+        assert_eq!(Ok(()), pool.insert(13, 0));
+        assert_eq!(Ok(()), pool.insert(14, 0));
+        assert_eq!(2, pool.len(0));
+        assert!(!pool.is_empty(0));
+
+        assert!(pool.pop(0).is_ok());
+        assert_eq!(1, pool.len(0));
+
+        // Elements from an old Phase are not counted
+        pool.update_phase(1).unwrap();
+        assert_eq!(0, pool.len(1));
+        assert!(pool.is_empty(1));
+    }
+
+    #[test]
+    fn pool_drop() {
+        struct Counted(Arc<AtomicUsize>);
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, atomic::Ordering::SeqCst);
+            }
+        }
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+
+        let pool = Pool::new();
+        for _ in 0..4 {
+            assert!(pool.insert(Counted(dropped.clone()), 0).is_ok());
+        }
+        let popped = pool.pop(0).ok().unwrap();
+        drop(popped);
+        assert_eq!(1, dropped.load(atomic::Ordering::SeqCst));
+
+        drop(pool);
+        assert_eq!(4, dropped.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn pool_drain() {
+        let pool = Pool::<usize>::new();
+
+        for data in 13..17 {
+            assert_eq!(Ok(()), pool.insert(data, 0));
+        }
+
+        let mut drained = pool.drain(0);
+        drained.sort_unstable();
+        assert_eq!(vec![13, 14, 15, 16], drained);
+        assert!(pool.is_empty(0));
+        assert_eq!(Err(PopError::Empty), pool.pop(0));
+
+        // The drained Nodes can be reused
+        assert_eq!(Ok(()), pool.insert(17, 0));
+        assert_eq!(1, pool.len(0));
+    }
+
+    #[test]
+    fn pool_drain_phase() {
+        let pool = Pool::<usize>::new();
+
+        assert_eq!(Ok(()), pool.insert(13, 0));
+        assert_eq!(Ok(()), pool.insert(14, 0));
+        pool.update_phase(1).unwrap();
+        // Overwrites the first stale Element
+        assert_eq!(Ok(()), pool.insert(15, 1));
+
+        // Draining for an old Phase does nothing
+        assert!(pool.drain(0).is_empty());
+
+        // Elements of the old Phase are left in the Pool
+        assert_eq!(vec![15], pool.drain(1));
+        assert_eq!(0, pool.len(1));
+        assert_eq!(1, pool.len(0));
+    }
+
+    #[test]
+    fn pool_remove_stale() {
+        let pool = Pool::<usize>::new();
+
+        assert_eq!(Ok(()), pool.insert(13, 0));
+        assert_eq!(Ok(()), pool.insert(14, 0));
+        assert_eq!(0, pool.remove_stale());
+
+        pool.update_phase(1).unwrap();
+        assert_eq!(Ok(()), pool.insert(15, 1));
+
+        // The first stale Element was overwritten by the Insert
+        assert_eq!(1, pool.remove_stale());
+        assert_eq!(0, pool.len(0));
+        assert_eq!(1, pool.len(1));
+    }
+
+    /// Records the ID of every dropped Instance
+    struct Droppable(usize, Arc<std::sync::Mutex<Vec<usize>>>);
+    impl Drop for Droppable {
+        fn drop(&mut self) {
+            self.1.lock().unwrap().push(self.0);
+        }
+    }
+
+    #[test]
+    fn pool_drop_exactly_once() {
+        let dropped = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let new = |id| Droppable(id, dropped.clone());
+
+        let pool = Pool::new();
+        assert!(pool.insert(new(0), 0).is_ok());
+        assert!(pool.insert(new(1), 0).is_ok());
+        assert!(pool.insert(new(2), 0).is_ok());
+
+        drop(pool.pop(0).ok().unwrap());
+        assert_eq!(vec![0], *dropped.lock().unwrap());
+
+        // Fills the now empty Node, without dropping anything
+        pool.update_phase(1).unwrap();
+        assert!(pool.insert(new(3), 1).is_ok());
+        assert_eq!(1, dropped.lock().unwrap().len());
+
+        // Overwrites the first stale Element
+        assert!(pool.insert(new(4), 1).is_ok());
+        assert_eq!(vec![0, 1], *dropped.lock().unwrap());
+
+        // Inserting for an old Phase drops the rejected Element
+        assert!(pool.insert(new(5), 0).is_err());
+        assert_eq!(vec![0, 1, 5], *dropped.lock().unwrap());
+
+        drop(pool.drain(1));
+        drop(pool);
+
+        let mut dropped = dropped.lock().unwrap().clone();
+        dropped.sort_unstable();
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], dropped);
+    }
+
+    #[test]
+    fn pool_zero_sized() {
+        let pool = Pool::<()>::new();
+
+        assert_eq!(Ok(()), pool.insert((), 0));
+        assert_eq!(Ok(()), pool.insert((), 0));
+        assert_eq!(2, pool.len(0));
+
+        assert_eq!(Ok(()), pool.pop(0));
+        assert_eq!(vec![()], pool.drain(0));
+        assert_eq!(Err(PopError::Empty), pool.pop(0));
+    }
+
     #[test]
     fn insert_new_pop() {
         let pool = Pool::<usize>::new();
@@ -419,4 +1063,129 @@ mod tests {
         assert_eq!(Err(PopError::InvalidPhase), pool.pop(0));
         assert_eq!(Err(PopError::Empty), pool.pop(1));
     }
+
+    #[test]
+    fn insert_pop_reuses_nodes() {
+        let pool = Pool::<usize>::new();
+
+        for data in 0..1000 {
+            assert_eq!(Ok(()), pool.insert(data, 0));
+            assert_eq!(Ok(data), pool.pop(0));
+        }
+
+        assert_eq!(1, pool.iter().count());
+    }
+
+    #[test]
+    fn insert_pop_concurrent_reuses_nodes() {
+        const THREADS: usize = 4;
+
+        let pool = Arc::new(Pool::<usize>::new());
+
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let pool = pool.clone();
+                std::thread::spawn(move || {
+                    for data in 0..1000 {
+                        assert_eq!(Ok(()), pool.insert(data, 0));
+                        // A Pop may miss the Elements that are inserted
+                        // concurrently, but our own Element keeps the Pool
+                        // from being empty until we popped one
+                        while pool.pop(0).is_err() {}
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // Every Thread holds at most one Element at a Time, so the Pool only
+        // grows while Threads race for the same Nodes
+        assert!(pool.iter().count() <= 2 * THREADS);
+        assert!(pool.is_empty(0));
+    }
+
+    #[test]
+    fn node_cache_line() {
+        // The Data starts right after the Cache-Line of the Atomics
+        let node = Node::<usize>::new();
+        let base = &node as *const Node<usize> as usize;
+        assert_eq!(64, node.data.get() as usize - base);
+
+        assert_eq!(64, core::mem::align_of::<Node<usize>>());
+        assert_eq!(128, core::mem::size_of::<Node<usize>>());
+        assert_eq!(128, core::mem::size_of::<Node<[u8; 64]>>());
+    }
+
+    #[test]
+    fn pop_blocking_timeout() {
+        let pool: Pool<u8> = Pool::new_blocking();
+
+        let timeout = std::time::Duration::from_millis(10);
+        assert_eq!(Err(PopError::Empty), pool.pop_blocking(0, timeout));
+
+        pool.update_phase(1).unwrap();
+        assert_eq!(Err(PopError::InvalidPhase), pool.pop_blocking(0, timeout));
+    }
+
+    #[test]
+    fn pop_blocking_insert() {
+        let pool: Pool<u8> = Pool::new_blocking();
+
+        std::thread::scope(|s| {
+            let waiter = s.spawn(|| pool.pop_blocking(0, std::time::Duration::from_secs(10)));
+
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            pool.insert(13, 0).unwrap();
+
+            assert_eq!(Ok(13), waiter.join().unwrap());
+        });
+    }
+
+    #[test]
+    fn pop_blocking_phase_notify() {
+        let pool: Pool<u8> = Pool::new_blocking();
+
+        std::thread::scope(|s| {
+            let waiter = s.spawn(|| pool.pop_blocking(1, std::time::Duration::from_secs(10)));
+
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            pool.update_phase_notify(1).unwrap();
+            pool.insert(13, 1).unwrap();
+
+            assert_eq!(Ok(13), waiter.join().unwrap());
+        });
+
+        assert_eq!(Err(InvalidPhase), pool.update_phase_notify(1));
+    }
+
+    #[test]
+    fn pop_blocking_busy_timeout() {
+        let pool: Pool<u8> = Pool::new_blocking();
+        let done = atomic::AtomicBool::new(false);
+
+        std::thread::scope(|s| {
+            // Keeps changing the Pool, without ever providing an Element for
+            // the Phase the Waiter is interested in
+            s.spawn(|| {
+                while !done.load(atomic::Ordering::Acquire) {
+                    pool.insert(13, 0).unwrap();
+                    assert_eq!(Ok(13), pool.pop(0));
+                    std::thread::yield_now();
+                }
+            });
+
+            let timeout = std::time::Duration::from_millis(20);
+            assert_eq!(Err(PopError::Empty), pool.pop_blocking(1, timeout));
+            done.store(true, atomic::Ordering::Release);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Pool::new_blocking")]
+    fn pop_blocking_requires_blocking() {
+        let pool: Pool<u8> = Pool::new();
+        let _ = pool.pop_blocking(0, std::time::Duration::from_millis(1));
+    }
 }
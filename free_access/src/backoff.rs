@@ -0,0 +1,83 @@
+//! A simple exponential Backoff, used by the Threads that are waiting on
+//! other Threads to make Progress.
+//!
+//! The Backoff only ever affects how the waiting Thread is scheduled and never
+//! whether or not a Loop using it terminates.
+
+/// The Backoff for a single waiting Loop
+pub struct Backoff {
+    step: u32,
+    spin_limit: u32,
+}
+
+impl Backoff {
+    /// The largest Step, after which the Backoff does not grow anymore
+    const MAX_STEP: u32 = 16;
+
+    /// Creates a new Backoff, which spins for up to `2^spin_limit` Iterations
+    /// before it starts yielding to other Threads instead
+    pub fn new(spin_limit: u32) -> Self {
+        Self {
+            step: 0,
+            spin_limit: spin_limit.min(Self::MAX_STEP),
+        }
+    }
+
+    /// Starts over with the shortest Backoff again, this should be called
+    /// whenever the Thread made some Progress
+    pub fn reset(&mut self) {
+        self.step = 0;
+    }
+
+    /// Waits for a bit, the Wait grows with every Call until `reset` is
+    /// called again
+    pub fn snooze(&mut self) {
+        if self.step < self.spin_limit {
+            for _ in 0..(1u32 << self.step) {
+                std::hint::spin_loop();
+            }
+        } else {
+            std::thread::yield_now();
+        }
+
+        if self.step < Self::MAX_STEP {
+            self.step += 1;
+        }
+    }
+
+    /// Whether or not the Backoff has started yielding to other Threads
+    #[cfg(test)]
+    pub fn is_yielding(&self) -> bool {
+        self.step >= self.spin_limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escalates_to_yield() {
+        let mut backoff = Backoff::new(3);
+
+        for _ in 0..3 {
+            assert!(!backoff.is_yielding());
+            backoff.snooze();
+        }
+        assert!(backoff.is_yielding());
+
+        backoff.reset();
+        assert!(!backoff.is_yielding());
+    }
+
+    #[test]
+    fn zero_spin_limit() {
+        let mut backoff = Backoff::new(0);
+        assert!(backoff.is_yielding());
+
+        for _ in 0..100 {
+            backoff.snooze();
+        }
+        assert!(backoff.is_yielding());
+    }
+}
@@ -0,0 +1,135 @@
+//! Helpers for storing the Pointers from one Node to its Children
+//!
+//! Every Datastructure needs to load its Children when implementing
+//! [`DataStructureNode::pointers`], these helpers make sure that this is
+//! always done using the same Ordering and that the Tags are removed
+//! consistently before handing the Ptrs to the Collector.
+
+use crate::sync::atomic;
+
+use crate::DataStructureNode;
+
+/// Loads the Ptr to a Child from the given Field and removes any Tag from it,
+/// so it can directly be returned from [`DataStructureNode::pointers`]
+pub fn load_child<N>(ptr: &atomic::AtomicPtr<N>) -> *mut N
+where
+    N: DataStructureNode,
+{
+    N::untag_ptr(ptr.load(atomic::Ordering::Acquire))
+}
+
+/// A Slot in a Node that stores a (possibly tagged) Ptr to a Child-Node
+pub struct ChildSlot<N> {
+    ptr: atomic::AtomicPtr<N>,
+}
+
+impl<N> ChildSlot<N> {
+    /// Creates a new Slot that initially stores the given Ptr
+    pub fn new(ptr: *mut N) -> Self {
+        Self {
+            ptr: atomic::AtomicPtr::new(ptr),
+        }
+    }
+
+    /// Creates a new empty Slot
+    pub fn null() -> Self {
+        Self::new(core::ptr::null_mut())
+    }
+
+    /// Loads the raw Ptr stored in the Slot, including its Tag
+    pub fn load(&self) -> *mut N {
+        self.ptr.load(atomic::Ordering::Acquire)
+    }
+
+    /// Stores the given (possibly tagged) Ptr in the Slot
+    pub fn store(&self, ptr: *mut N) {
+        self.ptr.store(ptr, atomic::Ordering::Release);
+    }
+
+    /// Atomically replaces the stored Ptr with `new`, if it currently stores
+    /// `current`. Both Ptrs are compared including their Tags
+    pub fn compare_exchange(&self, current: *mut N, new: *mut N) -> Result<*mut N, *mut N> {
+        self.ptr.compare_exchange(
+            current,
+            new,
+            atomic::Ordering::SeqCst,
+            atomic::Ordering::SeqCst,
+        )
+    }
+
+    /// Gives access to the underlying Atomic
+    pub fn as_atomic(&self) -> &atomic::AtomicPtr<N> {
+        &self.ptr
+    }
+}
+
+impl<N> ChildSlot<N>
+where
+    N: DataStructureNode,
+{
+    /// Loads the Ptr stored in the Slot with its Tag removed
+    pub fn load_untagged(&self) -> *mut N {
+        load_child(&self.ptr)
+    }
+}
+
+impl<N> Default for ChildSlot<N> {
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TaggedNode {
+        left: ChildSlot<Self>,
+        right: ChildSlot<Self>,
+    }
+
+    impl DataStructureNode for TaggedNode {
+        fn pointer_count() -> usize {
+            2
+        }
+        fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+            out.push(self.left.load_untagged());
+            out.push(self.right.load_untagged());
+        }
+
+        fn untag_ptr(ptr: *mut Self) -> *mut Self {
+            ((ptr as usize) & !0x01) as *mut Self
+        }
+    }
+
+    #[test]
+    fn load_child_untags() {
+        let ptr = atomic::AtomicPtr::new(0x101 as *mut TaggedNode);
+
+        assert_eq!(0x100 as *mut TaggedNode, load_child(&ptr));
+    }
+
+    #[test]
+    fn slot_pointers() {
+        let node = TaggedNode {
+            left: ChildSlot::new(0x201 as *mut TaggedNode),
+            right: ChildSlot::null(),
+        };
+
+        assert_eq!(0x201 as *mut TaggedNode, node.left.load());
+        assert_eq!(
+            vec![0x200 as *mut TaggedNode, std::ptr::null_mut()],
+            node.pointers()
+        );
+
+        assert_eq!(
+            Ok(std::ptr::null_mut()),
+            node.right
+                .compare_exchange(std::ptr::null_mut(), 0x301 as *mut TaggedNode)
+        );
+        assert_eq!(
+            vec![0x200 as *mut TaggedNode, 0x300 as *mut TaggedNode],
+            node.pointers()
+        );
+    }
+}
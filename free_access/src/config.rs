@@ -0,0 +1,165 @@
+use crate::{Allocator, CollectionPolicy, DataStructureGlobals, DataStructureNode, GcObserver};
+
+/// The Configuration used to create a new [`Allocator`](crate::Allocator)
+#[derive(Debug, Clone)]
+pub struct AllocatorConfig {
+    /// The Number of Nodes stored in a single Page, the Heap always grows by
+    /// one Page at a time
+    pub page_size: usize,
+
+    /// The Number of Pages that are allocated when creating the Allocator
+    pub initial_pages: usize,
+
+    /// The Size of the Buffers used to hand out free Nodes to the Threads,
+    /// larger Buffers need to be exchanged with the global Pool less often
+    pub allocation_buffer_size: usize,
+
+    /// When set, the entire Sweep of a Phase will be performed by a single
+    /// Thread, which processes the Pages and their Nodes in Order.
+    ///
+    /// This disables the cooperative Sweeping between Threads, but makes it
+    /// so that two runs over the same Graph reclaim the Nodes in the same
+    /// Order, which is really useful when debugging
+    pub deterministic_sweep: bool,
+
+    /// The maximum Number of Pages that will be swept during a single
+    /// Collection, `None` means that every Collection sweeps all the Pages.
+    ///
+    /// Every Collection continues where the Sweep of the previous one stopped,
+    /// so over enough Collections every Page will still be swept. The
+    /// overflow Nodes are limited the same way, to as many Nodes as the
+    /// budgeted Pages hold
+    pub sweep_budget_pages: Option<usize>,
+
+    /// When set, Nodes that can not be stored in the Pages, either because
+    /// they are too large or because no free Node could be found, will instead
+    /// be allocated using the System-Allocator.
+    ///
+    /// These Nodes are still traced like every other Node and will be freed
+    /// again once they are no longer reachable
+    pub overflow_alloc: bool,
+
+    /// How long a Thread, that is waiting for the other Threads to finish
+    /// their Tracing, spins before it starts yielding instead. The Thread
+    /// spins for up to `2^trace_spin_limit` Iterations at a time
+    pub trace_spin_limit: u32,
+
+    /// When set, every Thread has to register itself using
+    /// [`Allocator::register`](crate::Allocator::register) before using the
+    /// Allocator and stays registered until the returned Handle is dropped.
+    ///
+    /// Collections then only consider the State of the currently registered
+    /// Threads, instead of the State of every Thread that ever used the
+    /// Allocator, including the ones that already exited
+    pub explicit_registration: bool,
+}
+
+impl Default for AllocatorConfig {
+    fn default() -> Self {
+        Self {
+            page_size: 256,
+            initial_pages: 1,
+            allocation_buffer_size: crate::allocator::DEFAULT_BUFFER_SIZE,
+            deterministic_sweep: false,
+            sweep_budget_pages: None,
+            overflow_alloc: false,
+            trace_spin_limit: 6,
+            explicit_registration: false,
+        }
+    }
+}
+
+/// A Builder to configure and create a new [`Allocator`](crate::Allocator)
+pub struct AllocatorBuilder<G> {
+    globals: G,
+    config: AllocatorConfig,
+    policy: Option<Box<dyn CollectionPolicy>>,
+    observer: Option<Box<dyn GcObserver>>,
+}
+
+impl<G> AllocatorBuilder<G> {
+    /// Creates a new Builder, which starts out with the default Configuration
+    pub fn new(globals: G) -> Self {
+        Self {
+            globals,
+            config: AllocatorConfig::default(),
+            policy: None,
+            observer: None,
+        }
+    }
+
+    /// Replaces the entire Configuration
+    pub fn config(mut self, config: AllocatorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the Number of Nodes stored in a single Page
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.config.page_size = page_size;
+        self
+    }
+
+    /// Sets the Number of Pages that are allocated up front
+    pub fn initial_pages(mut self, initial_pages: usize) -> Self {
+        self.config.initial_pages = initial_pages;
+        self
+    }
+
+    /// Sets the Size of the Buffers used to hand out free Nodes to the Threads
+    ///
+    /// # Panics
+    /// If the Size is 0, because a Thread could never take a Node out of such
+    /// a Buffer
+    pub fn allocation_buffer_size(mut self, size: usize) -> Self {
+        assert!(
+            size > 0,
+            "the allocation_buffer_size must be at least 1, a Buffer of size 0 can never hand out a Node"
+        );
+        self.config.allocation_buffer_size = size;
+        self
+    }
+
+    /// Sets how long a Thread spins, while waiting for the Tracing of other
+    /// Threads, before it starts yielding
+    pub fn trace_spin_limit(mut self, limit: u32) -> Self {
+        self.config.trace_spin_limit = limit;
+        self
+    }
+
+    /// Requires every Thread to register itself before using the Allocator,
+    /// see [`AllocatorConfig::explicit_registration`]
+    pub fn explicit_registration(mut self, explicit: bool) -> Self {
+        self.config.explicit_registration = explicit;
+        self
+    }
+
+    /// Sets the Policy that decides when the Allocator starts a Collection on
+    /// its own, by default this only happens when it runs out of free Nodes
+    pub fn collection_policy<P>(mut self, policy: P) -> Self
+    where
+        P: CollectionPolicy + 'static,
+    {
+        self.policy = Some(Box::new(policy));
+        self
+    }
+
+    /// Sets the Observer that is notified about the Progress of every
+    /// Collection, by default no Observer is used
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: GcObserver + 'static,
+    {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Actually creates the Allocator with the configured Settings
+    pub fn build<N>(self) -> Allocator<N, G>
+    where
+        N: DataStructureNode,
+        G: DataStructureGlobals<N>,
+    {
+        Allocator::with_policy(self.globals, self.config, self.policy, self.observer)
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::atomic;
+use crate::{sync::atomic, Phase};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct DirtyValue {
@@ -6,15 +6,19 @@ pub struct DirtyValue {
     pub phase: u64,
 }
 impl DirtyValue {
+    /// The largest Phase that can be stored, as the Phase is stored in the
+    /// upper 56 Bits of the Encoding
+    pub const MAX_PHASE: u64 = u64::MAX >> 8;
+
     pub const fn from_u64(val: u64) -> Self {
         let dirty = val & 1 == 1;
-        let phase = val >> 8;
+        let phase = Phase::unpack(val, 8).get();
 
         Self { dirty, phase }
     }
 
-    pub const fn to_u64(&self) -> u64 {
-        let result = self.phase << 8;
+    pub fn to_u64(&self) -> u64 {
+        let result = Phase::from(self.phase).pack(8);
         let dirty_mask: u64 = if self.dirty { 0x01 } else { 0x00 };
         result | dirty_mask
     }
@@ -35,17 +39,68 @@ impl Udirty {
         DirtyValue::from_u64(raw)
     }
 
-    pub fn update(&self, expected: u64, n_dirty: DirtyValue) -> bool {
-        let raw = n_dirty.to_u64();
+    /// Clears the Dirty-Flag, while keeping the current Phase
+    pub fn clear_dirty(&self) {
+        self.modify(|current| DirtyValue {
+            dirty: false,
+            phase: current.phase,
+        });
+    }
+
+    /// Applies the given Modification to the current Value using a CAS-Loop,
+    /// so no concurrent Update is lost
+    fn modify<F>(&self, modification: F)
+    where
+        F: Fn(DirtyValue) -> DirtyValue,
+    {
+        let mut current = self.dirty_phase.load(atomic::Ordering::Acquire);
+        loop {
+            let n_value = modification(DirtyValue::from_u64(current));
+
+            match self.dirty_phase.compare_exchange(
+                current,
+                n_value.to_u64(),
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                Ok(_) => return,
+                Err(c) => {
+                    current = c;
+                }
+            };
+        }
+    }
+
+    /// Marks the Thread as dirty for the Collection of the given Phase, unless
+    /// it was already marked for this or a newer Phase.
+    ///
+    /// This retries until it succeeds, as a concurrent
+    /// [`clear_dirty`](Self::clear_dirty) of the Thread must not cause the
+    /// Mark to be lost.
+    ///
+    /// # Returns
+    /// Whether the Thread was marked by this Call
+    pub fn mark_dirty(&self, phase: u64) -> bool {
+        let n_value = DirtyValue { dirty: true, phase }.to_u64();
+
+        let mut current = self.dirty_phase.load(atomic::Ordering::Acquire);
+        loop {
+            if Phase::from(DirtyValue::from_u64(current).phase) >= Phase::from(phase) {
+                return false;
+            }
 
-        self.dirty_phase
-            .compare_exchange(
-                expected,
-                raw,
+            match self.dirty_phase.compare_exchange(
+                current,
+                n_value,
                 atomic::Ordering::SeqCst,
                 atomic::Ordering::SeqCst,
-            )
-            .is_ok()
+            ) {
+                Ok(_) => return true,
+                Err(c) => {
+                    current = c;
+                }
+            };
+        }
     }
 }
 
@@ -88,4 +143,76 @@ mod tests {
         };
         assert_eq!(0x2100, dirty.to_u64());
     }
+
+    #[test]
+    fn clear_dirty_keeps_phase() {
+        let dirty = Udirty::new();
+        assert!(dirty.mark_dirty(13));
+
+        dirty.clear_dirty();
+        assert_eq!(
+            DirtyValue {
+                dirty: false,
+                phase: 13
+            },
+            dirty.get()
+        );
+    }
+
+    #[test]
+    fn clear_dirty_concurrent() {
+        use std::sync::Arc;
+
+        let dirty = Arc::new(Udirty::new());
+
+        let setter = {
+            let dirty = dirty.clone();
+            std::thread::spawn(move || {
+                for phase in 1..=1000 {
+                    dirty.mark_dirty(phase);
+                }
+            })
+        };
+
+        for _ in 0..1000 {
+            dirty.clear_dirty();
+        }
+        setter.join().unwrap();
+
+        // No Phase-Update was lost due to clearing the Flag
+        assert_eq!(1000, dirty.get().phase);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "does not fit")]
+    fn phase_too_large() {
+        let dirty = DirtyValue {
+            dirty: false,
+            phase: DirtyValue::MAX_PHASE + 1,
+        };
+        dirty.to_u64();
+    }
+
+    #[test]
+    fn mark_dirty() {
+        let dirty = Udirty::new();
+
+        assert!(dirty.mark_dirty(1));
+        assert_eq!(
+            DirtyValue {
+                dirty: true,
+                phase: 1
+            },
+            dirty.get()
+        );
+
+        // Already marked for this Phase, even after clearing the Flag
+        dirty.clear_dirty();
+        assert!(!dirty.mark_dirty(1));
+        assert!(!dirty.get().dirty);
+
+        assert!(dirty.mark_dirty(2));
+        assert!(dirty.get().dirty);
+    }
 }
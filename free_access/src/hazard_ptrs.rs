@@ -1,43 +1,83 @@
-use std::sync::atomic;
+#[cfg(feature = "std")]
+use alloc::{boxed::Box, vec::Vec};
+
+#[cfg(feature = "std")]
+use crate::sync::atomic;
 
 mod ptr;
 pub use ptr::HazardPtr;
 
+/// The Hazard-Ptrs of a single Thread, which are stored in a linked List
+///
+/// # Shrinking
+/// The List grows whenever all of its Hazard-Ptrs are in use, but is only
+/// shrunk again by [`HazardPtrFrame::shrink_to`]. Other Threads may walk the
+/// List at any Time while gathering the Roots of a Collection, so the
+/// Hazard-Ptrs that are removed are only freed once no Thread is walking the
+/// List anymore. Until then they are kept in a separate List of retired
+/// Hazard-Ptrs.
+#[cfg(feature = "std")]
 pub struct HazardPtrFrame<T> {
     ptrs: *mut HazardPtr<T>,
+    /// The Number of Hazard-Ptrs currently in the Frame
+    len: atomic::AtomicUsize,
+    /// The largest Number of Hazard-Ptrs the Frame contained since it was
+    /// last shrunk
+    high_water_mark: atomic::AtomicUsize,
+    /// The Number of Threads currently walking the Hazard-Ptrs
+    readers: atomic::AtomicUsize,
+    /// Whether a Thread is currently shrinking the Frame
+    shrinking: atomic::AtomicBool,
+    /// The Hazard-Ptrs that were removed from the Frame, but may still be
+    /// visited by a Thread that started walking the Frame before
+    retired: atomic::AtomicPtr<HazardPtr<T>>,
 }
 
+#[cfg(feature = "std")]
 unsafe impl<T> Send for HazardPtrFrame<T> {}
+#[cfg(feature = "std")]
 unsafe impl<T> Sync for HazardPtrFrame<T> {}
 
+#[cfg(feature = "std")]
 impl<T> HazardPtrFrame<T> {
     pub fn new() -> Self {
-        let initial = Box::into_raw(Box::new(HazardPtr::new(std::ptr::null_mut())));
-        Self { ptrs: initial }
+        let initial = Box::into_raw(Box::new(HazardPtr::new(core::ptr::null_mut())));
+        Self {
+            ptrs: initial,
+            len: atomic::AtomicUsize::new(1),
+            high_water_mark: atomic::AtomicUsize::new(1),
+            readers: atomic::AtomicUsize::new(0),
+            shrinking: atomic::AtomicBool::new(false),
+            retired: atomic::AtomicPtr::new(core::ptr::null_mut()),
+        }
     }
 
     /// Stores the given `ptr` in the Hazard-Ptr-Frame, by either reusing an
     /// existing empty Hazard-Ptr or creating a new Hazard-Ptr and adding it to
-    /// the Hazard-Ptr-Frame
-    pub fn store(&self, ptr: *mut T) {
-        let mut latest_ptr = self.ptrs;
-        for current_ptr in self.iter() {
-            let current = unsafe { &*current_ptr };
-
-            match current.store(ptr) {
-                Ok(_) => return,
-                Err(_) => {}
-            };
-            latest_ptr = current_ptr;
-        }
-
-        let mut current = unsafe { &*latest_ptr };
+    /// the Hazard-Ptr-Frame.
+    ///
+    /// If another Thread appends a Hazard-Ptr at the same Time, the Frame is
+    /// scanned again before retrying, as a Hazard-Ptr may have been emptied in
+    /// the meantime, which is then reused instead of growing the Frame.
+    ///
+    /// # Returns
+    /// The Hazard-Ptr that now stores the `ptr`
+    pub fn store(&self, ptr: *mut T) -> &HazardPtr<T> {
+        // The last Hazard-Ptr that was visited is still used after walking
+        // the Frame, so it must not be freed in the meantime
+        let _reading = self.read();
+
+        let mut latest_ptr = match self.try_reuse(ptr) {
+            Ok(reused) => return unsafe { &*reused },
+            Err(latest) => latest,
+        };
 
         let new_hazard = Box::new(HazardPtr::new(ptr));
         let new_hazard_ptr = Box::into_raw(new_hazard);
         loop {
-            match current.next.compare_exchange(
-                std::ptr::null_mut(),
+            let latest = unsafe { &*latest_ptr };
+            match latest.next.compare_exchange(
+                core::ptr::null_mut(),
                 new_hazard_ptr,
                 atomic::Ordering::SeqCst,
                 atomic::Ordering::SeqCst,
@@ -45,11 +85,171 @@ impl<T> HazardPtrFrame<T> {
                 Ok(_) => {
                     break;
                 }
-                Err(next) => {
-                    current = unsafe { &*next };
+                Err(_) => {
+                    latest_ptr = match self.try_reuse(ptr) {
+                        Ok(reused) => {
+                            // The new Hazard-Ptr was never published
+                            drop(unsafe { Box::from_raw(new_hazard_ptr) });
+                            return unsafe { &*reused };
+                        }
+                        Err(latest) => latest,
+                    };
                 }
             };
         }
+
+        let len = self.len.fetch_add(1, atomic::Ordering::AcqRel) + 1;
+        self.high_water_mark
+            .fetch_max(len, atomic::Ordering::AcqRel);
+
+        unsafe { &*new_hazard_ptr }
+    }
+
+    /// Attempts to store the `ptr` in any of the empty Hazard-Ptrs already in
+    /// the Frame.
+    ///
+    /// # Returns
+    /// * Ok(reused): The Ptr was stored in the Hazard-Ptr `reused`
+    /// * Err(last): Every Hazard-Ptr was in use, `last` is the last Hazard-Ptr
+    ///   that was visited, after which a new one can be appended
+    fn try_reuse(&self, ptr: *mut T) -> Result<*mut HazardPtr<T>, *mut HazardPtr<T>> {
+        let mut latest_ptr = self.ptrs;
+        for current_ptr in self.iter() {
+            let current = unsafe { &*current_ptr };
+
+            if current.store(ptr).is_ok() {
+                return Ok(current_ptr);
+            }
+            latest_ptr = current_ptr;
+        }
+
+        Err(latest_ptr)
+    }
+
+    /// The largest Number of Hazard-Ptrs this Frame contained at once, since
+    /// it was last shrunk
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark.load(atomic::Ordering::Acquire)
+    }
+
+    /// Removes the trailing empty Hazard-Ptrs, so that at most `keep`
+    /// Hazard-Ptrs (but always at least one) remain in the Frame. Only the
+    /// Hazard-Ptrs after the last one that still protects a Ptr are removed.
+    ///
+    /// # Concurrency
+    /// This is safe to call concurrently with `store` and with other Threads
+    /// walking the Frame. Every Hazard-Ptr is only removed once it was
+    /// confirmed to be empty, by retiring it with a CAS, after which `store`
+    /// can no longer use it. The last Hazard-Ptr is sealed as well, so no new
+    /// Hazard-Ptr can be appended after the removed ones. If the Frame grew in
+    /// the meantime, nothing is removed.
+    ///
+    /// The removed Hazard-Ptrs are only freed once no Thread is walking the
+    /// Frame anymore, otherwise this is retried by the next Call. While a
+    /// previous Call is still waiting for that, or another Thread is shrinking
+    /// the Frame right now, nothing is removed either.
+    ///
+    /// # Returns
+    /// The Number of Hazard-Ptrs that were removed
+    pub fn shrink_to(&self, keep: usize) -> usize {
+        if self.shrinking.swap(true, atomic::Ordering::AcqRel) {
+            return 0;
+        }
+
+        let removed = if self.free_retired() {
+            self.remove_trailing(core::cmp::max(keep, 1))
+        } else {
+            0
+        };
+
+        self.shrinking.store(false, atomic::Ordering::Release);
+        removed
+    }
+
+    /// Retires and unlinks the trailing empty Hazard-Ptrs beyond `keep`, see
+    /// [`shrink_to`](Self::shrink_to), which must only be called by a single
+    /// Thread at a Time
+    fn remove_trailing(&self, keep: usize) -> usize {
+        let hazard_ptrs: Vec<_> = self.iter().collect();
+        if hazard_ptrs.len() <= keep {
+            return 0;
+        }
+
+        let mut first_retired = hazard_ptrs.len();
+        for index in (keep..hazard_ptrs.len()).rev() {
+            let hazard_ptr = unsafe { &*hazard_ptrs[index] };
+            if !hazard_ptr.try_retire() {
+                break;
+            }
+            first_retired = index;
+        }
+
+        let retired = &hazard_ptrs[first_retired..];
+        if retired.is_empty() {
+            return 0;
+        }
+
+        let last = unsafe { &*hazard_ptrs[hazard_ptrs.len() - 1] };
+        if last
+            .next
+            .compare_exchange(
+                core::ptr::null_mut(),
+                HazardPtr::sealed(),
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            )
+            .is_err()
+        {
+            // A new Hazard-Ptr was appended in the meantime, which still has
+            // to be reachable
+            for hazard_ptr in retired {
+                unsafe { &**hazard_ptr }.unretire();
+            }
+            return 0;
+        }
+
+        // Nothing can be appended to the last kept Hazard-Ptr, as long as it
+        // still points to the retired ones
+        let last_kept = unsafe { &*hazard_ptrs[first_retired - 1] };
+        last_kept
+            .next
+            .store(core::ptr::null_mut(), atomic::Ordering::SeqCst);
+        self.retired
+            .store(hazard_ptrs[first_retired], atomic::Ordering::Release);
+
+        let len = self.len.fetch_sub(retired.len(), atomic::Ordering::AcqRel) - retired.len();
+        self.high_water_mark.store(len, atomic::Ordering::Release);
+
+        self.free_retired();
+        retired.len()
+    }
+
+    /// Frees the Hazard-Ptrs removed by a previous Call to
+    /// [`shrink_to`](Self::shrink_to), if no Thread could still be visiting
+    /// them.
+    ///
+    /// # Returns
+    /// Whether there are no retired Hazard-Ptrs left
+    fn free_retired(&self) -> bool {
+        let mut current = self.retired.load(atomic::Ordering::Acquire);
+        if current.is_null() {
+            return true;
+        }
+
+        // Every Thread that starts walking after the Hazard-Ptrs were
+        // unlinked can not reach them anymore
+        if self.readers.load(atomic::Ordering::SeqCst) != 0 {
+            return false;
+        }
+
+        self.retired
+            .store(core::ptr::null_mut(), atomic::Ordering::Release);
+        while current != HazardPtr::sealed() {
+            let hazard_ptr = unsafe { Box::from_raw(current) };
+            current = hazard_ptr.next.load(atomic::Ordering::Acquire);
+        }
+
+        true
     }
 
     /// Clears the entire Hazard-Ptr-Frame
@@ -61,45 +261,158 @@ impl<T> HazardPtrFrame<T> {
         }
     }
 
+    /// Removes the given `ptr` from the Hazard-Ptr-Frame, so it is no longer
+    /// protected by this Frame, while all the other Ptrs stay protected.
+    ///
+    /// # Returns
+    /// Whether or not the Ptr was stored in the Frame
+    pub fn remove(&self, ptr: *mut T) -> bool {
+        for current_ptr in self.iter() {
+            let current = unsafe { &*current_ptr };
+
+            if current.protects(ptr) {
+                current.reset();
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Whether or not any Hazard-Ptr in the Frame currently protects the
+    /// given Ptr, without gathering all the Roots of the Frame.
+    ///
+    /// The Ptrs are compared exactly, so a tagged Ptr only matches the same
+    /// tagged Ptr and Null is never protected
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    pub fn protects(&self, ptr: *mut T) -> bool {
+        self.iter().any(|current_ptr| {
+            let current = unsafe { &*current_ptr };
+            current.protects(ptr)
+        })
+    }
+
     /// Gathers all the Ptrs stored in the Hazard-Ptr-Frame
+    #[cfg(test)]
     pub fn roots(&self) -> Vec<*mut T> {
         let mut result = Vec::new();
+        self.roots_into(&mut result);
+        result
+    }
 
+    /// Appends all the Ptrs stored in the Hazard-Ptr-Frame to the given
+    /// Buffer, which allows the same Buffer to be reused for many Frames
+    pub fn roots_into(&self, out: &mut Vec<*mut T>) {
         for current_ptr in self.iter() {
             let current = unsafe { &*current_ptr };
 
             match current.ptr() {
-                Some(ptr) => result.push(ptr),
+                Some(ptr) => out.push(ptr),
                 None => {}
             };
         }
+    }
 
-        result
+    /// Marks the current Thread as walking the Frame, until the returned
+    /// Guard is dropped, which prevents removed Hazard-Ptrs from being freed
+    fn read(&self) -> ReadGuard<'_, T> {
+        self.readers.fetch_add(1, atomic::Ordering::SeqCst);
+        ReadGuard { frame: self }
     }
 
     /// Creates an Iterator over all the Hazard-Ptr's contained in the
     /// Hazard-Ptr-Frame
-    fn iter(&self) -> HazardPtrIter<T> {
-        HazardPtrIter { current: self.ptrs }
+    fn iter(&self) -> HazardPtrIter<'_, T> {
+        HazardPtrIter {
+            _reading: self.read(),
+            current: self.ptrs,
+        }
+    }
+}
+
+/// Marks a Thread as walking a Hazard-Ptr-Frame, see [`HazardPtrFrame::read`]
+#[cfg(feature = "std")]
+struct ReadGuard<'a, T> {
+    frame: &'a HazardPtrFrame<T>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> Drop for ReadGuard<'a, T> {
+    fn drop(&mut self) {
+        self.frame.readers.fetch_sub(1, atomic::Ordering::SeqCst);
+    }
+}
+
+/// Protects a single Ptr using a Hazard-Ptr for as long as the Guard is
+/// alive, obtained using [`Allocator::protect`](crate::Allocator::protect).
+///
+/// Once the Guard is dropped, its Hazard-Ptr is reset again and the Ptr is no
+/// longer protected
+#[cfg(feature = "std")]
+pub struct HazardGuard<'a, T> {
+    hazard_ptr: &'a HazardPtr<T>,
+    ptr: *mut T,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> HazardGuard<'a, T> {
+    pub(crate) fn new(frame: &'a HazardPtrFrame<T>, ptr: *mut T) -> Self {
+        debug_assert!(!ptr.is_null(), "a HazardGuard can not protect Null");
+
+        let hazard_ptr = frame.store(ptr);
+        Self { hazard_ptr, ptr }
+    }
+
+    /// The Ptr protected by this Guard
+    pub fn ptr(&self) -> *mut T {
+        self.ptr
+    }
+
+    /// Moves the Protection of this Guard to the given Ptr and returns the
+    /// previously protected Ptr.
+    ///
+    /// The Hazard-Ptr of the Guard is replaced atomically, so the old Ptr
+    /// stays protected until the new one is, which allows walking from one
+    /// Node to the next one hand-over-hand. Like with
+    /// [`Allocator::protect`](crate::Allocator::protect), the new Ptr should
+    /// only be used after a following
+    /// [`validate_read`](crate::Allocator::validate_read) succeeded
+    pub fn swap(&mut self, ptr: *mut T) -> *mut T {
+        debug_assert!(!ptr.is_null(), "a HazardGuard can not protect Null");
+
+        self.hazard_ptr.swap(ptr);
+        core::mem::replace(&mut self.ptr, ptr)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> Drop for HazardGuard<'a, T> {
+    fn drop(&mut self) {
+        self.hazard_ptr.reset();
     }
 }
 
 /// An Iterator over all the Hazard-Ptr's in a Hazard-Ptr-Frame
-struct HazardPtrIter<T> {
+#[cfg(feature = "std")]
+struct HazardPtrIter<'a, T> {
+    _reading: ReadGuard<'a, T>,
     current: *mut HazardPtr<T>,
 }
 
-impl<T> Iterator for HazardPtrIter<T> {
+#[cfg(feature = "std")]
+impl<'a, T> Iterator for HazardPtrIter<'a, T> {
     type Item = *mut HazardPtr<T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current.is_null() {
+        if self.current.is_null() || self.current == HazardPtr::sealed() {
             return None;
         }
 
         let ptr = self.current;
         let current = unsafe { &*self.current };
-        self.current = current.next.load(atomic::Ordering::Acquire);
+        // Pairs with the Check for Readers in `free_retired`, so either the
+        // unlinked Hazard-Ptrs are not visited or they are not freed yet
+        self.current = current.next.load(atomic::Ordering::SeqCst);
 
         Some(ptr)
     }
@@ -129,6 +442,34 @@ mod tests {
         frame.store(234 as *mut u8);
     }
 
+    #[test]
+    fn store_remove_concurrent() {
+        const THREADS: usize = 4;
+
+        let frame = std::sync::Arc::new(HazardPtrFrame::<usize>::new());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let frame = frame.clone();
+                std::thread::spawn(move || {
+                    let ptr = ((thread + 1) * 0x10) as *mut usize;
+                    for _ in 0..10_000 {
+                        frame.store(ptr);
+                        assert!(frame.remove(ptr));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // Every Thread only ever holds a single Ptr, so the freed Hazard-Ptrs
+        // are reused instead of growing the Frame
+        assert!(frame.iter().count() <= 2 * THREADS);
+        assert!(frame.roots().is_empty());
+    }
+
     #[test]
     fn store_roots() {
         let frame = HazardPtrFrame::new();
@@ -157,4 +498,139 @@ mod tests {
         let result = frame.roots();
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn remove() {
+        let frame = HazardPtrFrame::new();
+
+        frame.store(123 as *mut u8);
+        frame.store(234 as *mut u8);
+
+        assert!(frame.remove(123 as *mut u8));
+        assert_eq!(vec![234 as *mut u8], frame.roots());
+
+        assert!(!frame.remove(123 as *mut u8));
+        assert!(!frame.remove(std::ptr::null_mut()));
+
+        // The freed Hazard-Ptr is reused by the next Store
+        frame.store(345 as *mut u8);
+        assert_eq!(vec![345 as *mut u8, 234 as *mut u8], frame.roots());
+    }
+
+    #[test]
+    fn protects() {
+        let frame = HazardPtrFrame::new();
+        assert!(!frame.protects(std::ptr::null_mut()));
+
+        frame.store(123 as *mut u8);
+        frame.store(234 as *mut u8);
+        assert!(frame.protects(123 as *mut u8));
+        assert!(frame.protects(234 as *mut u8));
+        assert!(!frame.protects(345 as *mut u8));
+        assert!(!frame.protects(std::ptr::null_mut()));
+
+        frame.remove(123 as *mut u8);
+        assert!(!frame.protects(123 as *mut u8));
+        assert!(frame.protects(234 as *mut u8));
+
+        frame.clear();
+        assert!(!frame.protects(234 as *mut u8));
+    }
+
+    #[test]
+    fn shrink_to() {
+        let frame = HazardPtrFrame::new();
+
+        for ptr in 0x11..=0x14 {
+            frame.store(ptr as *mut u8);
+        }
+        assert_eq!(4, frame.high_water_mark());
+
+        // The last Hazard-Ptr still protects something
+        assert_eq!(0, frame.shrink_to(1));
+
+        frame.remove(0x14 as *mut u8);
+        frame.remove(0x13 as *mut u8);
+        frame.remove(0x11 as *mut u8);
+        assert_eq!(2, frame.shrink_to(1));
+        assert_eq!(vec![0x12 as *mut u8], frame.roots());
+        assert_eq!(2, frame.iter().count());
+        assert_eq!(2, frame.high_water_mark());
+
+        frame.clear();
+        assert_eq!(1, frame.shrink_to(0));
+        assert_eq!(1, frame.iter().count());
+        assert_eq!(1, frame.high_water_mark());
+
+        frame.store(0x15 as *mut u8);
+        frame.store(0x16 as *mut u8);
+        assert_eq!(vec![0x15 as *mut u8, 0x16 as *mut u8], frame.roots());
+        assert_eq!(2, frame.high_water_mark());
+    }
+
+    #[test]
+    fn shrink_to_while_walking() {
+        let frame = HazardPtrFrame::new();
+        for ptr in 0x11..=0x14 {
+            frame.store(ptr as *mut u8);
+        }
+        frame.clear();
+
+        // The removed Hazard-Ptrs may still be visited, so they are only
+        // freed by the next Call
+        let walking = frame.iter();
+        assert_eq!(3, frame.shrink_to(1));
+        assert!(!frame.retired.load(atomic::Ordering::SeqCst).is_null());
+        assert_eq!(0, frame.shrink_to(1));
+        drop(walking);
+
+        assert_eq!(0, frame.shrink_to(1));
+        assert!(frame.retired.load(atomic::Ordering::SeqCst).is_null());
+        assert_eq!(1, frame.iter().count());
+    }
+
+    #[test]
+    fn shrink_to_concurrent() {
+        let frame = HazardPtrFrame::new();
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for round in 0..2_000 {
+                    let ptrs: Vec<_> = (1..=(round % 8) + 1).map(|p| (p * 8) as *mut u8).collect();
+                    for ptr in ptrs.iter() {
+                        frame.store(*ptr);
+                    }
+
+                    // Every stored Ptr is still protected
+                    let mut roots = frame.roots();
+                    roots.sort();
+                    assert_eq!(ptrs, roots);
+
+                    frame.clear();
+                }
+            });
+
+            for _ in 0..2_000 {
+                frame.shrink_to(1);
+                let _ = frame.roots();
+            }
+        });
+
+        frame.shrink_to(1);
+        assert_eq!(1, frame.iter().count());
+    }
+
+    #[test]
+    fn roots_into() {
+        let frame = HazardPtrFrame::<u8>::new();
+        frame.store(0x12 as *mut u8);
+        frame.store(0x23 as *mut u8);
+
+        // The Ptrs are appended after the existing Content of the Buffer
+        let mut result = vec![0x34 as *mut u8];
+        frame.roots_into(&mut result);
+        assert_eq!(3, result.len());
+        assert_eq!(0x34 as *mut u8, result[0]);
+        assert_eq!(frame.roots(), result[1..].to_vec());
+    }
 }
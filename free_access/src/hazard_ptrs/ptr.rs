@@ -1,18 +1,38 @@
-use std::sync::atomic;
+use crate::sync::atomic;
 
+/// A single Hazard-Ptr, which protects at most one Ptr at a time and can be
+/// linked together with other Hazard-Ptrs to form a List of them
 pub struct HazardPtr<T> {
     ptr: atomic::AtomicPtr<T>,
-    pub next: atomic::AtomicPtr<Self>,
+    /// Only used to link the Hazard-Ptrs of a Frame, which needs the Allocator
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) next: atomic::AtomicPtr<Self>,
 }
 
 impl<T> HazardPtr<T> {
+    /// Creates a new Hazard-Ptr, which protects the given Ptr, or nothing if
+    /// it is Null
     pub fn new(data: *mut T) -> Self {
         Self {
             ptr: atomic::AtomicPtr::new(data),
-            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            next: atomic::AtomicPtr::new(core::ptr::null_mut()),
         }
     }
 
+    /// The Value stored in a Hazard-Ptr that is about to be freed, which
+    /// prevents it from being reused by a `store`
+    fn retired() -> *mut T {
+        usize::MAX as *mut T
+    }
+
+    /// The Value stored in the `next` Ptr of the last Hazard-Ptr of a Frame,
+    /// while it is about to be freed, which prevents a new Hazard-Ptr from
+    /// being appended after it
+    #[cfg(feature = "std")]
+    pub(crate) fn sealed() -> *mut Self {
+        usize::MAX as *mut Self
+    }
+
     /// This attempts to load the Protected-Ptr stored in this Hazard-Ptr.
     ///
     /// # Returns
@@ -20,19 +40,51 @@ impl<T> HazardPtr<T> {
     /// * None: The Hazard-Ptr is currently empty and does not protect anything
     pub fn ptr(&self) -> Option<*mut T> {
         let ptr = self.ptr.load(atomic::Ordering::Acquire);
-        if ptr.is_null() {
+        if ptr.is_null() || ptr == Self::retired() {
             None
         } else {
             Some(ptr)
         }
     }
 
+    /// Whether or not this Hazard-Ptr currently protects the given Ptr, an
+    /// empty or retired Hazard-Ptr never protects anything, not even Null
+    pub fn protects(&self, ptr: *mut T) -> bool {
+        self.ptr() == Some(ptr)
+    }
+
+    /// Attempts to retire this Hazard-Ptr, which only succeeds if it is
+    /// currently empty. A retired Hazard-Ptr can not be used by `store`
+    /// anymore, until it is handed back using [`unretire`](Self::unretire)
+    #[cfg(feature = "std")]
+    pub fn try_retire(&self) -> bool {
+        self.ptr
+            .compare_exchange(
+                core::ptr::null_mut(),
+                Self::retired(),
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            )
+            .is_ok()
+    }
+
+    /// Makes a retired Hazard-Ptr empty again, so it can be used by `store`
+    #[cfg(feature = "std")]
+    pub fn unretire(&self) {
+        let _ = self.ptr.compare_exchange(
+            Self::retired(),
+            core::ptr::null_mut(),
+            atomic::Ordering::SeqCst,
+            atomic::Ordering::SeqCst,
+        );
+    }
+
     /// Attempts to store the `data` in an empty Hazard-Ptr, if the Hazard-Ptr
     /// already contains a valid Ptr (Non-Null), then this will fail and return
     /// the given `data`-Ptr
     pub fn store(&self, data: *mut T) -> Result<(), *mut T> {
         match self.ptr.compare_exchange(
-            std::ptr::null_mut(),
+            core::ptr::null_mut(),
             data,
             atomic::Ordering::SeqCst,
             atomic::Ordering::SeqCst,
@@ -42,10 +94,31 @@ impl<T> HazardPtr<T> {
         }
     }
 
-    /// Resets the Ptr stored in the Hazard-Ptr
+    /// Atomically replaces the Ptr stored in the Hazard-Ptr with `data`,
+    /// regardless of whether or not it is currently empty, and returns the
+    /// previously stored Ptr (which may be Null).
+    ///
+    /// Unlike calling `reset` followed by `store`, there is no Point in time
+    /// where the Hazard-Ptr protects nothing, which allows a Reader to move
+    /// from one Node to the next one without an unprotected Window
+    pub fn swap(&self, data: *mut T) -> *mut T {
+        self.ptr.swap(data, atomic::Ordering::SeqCst)
+    }
+
+    /// Resets the Ptr stored in the Hazard-Ptr, a retired Hazard-Ptr stays
+    /// retired
     pub fn reset(&self) {
-        self.ptr
-            .store(std::ptr::null_mut(), atomic::Ordering::Release);
+        let _ = self.ptr.fetch_update(
+            atomic::Ordering::Release,
+            atomic::Ordering::Acquire,
+            |ptr| {
+                if ptr == Self::retired() {
+                    None
+                } else {
+                    Some(core::ptr::null_mut())
+                }
+            },
+        );
     }
 }
 
@@ -84,6 +157,52 @@ mod tests {
         assert_eq!(0x23 as *mut usize, ptr.ptr.load(atomic::Ordering::SeqCst));
     }
 
+    #[test]
+    fn swap() {
+        let ptr: HazardPtr<usize> = HazardPtr::new(std::ptr::null_mut());
+
+        assert_eq!(std::ptr::null_mut(), ptr.swap(0x12 as *mut usize));
+        assert_eq!(Some(0x12 as *mut usize), ptr.ptr());
+
+        assert_eq!(0x12 as *mut usize, ptr.swap(0x23 as *mut usize));
+        assert_eq!(Some(0x23 as *mut usize), ptr.ptr());
+
+        // A swapped Hazard-Ptr is still occupied for `store`
+        assert_eq!(Err(0x34 as *mut usize), ptr.store(0x34 as *mut usize));
+    }
+
+    #[test]
+    fn retire() {
+        let ptr: HazardPtr<usize> = HazardPtr::new(0x12 as *mut usize);
+        assert!(!ptr.try_retire());
+
+        ptr.reset();
+        assert!(ptr.try_retire());
+        assert_eq!(None, ptr.ptr());
+        assert!(ptr.store(0x23 as *mut usize).is_err());
+
+        // Resetting does not hand the Hazard-Ptr out again
+        ptr.reset();
+        assert!(ptr.store(0x23 as *mut usize).is_err());
+
+        ptr.unretire();
+        assert_eq!(Ok(()), ptr.store(0x23 as *mut usize));
+    }
+
+    #[test]
+    fn protects() {
+        let ptr: HazardPtr<usize> = HazardPtr::new(std::ptr::null_mut());
+        assert!(!ptr.protects(std::ptr::null_mut()));
+
+        assert_eq!(Ok(()), ptr.store(0x12 as *mut usize));
+        assert!(ptr.protects(0x12 as *mut usize));
+        assert!(!ptr.protects(0x23 as *mut usize));
+
+        ptr.reset();
+        assert!(!ptr.protects(0x12 as *mut usize));
+        assert!(!ptr.protects(std::ptr::null_mut()));
+    }
+
     #[test]
     fn ptr() {
         let ptr: HazardPtr<usize> = HazardPtr::new(std::ptr::null_mut());
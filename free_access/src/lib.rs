@@ -1,29 +1,97 @@
 #![deny(missing_docs)]
 #![warn(rust_2018_idioms)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 //! TODO
 //!
 //! # General Strucuture
 //!
-
+//! # Features
+//! * `std` (enabled by default): The [`Allocator`] itself, which relies on
+//!   Thread-Locals. Without it, only the lock-free [`primitives`] are
+//!   available, which only need `core` and `alloc`
+//! * `serde`: Implements `Serialize` and `Deserialize` for the
+//!   [`HeapSnapshot`], so it can be dumped as JSON for external Tools
+
+extern crate alloc;
+// Allows the Code generated by the Macros to refer to this Crate by Name in
+// the Tests of this Crate itself
+#[cfg(test)]
+extern crate self as free_access;
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
 use allocator::PageList;
 pub use free_access_macros::*;
+#[cfg(feature = "std")]
 use thread_local::ThreadLocal;
 
-use std::{
-    collections::{HashMap, HashSet},
-    sync::atomic,
-};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+mod sync;
+#[cfg(feature = "std")]
+use sync::atomic;
+
+mod phase;
+use phase::Phase;
+
+#[cfg(feature = "std")]
+mod backoff;
+#[cfg(feature = "std")]
+use backoff::Backoff;
 
+#[cfg(feature = "std")]
 mod dirty;
-use dirty::{DirtyValue, Udirty};
 
 mod hazard_ptrs;
+#[cfg(feature = "std")]
+pub use hazard_ptrs::HazardGuard;
+#[cfg(feature = "std")]
 use hazard_ptrs::HazardPtrFrame;
 
 mod allocator;
-mod markstack;
+pub use allocator::NodeMarks;
+#[cfg(feature = "std")]
+mod config;
+#[cfg(feature = "std")]
+pub use config::{AllocatorBuilder, AllocatorConfig};
+
+mod policy;
+pub use policy::{CollectionPolicy, NeverCollect, ThresholdPolicy};
+
+mod observer;
+pub use observer::GcObserver;
+
+mod stats;
+#[cfg(feature = "std")]
+use stats::{CollectionCounters, SweepStats};
+pub use stats::{CollectionReport, GcStats, PolicyStats};
 
+mod snapshot;
+pub use snapshot::{HeapSnapshot, SnapshotNode};
+
+mod child;
+pub use child::{load_child, ChildSlot};
+mod markstack;
+#[cfg(feature = "std")]
+mod pin;
+#[cfg(feature = "std")]
+mod registration;
+#[cfg(feature = "std")]
+pub use registration::LocalHandle;
+pub mod primitives;
+pub mod tagging;
+#[cfg(feature = "std")]
+pub use pin::PinGuard;
+#[cfg(feature = "std")]
+mod txn;
+#[cfg(feature = "std")]
+pub use txn::{ReadTxn, RestartLimitExceeded, Retry};
+
+#[cfg(feature = "std")]
 struct Arbiter(atomic::AtomicU8);
+#[cfg(feature = "std")]
 impl Arbiter {
     pub fn new() -> Self {
         Self(atomic::AtomicU8::new(0))
@@ -33,9 +101,11 @@ impl Arbiter {
         self.0.load(atomic::Ordering::Acquire)
     }
 
+    /// The Frame that should be used by the next Write-Only Period, which is
+    /// always the one not currently in use
     pub fn next(&self) -> u8 {
         let prev = self.0.load(atomic::Ordering::Acquire);
-        prev % 2
+        prev ^ 1
     }
 
     pub fn store(&self, n_val: u8) {
@@ -43,256 +113,1257 @@ impl Arbiter {
     }
 }
 
+#[cfg(feature = "std")]
 mod local;
+#[cfg(feature = "std")]
 use local::{Local, MarkNodeState};
 
+/// The Number of Attempts an Allocation makes to obtain a free Node, before
+/// giving up
+#[cfg(feature = "std")]
+const MAX_ALLOCATION_ATTEMPTS: usize = 3;
+
+/// The Number of Hazard-Ptrs every Frame of an Operation keeps, when it is
+/// shrunk after a Restart
+#[cfg(feature = "std")]
+const KEPT_HAZARD_PTRS: usize = 16;
+
 /// The Allocator that should be used to allocate/create Nodes of the
 /// Datastructure
+///
+/// # Sharing between Threads
+/// The Allocator is `Send` and `Sync`, as long as the Nodes and Globals are,
+/// so it can be shared between Threads by wrapping it in an
+/// [`Arc`](std::sync::Arc) or by storing it inside of the Datastructure.
+/// Every Thread automatically gets its own Local-State the first time it
+/// uses the Allocator, so no further Setup is needed. Once a Thread exits,
+/// its Local-State may be reused by a Thread that is started afterwards.
+///
+/// ```ignore
+/// let allocator = Arc::new(Allocator::new(globals));
+///
+/// let thread_allocator = allocator.clone();
+/// std::thread::spawn(move || {
+///     let node = thread_allocator.allocate(Node::new());
+///     // ...
+///     thread_allocator.flush_local();
+/// });
+/// ```
+///
+/// # Limit on the Number of Collections
+/// Every Collection starts a new Phase and the Phase is packed together with
+/// other Data in several Atomics, which limits it to `u32::MAX`. An Allocator
+/// can therefore perform at most `u32::MAX` (about 4.3 Billion) Collections
+/// over its entire Lifetime and panics when it would start another one after
+/// that, instead of wrapping around and corrupting the Marks of the Nodes.
+/// Even at one Collection every Millisecond, this Limit is only reached after
+/// about 49 Days, but Applications that collect more often or run for longer
+/// should replace their Allocator before that happens.
+#[cfg(feature = "std")]
 pub struct Allocator<T, G> {
     phase_index: atomic::AtomicU64,
     local: ThreadLocal<Local<T>>,
     allocation_pool: allocator::GlobalAllocPool<T>,
     pages: PageList<T>,
+    overflow: allocator::OverflowList<T>,
     sweep_chunk_index: atomic::AtomicU64,
-    globals: G,
+    /// The Number of Threads that are currently sweeping, the last one to
+    /// finish runs the deferred Functions
+    sweepers: atomic::AtomicUsize,
+    collection: CollectionCounters,
+    /// Only locked exclusively by [`Allocator::with_globals_mut`], so the
+    /// Roots never change while a Collection is gathering them
+    globals: std::sync::RwLock<G>,
+    config: AllocatorConfig,
+    /// `None` behaves like [`NeverCollect`], but skips gathering the Stats
+    policy: Option<Box<dyn CollectionPolicy>>,
+    observer: Option<Box<dyn GcObserver>>,
+    /// The Functions registered using [`Allocator::defer`], together with the
+    /// Phase they were registered in
+    deferred: std::sync::Mutex<Vec<(u64, DeferredFn)>>,
+}
+
+/// A Function that is run once the Nodes, that were unreachable when it was
+/// registered, have been reclaimed
+#[cfg(feature = "std")]
+type DeferredFn = Box<dyn FnOnce() + Send>;
+
+#[cfg(feature = "std")]
+impl<T, G> Drop for Allocator<T, G> {
+    /// Drops the Data of all the Nodes that still hold Data, because they
+    /// were not reclaimed yet, the Pages themselves as well as the Pools and
+    /// MarkStacks are freed by their own Drop-Implementations afterwards.
+    ///
+    /// Any deferred Functions, that did not run yet, are run afterwards
+    fn drop(&mut self) {
+        for page in self.pages.iter() {
+            for node in page.nodes.iter() {
+                if !node.take_occupied() {
+                    continue;
+                }
+
+                unsafe { core::ptr::drop_in_place(node.get_data_ptr()) };
+            }
+        }
+
+        let deferred = core::mem::take(
+            self.deferred
+                .get_mut()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        );
+        for (_, func) in deferred {
+            func();
+        }
+    }
+}
+
+/// The Reason why an Allocation failed
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AllocError {
+    /// The Phase changed while trying to obtain a free Node, so retrying the
+    /// Allocation will likely succeed
+    PhaseMismatch,
+    /// No free Node could be found, even after running a Collection and
+    /// growing the Heap
+    OutOfMemory,
 }
 
 /// This is very similiar to the Standard Box with the main Difference being
 /// that this "Box" is tied to the Allocator and does not free the Memory
 /// to the OS itself when dropped
+///
+/// # Safety-Invariant
+/// The Box can be dereferenced to access the Node, but this is only valid as
+/// long as the Node is still reachable for the Allocator (from the Globals or
+/// the Hazard-Ptrs). Once the Node is unreachable, the next Collection may
+/// reclaim it and hand it out again, even though the Box still exists
 pub struct AoaBox<T> {
     inner: *mut T,
 }
 
 impl<T> AoaBox<T> {
-    /// TODO
+    /// Returns the raw Ptr to the Node, which is needed for example to store
+    /// it in an `AtomicPtr`
     pub fn ptr(&self) -> *mut T {
         self.inner
     }
+
+    /// Consumes the Box and returns the raw Ptr to the Node, which can later
+    /// be turned back into a Box using [`from_raw`](Self::from_raw)
+    pub fn into_raw(self) -> *mut T {
+        self.inner
+    }
+
+    /// Recreates a Box from a Ptr that was previously obtained from an
+    /// [`AoaBox`], using [`into_raw`](Self::into_raw) or
+    /// [`ptr`](Self::ptr).
+    ///
+    /// # Safety
+    /// The Ptr must point to a Node that was allocated by the same
+    /// [`Allocator`], the Datastructure uses, and it must not be tagged. The
+    /// same Safety-Invariant as for every other Box applies to the result
+    pub unsafe fn from_raw(ptr: *mut T) -> Self {
+        Self { inner: ptr }
+    }
+
+    /// Projects the Box to a Field of the Node and returns a raw Ptr to it,
+    /// for example to keep a stable Ptr to just the Key stored in a Node.
+    ///
+    /// The Ptr points into the Node itself, so it follows the same
+    /// Safety-Invariant as the Box and is only valid to dereference while the
+    /// Node is still reachable. As it is derived from a shared Reference, the
+    /// Field must only be read through it, unless it uses interior Mutability
+    pub fn project<U, F>(&self, f: F) -> *const U
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        f(unsafe { &*self.inner }) as *const U
+    }
+
+    /// Loads the current Marks of the Node backing this Box
+    pub fn marks(&self) -> NodeMarks {
+        let node = unsafe { allocator::PageNode::from_data_ptr(self.inner) };
+        node.load_marks()
+    }
+
+    /// Attempts to atomically replace the Marks of the Node backing this Box,
+    /// this only succeeds if the current Marks are equal to `expected`,
+    /// otherwise the Marks that were observed instead are returned
+    pub fn set_marks(&self, expected: NodeMarks, new: NodeMarks) -> Result<(), NodeMarks> {
+        let node = unsafe { allocator::PageNode::from_data_ptr(self.inner) };
+        node.update_marks(expected, new)
+    }
 }
 
+impl<T> core::ops::Deref for AoaBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.inner }
+    }
+}
+
+impl<T> core::ops::DerefMut for AoaBox<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.inner }
+    }
+}
+
+#[cfg(feature = "std")]
 impl<N, G> Allocator<N, G>
 where
     N: DataStructureNode,
     G: DataStructureGlobals<N>,
 {
-    /// TODO
-    #[tracing::instrument(skip(globals))]
+    /// Creates a new Allocator using the default Configuration, see
+    /// [`AllocatorBuilder`] for more control over the Configuration
     pub fn new(globals: G) -> Self {
+        AllocatorBuilder::new(globals).build()
+    }
+
+    /// Creates a new Builder to configure the Allocator
+    pub fn builder(globals: G) -> AllocatorBuilder<G> {
+        AllocatorBuilder::new(globals)
+    }
+
+    /// Creates a new Allocator using the given Configuration
+    ///
+    /// # Panics
+    /// If the `allocation_buffer_size` of the Configuration is 0
+    #[tracing::instrument(skip(globals))]
+    pub fn with_config(globals: G, config: AllocatorConfig) -> Self {
+        Self::with_policy(globals, config, None, None)
+    }
+
+    /// Creates a new Allocator using the default Configuration on top of the
+    /// given Pages, instead of building its own, so Tests can control the
+    /// exact Layout of the Heap. The Page-Size of the Configuration is taken
+    /// from the Pages and all their Nodes are swept right away, like the
+    /// initial Pages of every other Allocator
+    #[cfg(test)]
+    pub(crate) fn new_with(globals: G, pages: PageList<N>) -> Self {
+        let config = AllocatorConfig {
+            page_size: pages.page_size(),
+            ..AllocatorConfig::default()
+        };
+        Self::with_pages(globals, config, pages, None, None)
+    }
+
+    fn with_policy(
+        globals: G,
+        config: AllocatorConfig,
+        policy: Option<Box<dyn CollectionPolicy>>,
+        observer: Option<Box<dyn GcObserver>>,
+    ) -> Self {
+        assert!(
+            config.allocation_buffer_size > 0,
+            "the allocation_buffer_size must be at least 1, a Buffer of size 0 can never hand out a Node"
+        );
+
+        let pages = PageList::new(config.page_size);
+        for _ in 1..config.initial_pages {
+            pages.grow();
+        }
+
+        Self::with_pages(globals, config, pages, policy, observer)
+    }
+
+    fn with_pages(
+        globals: G,
+        config: AllocatorConfig,
+        pages: PageList<N>,
+        policy: Option<Box<dyn CollectionPolicy>>,
+        observer: Option<Box<dyn GcObserver>>,
+    ) -> Self {
         tracing::debug!("Creating new Allocator");
 
         let result = Self {
             phase_index: atomic::AtomicU64::new(0),
             local: ThreadLocal::new(),
             allocation_pool: allocator::GlobalAllocPool::new(),
-            pages: PageList::new(256),
+            pages,
+            overflow: allocator::OverflowList::new(),
             sweep_chunk_index: atomic::AtomicU64::new(0),
-            globals,
+            sweepers: atomic::AtomicUsize::new(0),
+            collection: CollectionCounters::default(),
+            globals: std::sync::RwLock::new(globals),
+            config,
+            policy,
+            observer,
+            deferred: std::sync::Mutex::new(Vec::new()),
         };
 
-        result.sweep();
+        {
+            // The Nodes of the initial Sweep are handed to the other Threads
+            // once the Registration ends
+            let _registration = result
+                .config
+                .explicit_registration
+                .then(|| result.register());
+            result.sweep();
+        }
 
         result
     }
 
+    /// Returns the Local-State of the current Thread, creating it if this
+    /// Thread has not used the Allocator before, without checking whether
+    /// the Thread is registered
+    fn get_or_create_local(&self) -> &Local<N> {
+        self.local.get_or(|| {
+            Local::new(
+                self.config.allocation_buffer_size,
+                !self.config.explicit_registration,
+            )
+        })
+    }
+
+    /// Returns the Local-State of the current Thread, creating it if this
+    /// Thread has not used the Allocator before
+    ///
+    /// # Panics
+    /// If the Threads have to register explicitly and the current Thread is
+    /// not registered
+    fn get_local(&self) -> &Local<N> {
+        let local = self.get_or_create_local();
+        assert!(
+            local.registered.load(atomic::Ordering::Acquire),
+            "The current Thread has to be registered before using the Allocator"
+        );
+        local
+    }
+
+    /// Registers the current Thread, which is required before using the
+    /// Allocator if it was configured with
+    /// [`explicit_registration`](AllocatorConfig::explicit_registration).
+    ///
+    /// Once the returned Handle is dropped, the free Nodes buffered by this
+    /// Thread are moved back into the global Pool, like with
+    /// [`flush_local`](Self::flush_local), and its Hazard-Ptrs are released,
+    /// so the Thread is no longer considered by any Collection. Without
+    /// explicit Registration, the Thread stays registered after that.
+    ///
+    /// # Panics
+    /// If the current Thread is already registered with explicit Registration
+    pub fn register(&self) -> LocalHandle<'_, N, G> {
+        let local = self.get_or_create_local();
+        if self.config.explicit_registration {
+            let was_registered = local.registered.swap(true, atomic::Ordering::SeqCst);
+            assert!(!was_registered, "The current Thread is already registered");
+        }
+
+        LocalHandle::new(self)
+    }
+
+    /// Ends the Registration of the current Thread, see
+    /// [`register`](Self::register)
+    ///
+    /// The other Threads only look at the MarkStacks of registered Threads,
+    /// so if the Thread is still in the Middle of a Trace, for example after
+    /// a [`trace_step`](Self::trace_step), it first marks all the Nodes left
+    /// on its MarkStack, instead of dropping them
+    fn unregister(&self) {
+        self.flush_local();
+        if !self.config.explicit_registration {
+            return;
+        }
+
+        let local = self.get_local();
+        let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
+        let _ = self.mark_budget(local, local_phase, usize::MAX);
+        Self::clear_local(local);
+        local.registered.store(false, atomic::Ordering::SeqCst);
+    }
+
+    /// Clears all the Hazard-Ptrs and the Tracing-State of the given
+    /// Local-State
+    fn clear_local(local: &Local<N>) {
+        for frame in local.hazard_ptr_frames.iter() {
+            frame.clear();
+        }
+        local.pinned.clear();
+        local.guarded.clear();
+        local.arbiter.store(0);
+        local.mark_stack.clear();
+        local
+            .cur_traced
+            .store(std::ptr::null_mut(), atomic::Ordering::Release);
+    }
+
+    /// Returns the Local-State of every Thread to a consistent global State,
+    /// so a single Thread can reason about the entire Heap, for example
+    /// before taking a Snapshot of it or before shutting down.
+    ///
+    /// This first takes Part in a Collection that is still in Progress, so
+    /// its Tracing is completed, and afterwards moves the Buffers of all the
+    /// Threads into the global Pool, clears their Hazard-Ptrs (including
+    /// [`PinGuard`]s) and empties their MarkStacks.
+    ///
+    /// # Shutdown-Sequence
+    /// 1. Park all the other Threads using the Allocator, outside of any
+    ///    Operation on the Datastructure, so none of them holds a Guard
+    /// 2. Call `quiesce` from one of the remaining Threads
+    /// 3. Inspect the Heap, for example using [`stats`](Self::stats), or drop
+    ///    the Allocator
+    ///
+    /// The parked Threads may resume afterwards, they simply start their next
+    /// Operation with an empty Buffer.
+    ///
+    /// # Safety
+    /// The Allocator can not park the other Threads itself, so the Caller
+    /// must make sure that no other Thread uses the Allocator while this runs.
+    /// Additionally no [`HazardGuard`] or [`PinGuard`] may be alive, as the
+    /// Nodes they protect could be reclaimed by the next Collection
+    pub unsafe fn quiesce(&self) {
+        self.catch_up(self.get_local());
+
+        for local in self.local.iter() {
+            self.flush_buffer(local);
+            Self::clear_local(local);
+        }
+    }
+
+    /// Iterates over the Local-States of all the registered Threads
+    fn registered_locals(&self) -> impl Iterator<Item = &Local<N>> {
+        self.local
+            .iter()
+            .filter(|l| l.registered.load(atomic::Ordering::Acquire))
+    }
+
     /// Actually allocates the given Data
+    ///
+    /// # Panics
+    /// If no free Node could be obtained, see
+    /// [`try_allocate`](Self::try_allocate) for a non-panicking Version
     #[tracing::instrument(skip(self, data))]
     pub fn allocate(&self, data: N) -> AoaBox<N> {
+        match self.try_allocate(data) {
+            Ok(allocated) => allocated,
+            Err((_, e)) => panic!("Could not obtain a free Node: {:?}", e),
+        }
+    }
+
+    /// Attempts to allocate the given Data.
+    ///
+    /// If no free Node could be obtained, the Data is returned back to the
+    /// Caller together with the Reason for the Failure
+    #[tracing::instrument(skip(self, data))]
+    pub fn try_allocate(&self, mut data: N) -> Result<AoaBox<N>, (N, AllocError)> {
         tracing::debug!("Allocating");
 
-        let local = self.local.get_or_default();
-        for _ in 0..2 {
+        #[cfg(debug_assertions)]
+        self.get_local().assert_outside_globals_mut("Allocating");
+
+        if self.config.overflow_alloc && allocator::is_oversized::<N>() {
+            return Ok(self.allocate_overflow(data));
+        }
+
+        let local = self.get_local();
+        loop {
+            // A Collection started since the Buffer was filled, so its Nodes
+            // may be freed again by that Collection
+            let global_phase = self.phase_index.load(atomic::Ordering::Acquire);
+            local.alloc.discard_outdated(global_phase);
+
             if local.alloc.is_empty() {
+                // Checked before taking the Node, as a Collection afterwards
+                // would reclaim the new Node again, because it is not
+                // reachable yet
+                self.check_policy();
+            }
+
+            let refill = local.alloc.is_empty();
+            let mut error = AllocError::OutOfMemory;
+            for attempt in 0..MAX_ALLOCATION_ATTEMPTS {
+                if !local.alloc.is_empty() {
+                    break;
+                }
+
                 let lphase_index = local.phase_index.load(atomic::Ordering::Acquire);
                 tracing::debug!("Current Phase: {}", lphase_index);
 
                 match self.allocation_pool.pop(lphase_index) {
                     Ok(n_buffer) => {
-                        local.alloc.new_buffer(n_buffer);
+                        local.alloc.new_buffer(n_buffer, lphase_index);
                         break;
                     }
                     Err(e) => {
-                        tracing::debug!("Getting New-Pool: {:?}", e);
-                        self.reclaimation();
+                        error = match e {
+                            allocator::PopError::InvalidPhase => AllocError::PhaseMismatch,
+                            allocator::PopError::Empty => AllocError::OutOfMemory,
+                        };
+
+                        if attempt == 0 {
+                            tracing::debug!("Getting New-Pool: {:?}", e);
+                            self.reclaimation();
+                        } else {
+                            tracing::debug!("Growing Heap: {:?}", e);
+                            self.grow(local);
+                        }
                     }
                 };
             }
+
+            if refill {
+                // The Buffer may have been filled from the global Pool, by the
+                // Sweep of a Collection or by growing the Heap
+                self.collection.add_allocated(local.alloc.len());
+            }
+
+            let ptr = match local.alloc.pop() {
+                Some(ptr) => ptr,
+                None if self.config.overflow_alloc => return Ok(self.allocate_overflow(data)),
+                None => return Err((data, error)),
+            };
+
+            match self.write_node(local, ptr, data) {
+                Ok(allocated) => return Ok(allocated),
+                // A Collection started in the meantime, so the Node may be
+                // freed again and another one has to be taken
+                Err(returned) => data = returned,
+            }
         }
+    }
 
-        let ptr = local.alloc.pop().unwrap();
+    /// Writes the Data into the Node that was just taken from the Buffer of
+    /// the given Local.
+    ///
+    /// A Collection, that started after the Buffer was checked, may have
+    /// already freed the Node again and handed it to another Thread. So the
+    /// Node is published first and the Phase is checked again afterwards,
+    /// any Collection starting after that will see the Node and keep it.
+    ///
+    /// # Returns
+    /// The Data is returned again, if the Phase changed in the meantime, in
+    /// which case the Node must not be used
+    fn write_node(&self, local: &Local<N>, ptr: *mut N, data: N) -> Result<AoaBox<N>, N> {
+        local.allocating.store(ptr, atomic::Ordering::SeqCst);
+        if self.phase_index.load(atomic::Ordering::SeqCst) != local.alloc.phase() {
+            local
+                .allocating
+                .store(std::ptr::null_mut(), atomic::Ordering::Release);
+            return Err(data);
+        }
 
         unsafe { ptr.write(data) };
-        AoaBox { inner: ptr }
-    }
+        unsafe { allocator::PageNode::from_data_ptr(ptr) }.set_occupied();
+        local
+            .allocating
+            .store(std::ptr::null_mut(), atomic::Ordering::Release);
 
-    /// Forces the Allocator to start a Garbage-Collection Phase
-    pub fn force_gc(&self) {
-        self.reclaimation();
+        Ok(AoaBox { inner: ptr })
     }
 
-    /// TODO
-    pub fn restart(&self, local_ptrs: &[&mut *mut N]) {
-        // TODO
-        // Help reclaimation
-        self.reclaimation();
+    /// Allocates all the given Items at once, which takes the Nodes directly
+    /// from the Buffer of the current Thread as long as it has free Nodes and
+    /// only falls back to [`allocate`](Self::allocate) to obtain a new Buffer,
+    /// once it is drained.
+    ///
+    /// Like with every other Allocation, the Nodes are not protected until
+    /// they are reachable or protected by a Hazard-Ptr. If a Collection is
+    /// started while allocating the later Nodes, for example because the
+    /// Batch exceeds the free Nodes, the earlier Nodes may be reclaimed
+    /// again, which is detected by the following
+    /// [`begin_write_only`](Self::begin_write_only) failing.
+    ///
+    /// # Panics
+    /// Like [`allocate`](Self::allocate), if no free Node could be obtained
+    pub fn allocate_n<I>(&self, items: I) -> Vec<AoaBox<N>>
+    where
+        I: IntoIterator<Item = N>,
+    {
+        #[cfg(debug_assertions)]
+        self.get_local().assert_outside_globals_mut("Allocating");
+
+        let items = items.into_iter();
+        let mut result = Vec::with_capacity(items.size_hint().0);
+
+        if self.config.overflow_alloc && allocator::is_oversized::<N>() {
+            result.extend(items.map(|data| self.allocate_overflow(data)));
+            return result;
+        }
 
-        let local = self.local.get_or_default();
-        let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
-        local.dirty.update(
-            DirtyValue {
-                dirty: true,
-                phase: local_phase,
-            }
-            .to_u64(),
-            DirtyValue {
-                phase: local_phase,
-                dirty: false,
-            },
-        );
+        let local = self.get_local();
+        for data in items {
+            let data = match local.alloc.pop() {
+                Some(ptr) => match self.write_node(local, ptr, data) {
+                    Ok(allocated) => {
+                        result.push(allocated);
+                        continue;
+                    }
+                    Err(data) => data,
+                },
+                None => data,
+            };
 
-        let ar = local.arbiter.get();
-        let hazard_frame = &local.hazard_ptr_frames[ar as usize];
+            result.push(self.allocate(data));
+        }
 
-        todo!()
+        result
     }
 
-    /// This is used to attempt the Start of a Write-Only Period, if this
-    /// Returns an Error, the execution should be restarted at the previous
-    /// Read-Only Period
-    ///
-    /// # Parameters
-    /// * `local_ptrs`: The currently loaded Ptrs that will be used in the next
-    /// Stage, these should still contain the Tags, if the Datastructure uses
-    /// Tags (they should not be cleared here)
-    pub fn begin_write_only(&self, local_ptrs: &[*mut N]) -> Result<(), ()> {
-        let locals = self.local.get_or_default();
-
-        let next_arbiter = locals.arbiter.next();
+    /// Starts a Collection if the configured [`CollectionPolicy`] asks for it
+    fn check_policy(&self) {
+        let policy = match self.policy.as_ref() {
+            Some(p) => p,
+            None => return,
+        };
 
-        let hazard_ptr_frame = &locals.hazard_ptr_frames[next_arbiter as usize];
-        for p in local_ptrs {
-            hazard_ptr_frame.store(*p);
+        if policy.should_collect(&self.policy_stats()) {
+            tracing::debug!("Collection requested by the Policy");
+            self.reclaimation();
         }
+    }
 
-        let dirty = locals.dirty.get();
-        if dirty.dirty {
-            return Err(());
+    /// Gathers the Counters for the [`CollectionPolicy`], which unlike
+    /// [`collect_stats`](Self::collect_stats) does not walk the Heap
+    fn policy_stats(&self) -> PolicyStats {
+        let phase_index = self.phase_index.load(atomic::Ordering::Acquire);
+
+        PolicyStats {
+            phase_index,
+            pooled_buffers: self.allocation_pool.available(phase_index),
+            buffer_size: self.config.allocation_buffer_size,
+            allocated_nodes: self.collection.allocated(),
         }
+    }
 
-        locals.arbiter.store(next_arbiter);
-        Ok(())
+    /// Adds a new Page to the Heap and immediately sweeps it, so all of its
+    /// Nodes can be used for the next Allocations
+    #[tracing::instrument(skip(self, local))]
+    fn grow(&self, local: &local::Local<N>) {
+        let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
+
+        let page = self.pages.grow();
+        page.update_marks(local_phase);
+        // The Nodes of a new Page were never allocated, so they are not
+        // reported as reclaimed
+        local.sweep_page(page, &self.allocation_pool, |_, _| {});
     }
 
-    /// This validates that a Value read from some Address is valid, this
-    /// should be called before using the Value's read
-    pub fn validate_read(&self) -> Result<(), ()> {
-        let local = self.local.get_or_default();
-        let dirty = local.dirty.get();
-        if dirty.dirty {
-            Err(())
-        } else {
-            Ok(())
+    /// Returns the given Node to the Allocator right away, instead of waiting
+    /// for the next Collection to reclaim it. The Memory is not returned to
+    /// the OS, but will instead be reused by one of the next Allocations.
+    /// [`on_reclaim`](DataStructureNode::on_reclaim) is called for the Node
+    /// and its Data is dropped, before it is returned.
+    ///
+    /// # Safety
+    /// The Caller must guarantee that the Node is no longer reachable and that
+    /// no other Thread still holds a Ptr to it, as the Node could otherwise be
+    /// handed out again while it is still in use.
+    /// The Node must also have been allocated from the Pages, Nodes that were
+    /// allocated using the overflow Allocation can not be deallocated this way
+    pub unsafe fn deallocate(&self, node: AoaBox<N>) {
+        tracing::debug!("Deallocating: {:p}", node.inner);
+
+        if allocator::PageNode::from_data_ptr(node.inner).take_occupied() {
+            (*node.inner).on_reclaim();
+            core::ptr::drop_in_place(node.inner);
         }
+
+        let local = self.get_local();
+        let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
+
+        local.free_node(node.inner, local_phase, &self.allocation_pool);
     }
 
-    fn local_roots(&self) -> Vec<*mut N> {
-        let mut result = Vec::new();
+    /// Moves all the free Nodes, that are still buffered by the current
+    /// Thread, back into the global Pool, so other Threads can use them for
+    /// their Allocations.
+    ///
+    /// This should be called by every Thread that used the Allocator before
+    /// it exits. Otherwise the free Nodes in its Buffer can not be used by any
+    /// other Thread, until the next Collection frees them again
+    pub fn flush_local(&self) {
+        self.flush_buffer(self.get_local());
+    }
 
-        for t in self.local.iter() {
-            result.extend(
-                t.hazard_ptr_frames[0]
-                    .roots()
-                    .into_iter()
-                    .map(|ptr| N::untag_ptr(ptr)),
-            );
-            result.extend(
-                t.hazard_ptr_frames[1]
-                    .roots()
-                    .into_iter()
-                    .map(|ptr| N::untag_ptr(ptr)),
-            );
+    /// Moves the Buffer of the given Local-State back into the global Pool
+    fn flush_buffer(&self, local: &Local<N>) {
+        let buffer_phase = local.alloc.phase();
+        let buffer = local.alloc.take();
+        if buffer.is_empty() {
+            return;
         }
 
-        result
+        if self.allocation_pool.insert(buffer_phase, buffer).is_err() {
+            // The Buffer belongs to an old Phase, its Nodes are freed again by
+            // the Sweep of the current Phase
+            tracing::debug!("Dropped outdated local Buffer");
+        }
     }
 
-    fn global_roots(&self) -> Vec<*mut N> {
-        self.globals.get_globals()
+    /// Allocates the given Data outside of the Pages, using the
+    /// System-Allocator
+    fn allocate_overflow(&self, data: N) -> AoaBox<N> {
+        tracing::debug!("Allocating Overflow-Node");
+
+        let local = self.get_local();
+        let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
+
+        let ptr = self.overflow.allocate(data, local_phase);
+        AoaBox { inner: ptr }
     }
 
-    fn gather_roots(&self) -> Vec<*mut N> {
-        let mut result = self.local_roots();
-        result.extend(self.global_roots());
+    /// Allocates the given Data, like [`allocate`](Self::allocate), but also
+    /// returns the Marks of the Node right after it was allocated
+    pub fn allocate_raw(&self, data: N) -> (AoaBox<N>, NodeMarks) {
+        let allocated = self.allocate(data);
+        let marks = allocated.marks();
 
-        result
+        (allocated, marks)
     }
 
-    fn help(&self, local: &local::Local<N>, node: *mut N) {
-        if local.phase_index.load(atomic::Ordering::Acquire)
-            == self.phase_index.load(atomic::Ordering::Acquire)
-        {
-            local.mark_stack.push(node);
-        } else {
-            todo!("Clear MarkStack")
-        }
+    /// Registers a Function that runs once every Node, that is already
+    /// unreachable now, has been reclaimed, like `defer` in `crossbeam-epoch`.
+    /// This can be used to clean up non-Memory Resources, like File-Handles,
+    /// that belonged to a removed Node.
+    ///
+    /// # Ordering
+    /// The Function is stored together with the current Phase and runs once
+    /// the entire Sweep of a later Phase is complete, on the last Thread that
+    /// finishes sweeping. With a
+    /// [`sweep_budget_pages`](AllocatorConfig::sweep_budget_pages), a single
+    /// Sweep only visits some of the Pages, so the Function instead waits for
+    /// as many later Phases as it takes the limited Sweeps to visit every Page
+    /// and overflow Node once. Functions that become ready at the same Time run
+    /// in an unspecified Order. A Node that is still protected by a Hazard-Ptr or
+    /// [`PinGuard`] is not reclaimed by that Sweep, so the Function must not
+    /// rely on it being freed in that Case.
+    ///
+    /// Any Function that did not run yet, runs once the Allocator is dropped
+    pub fn defer<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let phase = self.phase_index.load(atomic::Ordering::Acquire);
+
+        self.deferred
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push((phase, Box::new(f)));
     }
 
-    #[tracing::instrument(skip(self))]
-    fn finish_or_progress(&self) -> bool {
-        let mut threads: HashSet<std::thread::ThreadId> = HashSet::new();
-        let mut cur_phase: HashMap<std::thread::ThreadId, u64> = HashMap::new();
-        let mut cur_traces: HashMap<std::thread::ThreadId, *mut N> = HashMap::new();
+    /// Forces the Allocator to start a Garbage-Collection Phase
+    ///
+    /// # Returns
+    /// What was traced and reclaimed by the Collection, see
+    /// [`CollectionReport`] for when this may be incomplete
+    pub fn force_gc(&self) -> CollectionReport {
+        self.reclaimation();
+        self.collection.report()
+    }
 
-        let own_local = self.local.get_or_default();
-        let local_phase = own_local.phase_index.load(atomic::Ordering::Acquire);
+    /// Makes sure that a Collection has completed before returning.
+    ///
+    /// If another Thread already started a Collection, that this Thread has
+    /// not taken Part in yet, this Thread helps with its Tracing, until every
+    /// Thread is done, and sweeps its Share of the Pages, instead of starting
+    /// another Collection. Otherwise it starts a new Collection itself, like
+    /// [`force_gc`](Self::force_gc).
+    ///
+    /// This can be used as a Barrier in Tests or at Points where the
+    /// Application is quiescent. It may block for a Time proportional to the
+    /// Number of live Nodes, as the Tracing has to be finished by all the
+    /// Threads before this returns
+    pub fn collect_blocking(&self) {
+        let local = self.get_local();
+        if !self.catch_up(local) {
+            self.reclaimation();
+        }
+    }
 
-        tracing::debug!("First Block");
-        for tmp_local in self.local.iter() {
-            let local_thread_id = &tmp_local.thread_id;
+    /// Returns the Pages at the End of the Heap, that only contain free Nodes,
+    /// to the OS and returns how many Pages were freed. This allows the Heap
+    /// to shrink again, after it grew during a Burst of Allocations.
+    ///
+    /// A Node is only considered free, if it is unmarked and stored in one of
+    /// the Allocation-Buffers for the current Phase, as unmarked Nodes that
+    /// are not in any Buffer may have been allocated since the last
+    /// Collection. The Nodes of the freed Pages are removed from the Buffers
+    /// and all the other free Nodes are moved into the global Pool, like
+    /// [`quiesce`](Self::quiesce) does.
+    ///
+    /// To free as many Pages as possible, this should be called right after a
+    /// Collection, as that is when every free Node is stored in a Buffer.
+    ///
+    /// # Safety
+    /// The same Requirements as for [`quiesce`](Self::quiesce) apply, as
+    /// this frees the Memory of the Pages and therefore no other Thread may
+    /// use the Allocator or still hold any Ptr into the freed Pages
+    pub unsafe fn shrink(&self) -> usize {
+        self.quiesce();
+
+        let phase = self.phase_index.load(atomic::Ordering::Acquire);
+        let mut free = Vec::new();
+        for buffer in self.allocation_pool.drain(phase) {
+            buffer.drain_into(|ptr| free.push(ptr));
+        }
 
-            let tmp_phase = tmp_local.phase_index.load(atomic::Ordering::Acquire);
-            let tmp_cur_traced = tmp_local.cur_traced.load(atomic::Ordering::Acquire);
-            if tmp_cur_traced.is_null() {
-                continue;
+        let free_set: HashSet<*mut N> = free.iter().copied().collect();
+        let mut freed_nodes = HashSet::new();
+        let freed_pages = self.pages.shrink(|page| {
+            let all_free = page
+                .nodes
+                .iter()
+                .all(|node| !node.load_marks().marked && free_set.contains(&node.get_data_ptr()));
+            if all_free {
+                freed_nodes.extend(page.nodes.iter().map(|node| node.get_data_ptr()));
             }
+            all_free
+        });
+        tracing::debug!(freed_pages, "Shrunk the Heap");
 
-            threads.insert(local_thread_id.clone());
-            cur_phase.insert(local_thread_id.clone(), tmp_phase);
-            cur_traces.insert(local_thread_id.clone(), tmp_cur_traced);
+        let buffer_size = self.config.allocation_buffer_size;
+        let mut remaining = free.into_iter().filter(|ptr| !freed_nodes.contains(ptr));
+        loop {
+            let buffer = allocator::AllocationBuffer::new(buffer_size);
+            for ptr in remaining.by_ref().take(buffer_size) {
+                let _ = buffer.insert(ptr);
+            }
+            if buffer.is_empty() {
+                break;
+            }
 
-            let obj_node = unsafe { allocator::PageNode::from_data_ptr(tmp_cur_traced) };
-            let marks = obj_node.load_marks();
-            if tmp_phase == local_phase && !marks.marked {
-                self.help(own_local, tmp_cur_traced);
-                return false;
+            if self.allocation_pool.insert(phase, buffer).is_err() {
+                tracing::debug!("Dropped Buffer while shrinking");
             }
         }
 
-        tracing::debug!("Second Block");
-        for tmp_local in self.local.iter() {
-            let tmp_thread_id = &tmp_local.thread_id;
-            if !threads.contains(tmp_thread_id) {
-                continue;
-            }
+        freed_pages
+    }
 
-            if *cur_phase.get(tmp_thread_id).unwrap() != local_phase {
-                continue;
-            }
+    /// Records the Graph of all the Nodes reachable from the current Roots,
+    /// together with their Marks, see [`HeapSnapshot`].
+    ///
+    /// # Safety
+    /// The Graph is walked by reading the Nodes directly, without any
+    /// Protection, so no other Thread may modify the Datastructure or run a
+    /// Collection while this runs. Parking all other Threads and calling
+    /// [`quiesce`](Self::quiesce) first is one Way to ensure that
+    pub unsafe fn snapshot(&self) -> HeapSnapshot {
+        let phase = self.phase_index.load(atomic::Ordering::Acquire);
+        let roots: Vec<*mut N> = self
+            .gather_roots()
+            .into_iter()
+            .map(N::untag_ptr)
+            .filter(|ptr| !ptr.is_null())
+            .collect();
+
+        let mut visited: HashSet<*mut N> = roots.iter().copied().collect();
+        let mut pending = roots.clone();
+        let mut nodes = Vec::new();
+        let mut children = Vec::with_capacity(N::pointer_count());
+        while let Some(ptr) = pending.pop() {
+            let mut edges = Vec::new();
+            children.clear();
+            (*ptr).pointers_into(&mut children);
+            for child in children.iter().map(|child| N::untag_ptr(*child)) {
+                if child.is_null() {
+                    continue;
+                }
 
-            // Iterate over all entries of the MarkStack and help if needed
-            let tmp_mark_stack = &tmp_local.mark_stack;
-            for node in tmp_mark_stack.iter() {
-                let obj_node = unsafe { allocator::PageNode::from_data_ptr(node) };
-                let marks = obj_node.load_marks();
-                if !marks.marked {
-                    self.help(own_local, node);
-                    return false;
+                edges.push(child as usize);
+                if visited.insert(child) {
+                    pending.push(child);
+                }
+            }
+
+            let marks = allocator::PageNode::from_data_ptr(ptr).load_marks();
+            nodes.push(SnapshotNode {
+                address: ptr as usize,
+                marked: marks.marked,
+                phase: marks.phase,
+                edges,
+            });
+        }
+
+        HeapSnapshot {
+            phase,
+            roots: roots.into_iter().map(|ptr| ptr as usize).collect(),
+            nodes,
+        }
+    }
+
+    /// The Number of Pages currently in the Heap
+    pub fn page_count(&self) -> u64 {
+        self.pages.page_count()
+    }
+
+    /// The Number of Nodes the Pages of the Heap can currently hold, this
+    /// does not include Nodes from the overflow Allocation
+    pub fn node_capacity(&self) -> usize {
+        self.pages.node_capacity()
+    }
+
+    /// Gathers the current Metrics of the Allocator, like the Size of the
+    /// Heap and how many of its Nodes are in use.
+    ///
+    /// This is only a racy Snapshot, see [`GcStats`] for more details
+    pub fn collect_stats(&self) -> GcStats {
+        let phase_index = self.phase_index.load(atomic::Ordering::Acquire);
+
+        let total_nodes = self.pages.node_capacity();
+        let live_nodes = std::cmp::min(total_nodes, self.pages.marked_nodes(phase_index));
+
+        let depths: Vec<usize> = self.local.iter().map(|l| l.mark_stack.depth()).collect();
+
+        GcStats {
+            phase_index,
+            page_count: self.pages.page_count(),
+            total_nodes,
+            live_nodes,
+            free_nodes: total_nodes - live_nodes,
+            overflow_nodes: self.overflow.len(),
+            mark_stack_depth: depths.iter().sum(),
+            max_mark_stack_depth: depths.iter().copied().max().unwrap_or(0),
+            pooled_buffers: self.allocation_pool.available(phase_index),
+        }
+    }
+
+    /// Iterates over the Ptrs to all the Nodes in the Pages, that are marked in
+    /// the current Phase, which are the Nodes that were found to be reachable
+    /// by the last Collection.
+    ///
+    /// This only reads the Marks and never modifies them. It should only be
+    /// called while no Collection is in progress, because the Marks are
+    /// constantly changing during the Tracing and Sweeping
+    pub fn iter_live_nodes(&self) -> impl Iterator<Item = *mut N> + '_ {
+        let phase = self.phase_index.load(atomic::Ordering::Acquire);
+
+        self.pages
+            .iter()
+            .flat_map(|page| page.nodes.iter())
+            .filter(move |node| {
+                let marks = node.load_marks();
+                marks.marked && marks.phase == phase
+            })
+            .map(|node| unsafe { node.get_data_ptr() })
+    }
+
+    /// Restarts the current Operation on the Datastructure, this should be
+    /// called whenever a Read could not be validated or a Write-Only Period
+    /// could not be started.
+    ///
+    /// If a Collection was started since this Thread last synchronized its
+    /// Phase, it will first help finishing that Collection. Afterwards all the
+    /// Hazard-Ptrs of this Thread are released and the given `local_ptrs` are
+    /// reset to Null, as they are no longer protected and need to be loaded
+    /// again from the Datastructure.
+    ///
+    /// A Frame that grew beyond a small fixed Number of Hazard-Ptrs, for
+    /// example during a long Traversal, is shrunk back to that Size here
+    #[tracing::instrument(skip(self, local_ptrs))]
+    pub fn restart(&self, local_ptrs: &mut [&mut *mut N]) {
+        let local = self.get_local();
+
+        self.catch_up(local);
+
+        for frame in local.hazard_ptr_frames.iter() {
+            frame.clear();
+            if frame.high_water_mark() > KEPT_HAZARD_PTRS {
+                frame.shrink_to(KEPT_HAZARD_PTRS);
+            }
+        }
+        local.arbiter.store(0);
+
+        // The Phase is kept, so the next Collection marks us as dirty again
+        local.dirty.clear_dirty();
+
+        for ptr in local_ptrs.iter_mut() {
+            **ptr = std::ptr::null_mut();
+        }
+    }
+
+    /// Takes Part in the Collection that was started by another Thread since
+    /// the current Thread last synchronized its Phase, if there is one.
+    ///
+    /// # Returns
+    /// Whether the current Thread helped with a Collection
+    fn catch_up(&self, local: &Local<N>) -> bool {
+        let global_phase = self.phase_index.load(atomic::Ordering::Acquire);
+        let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
+        if Phase::from(local_phase) >= Phase::from(global_phase) {
+            return false;
+        }
+
+        tracing::debug!(local_phase, global_phase, "Catching up");
+
+        local.reset_phase(global_phase);
+
+        // Take Part in the ongoing Collection
+        self.collect_phase();
+        true
+    }
+
+    /// This is used to attempt the Start of a Write-Only Period, if this
+    /// Returns an Error, the execution should be restarted at the previous
+    /// Read-Only Period
+    ///
+    /// # Parameters
+    /// * `local_ptrs`: The currently loaded Ptrs that will be used in the next
+    /// Stage, these should still contain the Tags, if the Datastructure uses
+    /// Tags (they should not be cleared here)
+    pub fn begin_write_only(&self, local_ptrs: &[*mut N]) -> Result<(), ()> {
+        let locals = self.get_local();
+
+        let next_arbiter = locals.arbiter.next();
+
+        // Nothing is modified if the Attempt is already known to fail
+        let dirty = locals.dirty.get();
+        if dirty.dirty {
+            return Err(());
+        }
+
+        // The next Frame still holds the Protections of the Period before the
+        // current one, which are no longer needed, as the current Frame
+        // protects everything this Thread still uses
+        let hazard_ptr_frame = &locals.hazard_ptr_frames[next_arbiter as usize];
+        hazard_ptr_frame.clear();
+        for p in local_ptrs {
+            hazard_ptr_frame.store(*p);
+        }
+
+        let previous_arbiter = locals.arbiter.get();
+        locals.arbiter.store(next_arbiter);
+
+        // A Collection could have been started between checking the Dirty-Flag
+        // and publishing the new Arbiter, in which case the Collection may
+        // have already gathered the Roots from the old Frame. So the Flag is
+        // checked again after publishing and the Arbiter is rolled back if it
+        // changed in the meantime, together with the Protections that were
+        // just added
+        if locals.dirty.get() != dirty {
+            locals.arbiter.store(previous_arbiter);
+            for p in local_ptrs {
+                hazard_ptr_frame.remove(*p);
+            }
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    /// Protects the given Ptr using a Hazard-Ptr of the current Thread, until
+    /// the returned Guard is dropped.
+    ///
+    /// The Ptr is stored in a separate Hazard-Ptr-Frame of this Thread, so it
+    /// is treated as a Root by every Collection that gathers its Roots after
+    /// this call and stays protected across [`restart`](Self::restart). A
+    /// Collection that already started before may have missed it however, so
+    /// the Ptr should only be used after a following
+    /// [`validate_read`](Self::validate_read) succeeded, otherwise the Guard
+    /// has to be dropped before restarting the Operation
+    pub fn protect(&self, ptr: *mut N) -> HazardGuard<'_, N> {
+        HazardGuard::new(&self.get_local().guarded, ptr)
+    }
+
+    /// Pins the given Node, which keeps it alive across Collections and
+    /// Restarts of the current Operation, until the returned Guard is dropped.
+    ///
+    /// Unlike [`protect`](Self::protect), the Ptr is not stored in one of the
+    /// Hazard-Ptr-Frames used by the Operations, but in a separate Frame of
+    /// this Thread, which is also treated as a Root by every Collection
+    ///
+    /// # Safety
+    /// The Ptr must not be tagged and must point to a Node that is currently
+    /// allocated by this Allocator. A Collection that already gathered its
+    /// Roots before this call may still reclaim the Node, so it must either
+    /// still be protected otherwise or a following
+    /// [`validate_read`](Self::validate_read) has to succeed before the Node
+    /// can be relied upon
+    pub unsafe fn pin(&self, ptr: *mut N) -> PinGuard<'_, N, G> {
+        PinGuard::new(&self.get_local().pinned, ptr)
+    }
+
+    /// Starts a Read-Only Period, whose Loads are validated automatically, see
+    /// [`ReadTxn`] for more details
+    pub fn read_txn(&self) -> ReadTxn<'_, N, G> {
+        ReadTxn::new(self)
+    }
+
+    /// Calls the given Function with shared Access to the Globals
+    pub fn with_globals<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&G) -> R,
+    {
+        let globals = self
+            .globals
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        f(&globals)
+    }
+
+    /// Calls the given Function with exclusive Access to the Globals, which
+    /// allows a Datastructure to add or remove Roots at runtime, for example
+    /// when a Hash-Map replaces its Bucket-Array.
+    ///
+    /// # Synchronization
+    /// The Roots must never change while a Collection is gathering them, as
+    /// the Collection would otherwise trace an inconsistent Set of Roots and
+    /// reclaim Nodes that are still reachable. So this waits for every
+    /// Collection that is currently gathering the Roots and blocks any other
+    /// Collection from doing so, until the Function returns.
+    ///
+    /// A Collection that gathered its Roots before the Change is still
+    /// tracing from the old Roots however, so every Node that is only
+    /// reachable from the new Roots must be protected by a Hazard-Ptr, using
+    /// [`begin_write_only`](Self::begin_write_only), just like for any other
+    /// Write to the Datastructure.
+    ///
+    /// # Deadlocks
+    /// The Lock is held while the Function runs, so the Function must not
+    /// allocate, start a Collection or access the Globals again, as any
+    /// Collection started from within it would wait for the Lock forever.
+    /// Nodes for the new Roots have to be allocated beforehand. Debug-Builds
+    /// panic when allocating or collecting inside of the Function
+    pub fn with_globals_mut<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut G) -> R,
+    {
+        #[cfg(debug_assertions)]
+        let _guard = self.get_local().enter_globals_mut();
+
+        let mut globals = self
+            .globals
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        f(&mut globals)
+    }
+
+    /// This validates that a Value read from some Address is valid, this
+    /// should be called before using the Value's read
+    pub fn validate_read(&self) -> Result<(), ()> {
+        let local = self.get_local();
+        let dirty = local.dirty.get();
+        if dirty.dirty {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Runs the given Read and validates it afterwards, like
+    /// [`validate_read`](Self::validate_read), so the Value is only handed
+    /// back if it can be used safely.
+    ///
+    /// If the Validation fails, the Value is dropped exactly once before
+    /// returning [`Retry`] and the current Operation has to be restarted
+    pub fn read_checked<V, F>(&self, read: F) -> Result<V, Retry>
+    where
+        F: FnOnce() -> V,
+    {
+        let value = read();
+        match self.validate_read() {
+            Ok(()) => Ok(value),
+            Err(()) => {
+                drop(value);
+                Err(Retry)
+            }
+        }
+    }
+
+    fn local_roots(&self) -> Vec<*mut N> {
+        // A single Buffer is reused for all the Frames of all the Threads
+        let mut result = Vec::new();
+
+        for t in self.registered_locals() {
+            let start = result.len();
+            t.hazard_ptr_frames[0].roots_into(&mut result);
+            t.hazard_ptr_frames[1].roots_into(&mut result);
+            t.guarded.roots_into(&mut result);
+            for ptr in result[start..].iter_mut() {
+                *ptr = N::untag_ptr(*ptr);
+            }
+
+            t.pinned.roots_into(&mut result);
+        }
+
+        result
+    }
+
+    fn gather_roots(&self) -> Vec<*mut N> {
+        let mut result = self.local_roots();
+        self.with_globals(|globals| globals.for_each_global(&mut |ptr| result.push(ptr)));
+
+        result
+    }
+
+    fn help(&self, local: &local::Local<N>, node: *mut N) {
+        let global_phase = self.phase_index.load(atomic::Ordering::Acquire);
+        if local.phase_index.load(atomic::Ordering::Acquire) != global_phase {
+            // The Entries on our MarkStack belong to an older Phase
+            local.reset_phase(global_phase);
+        }
+
+        local.mark_stack.push(node);
+    }
+
+    /// Checks if the Tracing of the current Phase is done, by looking at the
+    /// Tracing-State of every Thread, or otherwise helps with the Nodes that
+    /// are still left.
+    ///
+    /// A Thread that died while tracing leaves its `cur_traced` and MarkStack
+    /// behind, these are picked up here like for any other Thread, so the
+    /// Nodes it did not get to are still traced by the remaining Threads
+    #[tracing::instrument(skip(self))]
+    fn finish_or_progress(&self) -> bool {
+        let mut threads: HashSet<std::thread::ThreadId> = HashSet::new();
+        let mut cur_phase: HashMap<std::thread::ThreadId, u64> = HashMap::new();
+        let mut cur_traces: HashMap<std::thread::ThreadId, *mut N> = HashMap::new();
+
+        let own_local = self.get_local();
+        let local_phase = own_local.phase_index.load(atomic::Ordering::Acquire);
+
+        tracing::debug!("First Block");
+        for tmp_local in self.registered_locals() {
+            let local_thread_id = &tmp_local.thread_id;
+
+            let tmp_phase = tmp_local.phase_index.load(atomic::Ordering::Acquire);
+            let tmp_cur_traced = tmp_local.cur_traced.load(atomic::Ordering::Acquire);
+            if tmp_cur_traced.is_null() {
+                continue;
+            }
+
+            threads.insert(local_thread_id.clone());
+            cur_phase.insert(local_thread_id.clone(), tmp_phase);
+            cur_traces.insert(local_thread_id.clone(), tmp_cur_traced);
+
+            // Like in `mark_node`, a Node whose Marks belong to another Phase
+            // must not be marked by us
+            let obj_node = unsafe { allocator::PageNode::from_data_ptr(tmp_cur_traced) };
+            let marks = obj_node.load_marks();
+            if tmp_phase == local_phase && marks.phase == local_phase && !marks.marked {
+                self.help(own_local, tmp_cur_traced);
+                return false;
+            }
+        }
+
+        tracing::debug!("Second Block");
+        for tmp_local in self.registered_locals() {
+            let tmp_thread_id = &tmp_local.thread_id;
+            if !threads.contains(tmp_thread_id) {
+                continue;
+            }
+
+            if *cur_phase.get(tmp_thread_id).unwrap() != local_phase {
+                continue;
+            }
+
+            // Iterate over all entries of the MarkStack and help if needed
+            let tmp_mark_stack = &tmp_local.mark_stack;
+            for node in tmp_mark_stack.iter() {
+                let obj_node = unsafe { allocator::PageNode::from_data_ptr(node) };
+                let marks = obj_node.load_marks();
+                if marks.phase == local_phase && !marks.marked {
+                    self.help(own_local, node);
+                    return false;
                 }
             }
         }
 
         tracing::debug!("Third Block");
-        for tmp_local in self.local.iter() {
+        for tmp_local in self.registered_locals() {
             let tmp_thread_id = &tmp_local.thread_id;
             if !threads.contains(tmp_thread_id) {
                 continue;
@@ -317,75 +1388,364 @@ where
     fn trace(&self, roots: Vec<*mut N>) {
         tracing::debug!("Tracing");
 
-        let local = self.local.get_or_default();
+        let local = self.get_local();
         let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
 
-        for root in roots {
-            local.mark_stack.push(root);
-        }
+        local.mark_stack.push_batch(&roots);
 
         tracing::debug!("Starting the Trace-Routine");
+        let mut backoff = Backoff::new(self.config.trace_spin_limit);
         loop {
-            loop {
-                if let MarkNodeState::Done = local.mark_node(local_phase) {
+            let (processed, state) = self.mark_budget(local, local_phase, usize::MAX);
+
+            if let MarkNodeState::Done = state {
+                if self.finish_or_progress() {
                     break;
                 }
             }
 
-            if self.finish_or_progress() {
-                break;
+            // Only back off while this Thread has nothing to do itself, it
+            // still checks for Termination after every Snooze
+            if processed > 0 {
+                backoff.reset();
+            } else {
+                backoff.snooze();
             }
         }
     }
 
-    #[tracing::instrument(skip(self))]
-    fn sweep(&self) {
-        let local = self.local.get_or_default();
+    /// Processes at most `budget` Entries from the MarkStack of the current
+    /// Thread, which allows an Application to interleave the Marking with its
+    /// own Work instead of waiting for the entire Trace to finish.
+    ///
+    /// # Returns
+    /// Whether the Tracing of the current Phase is complete. This is only
+    /// checked once the MarkStack of this Thread has been drained, so a Step
+    /// that used up its entire Budget always returns `false`
+    pub fn trace_step(&self, budget: usize) -> bool {
+        let local = self.get_local();
         let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
 
-        tracing::debug!(local_phase, "Sweeping");
+        match self.mark_budget(local, local_phase, budget) {
+            (_, MarkNodeState::Done) => self.finish_or_progress(),
+            (_, _) => false,
+        }
+    }
 
-        loop {
-            match self.pages.get_page(&self.sweep_chunk_index, local_phase) {
-                Some(page) => local.sweep_page(page, &self.allocation_pool),
-                None => {
-                    tracing::debug!("Done-Sweeping");
-                    return;
+    /// Marks Nodes from the MarkStack of the given Local until it is empty or
+    /// `budget` Entries have been processed.
+    ///
+    /// # Returns
+    /// The Number of processed Entries and whether the MarkStack was drained
+    fn mark_budget(
+        &self,
+        local: &local::Local<N>,
+        local_phase: u64,
+        budget: usize,
+    ) -> (usize, MarkNodeState) {
+        let mut processed = 0;
+        let mut marked = 0;
+        let mut state = MarkNodeState::NotDone;
+        while processed < budget {
+            match local.mark_node(local_phase) {
+                MarkNodeState::Done => {
+                    state = MarkNodeState::Done;
+                    break;
                 }
+                MarkNodeState::Marked => marked += 1,
+                MarkNodeState::NotDone => {}
             };
+            processed += 1;
         }
+
+        self.collection.add_traced(marked);
+        (processed, state)
     }
 
     #[tracing::instrument(skip(self))]
+    fn sweep(&self) -> SweepStats {
+        let local = self.get_local();
+        let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
+
+        tracing::debug!(local_phase, "Sweeping");
+
+        self.pages
+            .reset_sweep_chunks(&self.sweep_chunk_index, local_phase);
+
+        let budget = self.config.sweep_budget_pages;
+        let start = self.pages.sweep_start(local_phase, budget);
+
+        let mut stats = SweepStats::default();
+
+        if self.config.deterministic_sweep {
+            for (index, page) in
+                self.pages
+                    .claim_pages(&self.sweep_chunk_index, local_phase, start, budget)
+            {
+                stats.nodes += self.sweep_page(local, index, page);
+                stats.pages += 1;
+            }
+        }
+
+        let mut cursor = self.pages.cursor();
+        while let Some(page) = self.pages.get_page(
+            &mut cursor,
+            &self.sweep_chunk_index,
+            local_phase,
+            start,
+            budget,
+        ) {
+            stats.nodes += self.sweep_page(local, cursor.index(), page);
+            stats.pages += 1;
+        }
+
+        // The overflow Nodes count against the Budget like the Nodes of the
+        // same Number of Pages would
+        let overflow_budget = self
+            .config
+            .sweep_budget_pages
+            .map(|pages| pages * self.config.page_size);
+        let freed = self.overflow.sweep(local_phase, overflow_budget, |ptr| {
+            #[cfg(debug_assertions)]
+            self.assert_no_hazard_retained(ptr);
+            unsafe { (*ptr).on_reclaim() };
+            self.observe_reclaim(ptr);
+        });
+        stats.nodes += freed;
+        tracing::debug!(
+            freed,
+            overflow_nodes = self.overflow.len(),
+            pooled_buffers = self.allocation_pool.available(local_phase),
+            "Done-Sweeping"
+        );
+
+        stats
+    }
+
+    /// Sweeps the Page at the given Index on behalf of the current Thread and
+    /// reports it to the Observer
+    fn sweep_page(&self, local: &Local<N>, index: u64, page: &allocator::Page<N>) -> usize {
+        if let Some(observer) = self.observer.as_ref() {
+            observer.on_sweep_page(index);
+        }
+
+        local.sweep_page(page, &self.allocation_pool, |ptr, occupied| {
+            if occupied {
+                #[cfg(debug_assertions)]
+                self.assert_no_hazard_retained(ptr);
+                unsafe { (*ptr).on_reclaim() };
+            }
+            self.observe_reclaim(ptr);
+        })
+    }
+
+    /// Panics if the given Node, which is about to be reclaimed, is still
+    /// protected by a Hazard-Ptr of a Thread that is not dirty or is pinned by
+    /// any Thread, as that Thread could keep using the Node after it was
+    /// freed.
+    ///
+    /// Dirty Threads are skipped for the Hazard-Ptrs and [`HazardGuard`]s,
+    /// because they may have protected the Node only after the Roots were
+    /// gathered, but they restart their current Operation before using it and
+    /// release these Protections in the Process. Pins survive a Restart, so they are checked for every
+    /// Thread. The Protections are compared with every Tag in the low Bits of
+    /// the Ptr, see the [`tagging`] Module.
+    ///
+    /// This is only compiled into Debug-Builds
+    #[cfg(debug_assertions)]
+    fn assert_no_hazard_retained(&self, ptr: *mut N) {
+        let protects = |frame: &HazardPtrFrame<N>| {
+            (0..=tagging::tag_mask::<N>()).any(|tag| frame.protects(tagging::tag_ptr(ptr, tag)))
+        };
+
+        for local in self.registered_locals() {
+            assert!(
+                !protects(&local.pinned),
+                "The Node {:p} is about to be reclaimed, but is still pinned by a Thread",
+                ptr
+            );
+
+            if local.dirty.get().dirty {
+                continue;
+            }
+
+            for frame in local.hazard_ptr_frames.iter().chain([&local.guarded]) {
+                assert!(
+                    !protects(frame),
+                    "The Node {:p} is about to be reclaimed, but is still protected by a Hazard-Ptr of a Thread that will not restart",
+                    ptr
+                );
+            }
+        }
+    }
+
+    fn observe_reclaim(&self, ptr: *mut N) {
+        if let Some(observer) = self.observer.as_ref() {
+            observer.on_reclaim(ptr as *const ());
+        }
+    }
+
+    /// Runs an entire Collection-Cycle.
+    ///
+    /// The whole Cycle is recorded in a single Span, with the Steps being
+    /// Child-Spans of it, which records the Duration of the Cycle and how much
+    /// Work this Thread did as part of it
+    #[tracing::instrument(
+        name = "reclaimation_cycle",
+        skip(self),
+        fields(
+            roots = tracing::field::Empty,
+            swept_pages = tracing::field::Empty,
+            reclaimed_nodes = tracing::field::Empty,
+            elapsed_us = tracing::field::Empty,
+        )
+    )]
     fn reclaimation(&self) {
         tracing::debug!("Starting Reclaimation");
+        let start = std::time::Instant::now();
 
         self.init_reclaimation();
+        let (root_count, sweep_stats) = self.collect_phase();
+
+        let elapsed = start.elapsed();
+        let span = tracing::Span::current();
+        span.record("roots", root_count);
+        span.record("swept_pages", sweep_stats.pages);
+        span.record("reclaimed_nodes", sweep_stats.nodes);
+        span.record("elapsed_us", elapsed.as_micros() as u64);
+
+        tracing::debug!("Done Reclaimation");
+    }
+
+    /// Performs the Collection of the Phase the current Thread is in, which is
+    /// done by every Thread that takes Part in the Collection.
+    ///
+    /// Every Thread gathers and traces all the Roots itself, before it starts
+    /// sweeping. Otherwise a Thread, that joins the Collection before any
+    /// other Thread started tracing, would find nothing left to trace and
+    /// sweep the Nodes before they could be marked.
+    ///
+    /// # Returns
+    /// The Number of Roots and the Work done during the Sweep
+    fn collect_phase(&self) -> (usize, SweepStats) {
+        #[cfg(debug_assertions)]
+        self.get_local().assert_outside_globals_mut("Collecting");
 
         self.update_marks();
         self.clear_alloc_pools();
+        self.mark_allocating();
 
         // Gather all Roots
         let roots = self.gather_roots();
+        let root_count = roots.len();
 
         // Trace the Roots
         self.trace(roots);
 
         // Sweep
-        self.sweep();
+        self.sweepers.fetch_add(1, atomic::Ordering::SeqCst);
+        let sweep_stats = self.sweep();
+        self.collection.add_sweep(sweep_stats);
+
+        // Every Page of the Phase was claimed once our Sweep returns, so the
+        // Sweep of the Phase is complete once no other Thread is still
+        // sweeping one of its claimed Pages
+        if self.sweepers.fetch_sub(1, atomic::Ordering::SeqCst) == 1 {
+            self.run_deferred();
+        }
+
+        (root_count, sweep_stats)
+    }
+
+    /// Runs all the deferred Functions that were registered at least
+    /// [`sweep_phases`](Self::sweep_phases) Phases before the one the current
+    /// Thread just finished sweeping, which is only done once every Thread
+    /// finished sweeping.
+    ///
+    /// The Functions are removed from the List first and only run once the
+    /// Lock is released again, so they may call [`Allocator::defer`]
+    /// themselves
+    fn run_deferred(&self) {
+        let local_phase = self.get_local().phase_index.load(atomic::Ordering::Acquire);
+        let sweep_phases = self.sweep_phases();
+
+        let ready: Vec<_> = {
+            let mut deferred = self
+                .deferred
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if deferred.is_empty() {
+                return;
+            }
+
+            let (ready, pending) = core::mem::take(&mut *deferred)
+                .into_iter()
+                .partition(|(phase, _)| local_phase.saturating_sub(*phase) >= sweep_phases);
+            *deferred = pending;
+            ready
+        };
+
+        for (_, func) in ready {
+            func();
+        }
+    }
+
+    /// The Number of consecutive Phases it takes to sweep every Page and
+    /// overflow Node at least once, which is only more than one with a
+    /// [`sweep_budget_pages`](AllocatorConfig::sweep_budget_pages)
+    fn sweep_phases(&self) -> u64 {
+        let budget = match self.config.sweep_budget_pages {
+            Some(0) => return u64::MAX,
+            Some(budget) => budget as u64,
+            None => return 1,
+        };
+
+        let pages = self.pages.page_count();
+        let overflow_nodes = self.overflow.len() as u64;
+        let overflow_budget = budget * self.config.page_size as u64;
+
+        core::cmp::max(
+            pages.div_ceil(budget),
+            overflow_nodes.div_ceil(overflow_budget),
+        )
+        .max(1)
     }
 
     #[tracing::instrument(skip(self))]
     fn update_marks(&self) {
         tracing::debug!("Clearing Marks");
-        let local = self.local.get_or_default();
+        let local = self.get_local();
         let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
 
-        let sweep_chunk_index = (local_phase << 32) | 0x00;
-        self.sweep_chunk_index
-            .store(sweep_chunk_index, atomic::Ordering::Release);
-
         self.pages.update_marks(local_phase);
+        self.overflow.update_marks(local_phase);
+    }
+
+    /// Marks the Nodes that are currently being allocated by any Thread.
+    ///
+    /// These Nodes are only marked themselves and are not traced, as their
+    /// Data may not have been written yet
+    fn mark_allocating(&self) {
+        let local_phase = self.get_local().phase_index.load(atomic::Ordering::Acquire);
+
+        for thread in self.registered_locals() {
+            let ptr = thread.allocating.load(atomic::Ordering::SeqCst);
+            if ptr.is_null() {
+                continue;
+            }
+
+            let node = unsafe { allocator::PageNode::from_data_ptr(ptr) };
+            let _ = node.update_marks(
+                NodeMarks {
+                    phase: local_phase,
+                    marked: false,
+                },
+                NodeMarks {
+                    phase: local_phase,
+                    marked: true,
+                },
+            );
+        }
     }
 
     #[tracing::instrument(skip(self))]
@@ -393,7 +1753,7 @@ where
         tracing::debug!("Clearing Allocation-Pools");
 
         // TODO
-        let local = self.local.get_or_default();
+        let local = self.get_local();
         let local_phase = local.phase_index.load(atomic::Ordering::Acquire);
 
         match self.allocation_pool.clear(local_phase) {
@@ -407,18 +1767,33 @@ where
     }
 
     /// This signals all Threads that a new Phase has started
+    ///
+    /// # Phase-Wraparound
+    /// The Phase is assumed to only ever increase, which a lot of the
+    /// Comparisons (like when clearing the Marks or updating the Pools) rely
+    /// on. Wrapping around is therefore not supported and this panics once
+    /// the Phase would exceed [`Phase::MAX`], instead of silently corrupting
+    /// the Marks
     #[tracing::instrument(skip(self))]
     fn init_reclaimation(&self) {
         tracing::debug!("Init Reclaimation");
 
-        let local = self.local.get_or_default();
+        let local = self.get_local();
         let lphase_index = local.phase_index.load(atomic::Ordering::Acquire);
-        let _ = self.phase_index.compare_exchange(
+        let next_phase = Phase::from(lphase_index).next().get();
+        let started = self.phase_index.compare_exchange(
             lphase_index,
-            lphase_index + 1,
+            next_phase,
             atomic::Ordering::SeqCst,
             atomic::Ordering::SeqCst,
         );
+        if started.is_ok() {
+            self.collection.reset();
+
+            if let Some(observer) = self.observer.as_ref() {
+                observer.on_phase_start(next_phase);
+            }
+        }
 
         let nphase_index = self.phase_index.load(atomic::Ordering::Acquire);
 
@@ -426,21 +1801,10 @@ where
             .phase_index
             .store(nphase_index, atomic::Ordering::Release);
 
+        // Every Thread, including this one, has to restart its current
+        // Operation, as its Reads may be invalidated by this Collection
         for thread in self.local.iter() {
-            let t_dirty = thread.dirty.get();
-
-            // TODO
-            // Investigate why the Paper uses a while loop for this
-            if t_dirty.phase < lphase_index {
-                let expected = t_dirty.to_u64();
-                thread.dirty.update(
-                    expected,
-                    DirtyValue {
-                        dirty: true,
-                        phase: lphase_index,
-                    },
-                );
-            }
+            thread.dirty.mark_dirty(nphase_index);
         }
     }
 }
@@ -449,11 +1813,68 @@ where
 /// Datastructure
 pub trait DataStructureNode {
     /// The maximum amount of pointers to other Nodes in a single Node
+    ///
+    /// This is used to preallocate the Space for the Pointers of a Node, so
+    /// a Node must never report more Pointers than this, including the Null
+    /// Pointers. Debug-Builds panic while tracing a Node that does
     fn pointer_count() -> usize;
-    /// Actually loads the Pointers from the current Node to others
-    fn pointers(&self) -> Vec<*mut Self>;
 
-    /// This gets passed a Ptr that could be tagged and should remove the
+    /// Actually loads the Pointers from the current Node to others and
+    /// appends them to `out`, without clearing it first.
+    ///
+    /// This is used while Tracing, where every Thread reuses a single Buffer
+    /// for all the Nodes it visits, instead of allocating a new Vec for every
+    /// one of them
+    fn pointers_into(&self, out: &mut Vec<*mut Self>);
+
+    /// Loads the Pointers from the current Node to others into a new Vec,
+    /// using [`pointers_into`](DataStructureNode::pointers_into)
+    fn pointers(&self) -> Vec<*mut Self>
+    where
+        Self: Sized,
+    {
+        let mut result = Vec::with_capacity(Self::pointer_count());
+        self.pointers_into(&mut result);
+        result
+    }
+
+    /// Whether the Node can not point to any other Nodes right now, in which
+    /// case its Pointers are not even loaded while Tracing it.
+    ///
+    /// This can be used for Datastructures with a lot of Leaf-Nodes, like the
+    /// Values of a Map, to avoid visiting their (Null) Pointers
+    fn is_leaf(&self) -> bool {
+        false
+    }
+
+    /// Called once the Node was reclaimed, either by a Sweep that found it
+    /// unreachable or by [`deallocate`](Allocator::deallocate).
+    ///
+    /// This can be used to release Resources held by the Node, which need
+    /// access to the Allocator or other Nodes, as the Data of the Node is
+    /// dropped right afterwards.
+    ///
+    /// # Ordering
+    /// This runs exactly once for every reclaimed Node, on the Thread that
+    /// reclaimed it, right before the Data of the Node is dropped and its Slot
+    /// is handed to the Allocator again, so before the Slot can be reused by
+    /// another Allocation. Nodes that were never allocated, or already
+    /// reclaimed, are not reported again.
+    ///
+    /// # Stale Readers
+    /// Readers that loaded a Ptr to the Node before it became unreachable may
+    /// still read it concurrently, until they notice the Collection and
+    /// restart. This is why the Hook only gets a shared Reference to the
+    /// Node, any State it changes has to use Atomics or other Types that can
+    /// be accessed concurrently. The Node itself has to stay readable
+    /// afterwards, which means its Pointers must remain valid Ptrs or Null.
+    /// The same applies to the Drop-Implementation of the Node, and Readers
+    /// must validate their Read-Region before following any Ptr owned by the
+    /// Data, like the Buffer of a `String` or `Vec`, as it may already be
+    /// freed
+    fn on_reclaim(&self) {}
+
+    /// This gets passed a Ptr that could be tagged and should remove the
     /// Tag from it
     fn untag_ptr(ptr: *mut Self) -> *mut Self;
 }
@@ -462,4 +1883,2126 @@ pub trait DataStructureNode {
 pub trait DataStructureGlobals<N> {
     /// TODO
     fn get_globals(&self) -> Vec<*mut N>;
+
+    /// Calls the given Function for every Global of the Data-Structure.
+    ///
+    /// This is used to gather the Roots for every Collection and avoids
+    /// allocating a new Vec each time, so it should be implemented directly
+    /// if possible. By default it simply iterates over the Result of
+    /// [`get_globals`](DataStructureGlobals::get_globals)
+    fn for_each_global(&self, f: &mut dyn FnMut(*mut N)) {
+        for ptr in self.get_globals() {
+            f(ptr);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    impl<N, G> Allocator<N, G>
+    where
+        N: DataStructureNode,
+        G: DataStructureGlobals<N>,
+    {
+        /// Forces the current Thread and the Allocator into the given Phase
+        fn set_phase(&self, phase: u64) {
+            self.phase_index.store(phase, atomic::Ordering::Release);
+            self.get_local()
+                .phase_index
+                .store(phase, atomic::Ordering::Release);
+        }
+    }
+
+    /// The Node used by the Tests of all the Modules, which points to at
+    /// most one other Node
+    pub(crate) struct TestNode {
+        pub(crate) next: atomic::AtomicPtr<Self>,
+    }
+
+    impl TestNode {
+        pub(crate) fn new() -> Self {
+            Self {
+                next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            }
+        }
+    }
+
+    impl DataStructureNode for TestNode {
+        fn pointer_count() -> usize {
+            1
+        }
+        fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+            out.push(self.next.load(atomic::Ordering::Acquire));
+        }
+
+        fn untag_ptr(ptr: *mut Self) -> *mut Self {
+            ptr
+        }
+    }
+
+    /// Globals without any Roots, so every Node has to be protected by a
+    /// Thread to survive a Collection
+    pub(crate) struct NoGlobals;
+
+    impl DataStructureGlobals<TestNode> for NoGlobals {
+        fn get_globals(&self) -> Vec<*mut TestNode> {
+            Vec::new()
+        }
+    }
+
+    /// Counts how many of the [`CountedNode`]s sharing them were dropped and
+    /// reclaimed
+    #[derive(Default)]
+    struct NodeCounters {
+        dropped: atomic::AtomicUsize,
+        reclaimed: atomic::AtomicUsize,
+    }
+
+    /// A Node like the [`TestNode`], which additionally counts how often
+    /// Nodes were dropped and reclaimed in its [`NodeCounters`]
+    struct CountedNode {
+        next: atomic::AtomicPtr<Self>,
+        counters: std::sync::Arc<NodeCounters>,
+    }
+
+    impl CountedNode {
+        fn new(counters: &std::sync::Arc<NodeCounters>) -> Self {
+            Self {
+                next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+                counters: counters.clone(),
+            }
+        }
+    }
+
+    impl Drop for CountedNode {
+        fn drop(&mut self) {
+            self.counters.dropped.fetch_add(1, atomic::Ordering::SeqCst);
+        }
+    }
+
+    impl DataStructureNode for CountedNode {
+        fn pointer_count() -> usize {
+            1
+        }
+        fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+            out.push(self.next.load(atomic::Ordering::Acquire));
+        }
+        fn on_reclaim(&self) {
+            self.counters
+                .reclaimed
+                .fetch_add(1, atomic::Ordering::SeqCst);
+        }
+        fn untag_ptr(ptr: *mut Self) -> *mut Self {
+            ptr
+        }
+    }
+
+    #[test]
+    fn trace_pointers_into() {
+        // Appends its Ptr after Nulls, so only a Node that is traced using
+        // the Buffer it filled keeps its Child alive
+        struct PaddedNode {
+            next: atomic::AtomicPtr<PaddedNode>,
+            dropped: std::sync::Arc<atomic::AtomicUsize>,
+        }
+        impl Drop for PaddedNode {
+            fn drop(&mut self) {
+                self.dropped.fetch_add(1, atomic::Ordering::SeqCst);
+            }
+        }
+        impl DataStructureNode for PaddedNode {
+            fn pointer_count() -> usize {
+                3
+            }
+            fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+                out.push(std::ptr::null_mut());
+                out.push(std::ptr::null_mut());
+                out.push(self.next.load(atomic::Ordering::Acquire));
+            }
+            fn untag_ptr(ptr: *mut Self) -> *mut Self {
+                ptr
+            }
+        }
+
+        let dropped = std::sync::Arc::new(atomic::AtomicUsize::new(0));
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::<PaddedNode, _>::builder(SingleGlobal(root.clone()))
+            .page_size(8)
+            .build();
+
+        let mut head = std::ptr::null_mut();
+        for _ in 0..20 {
+            let node = allocator.allocate(PaddedNode {
+                next: atomic::AtomicPtr::new(head),
+                dropped: dropped.clone(),
+            });
+            head = node.ptr();
+            root.store(head, atomic::Ordering::Release);
+        }
+        for _ in 0..5 {
+            allocator.allocate(PaddedNode {
+                next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+                dropped: dropped.clone(),
+            });
+        }
+
+        allocator.force_gc();
+        allocator.force_gc();
+        assert_eq!(5, dropped.load(atomic::Ordering::SeqCst));
+
+        let second = unsafe { &*head }.next.load(atomic::Ordering::Acquire);
+        assert_eq!(
+            vec![std::ptr::null_mut(), std::ptr::null_mut(), second],
+            unsafe { &*head }.pointers()
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "pointer_count")]
+    fn pointer_count_too_small() {
+        struct WrongCount;
+        impl DataStructureNode for WrongCount {
+            fn pointer_count() -> usize {
+                1
+            }
+            fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+                out.push(std::ptr::null_mut());
+                out.push(std::ptr::null_mut());
+            }
+            fn untag_ptr(ptr: *mut Self) -> *mut Self {
+                ptr
+            }
+        }
+
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::<WrongCount, _>::new(SingleGlobal(root.clone()));
+        let node = allocator.allocate(WrongCount);
+        root.store(node.ptr(), atomic::Ordering::Release);
+
+        allocator.force_gc();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "still protected by a Hazard-Ptr")]
+    fn hazard_retained() {
+        let allocator = Allocator::<TestNode, _>::builder(NoGlobals)
+            .page_size(8)
+            .build();
+        let node = allocator.allocate(TestNode::new());
+
+        allocator.init_reclaimation();
+        allocator.update_marks();
+
+        // The Node is protected after the Roots were gathered, by a Thread
+        // that already restarted, so nothing else would catch it
+        let local = allocator.get_local();
+        local.dirty.clear_dirty();
+        local.hazard_ptr_frames[0].store(tagging::tag_ptr(node.ptr(), 1));
+
+        allocator.sweep();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "still pinned by a Thread")]
+    fn pin_retained_by_dirty_thread() {
+        let allocator = Allocator::<TestNode, _>::builder(NoGlobals)
+            .page_size(8)
+            .build();
+        let node = allocator.allocate(TestNode::new());
+
+        allocator.init_reclaimation();
+        allocator.update_marks();
+
+        // Unlike a Hazard-Ptr, a Pin is not released by the Restart of the
+        // dirty Thread
+        let local = allocator.get_local();
+        local.pinned.store(node.ptr());
+
+        allocator.sweep();
+    }
+
+    #[test]
+    fn hazard_retained_by_dirty_thread() {
+        let allocator = Allocator::<TestNode, _>::builder(NoGlobals)
+            .page_size(8)
+            .build();
+        let node = allocator.allocate(TestNode::new());
+
+        allocator.init_reclaimation();
+        allocator.update_marks();
+
+        // The dirty Thread restarts before using the Node
+        let local = allocator.get_local();
+        local.hazard_ptr_frames[0].store(node.ptr());
+
+        allocator.sweep();
+        assert_eq!(0, allocator.collect_stats().live_nodes);
+    }
+
+    #[test]
+    fn try_allocate() {
+        let allocator = Allocator::<TestNode, _>::new(NoGlobals);
+        assert!(allocator.try_allocate(TestNode::new()).is_ok());
+
+        // Pages without any Nodes can never satisfy an Allocation
+        let allocator = Allocator::<TestNode, _>::builder(NoGlobals)
+            .page_size(0)
+            .build();
+        let node = TestNode::new();
+        node.next
+            .store(0x10 as *mut TestNode, atomic::Ordering::Release);
+
+        match allocator.try_allocate(node) {
+            Err((data, AllocError::OutOfMemory)) => {
+                assert_eq!(
+                    0x10 as *mut TestNode,
+                    data.next.load(atomic::Ordering::Acquire)
+                );
+            }
+            _ => panic!("Expected the Allocation to fail"),
+        };
+    }
+
+    #[test]
+    fn aligned_nodes() {
+        #[repr(align(64))]
+        struct AlignedNode {
+            next: atomic::AtomicPtr<Self>,
+        }
+        impl DataStructureNode for AlignedNode {
+            fn pointer_count() -> usize {
+                1
+            }
+            fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+                out.push(self.next.load(atomic::Ordering::Acquire));
+            }
+            fn untag_ptr(ptr: *mut Self) -> *mut Self {
+                ptr
+            }
+        }
+
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::builder(SingleGlobal(root.clone()))
+            .page_size(8)
+            .build();
+
+        let first = allocator.allocate(AlignedNode {
+            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+        });
+        let second = allocator.allocate(AlignedNode {
+            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+        });
+        assert_eq!(0, first.ptr() as usize % 64);
+        assert_eq!(0, second.ptr() as usize % 64);
+
+        first.next.store(second.ptr(), atomic::Ordering::Release);
+        root.store(first.ptr(), atomic::Ordering::Release);
+        allocator.force_gc();
+
+        assert!(first.marks().marked);
+        assert!(second.marks().marked);
+        assert_eq!(2, allocator.collect_stats().live_nodes);
+    }
+
+    #[test]
+    fn leaf_nodes_are_not_expanded() {
+        struct LeafNode {
+            next: atomic::AtomicPtr<Self>,
+            leaf: bool,
+        }
+        impl DataStructureNode for LeafNode {
+            fn pointer_count() -> usize {
+                1
+            }
+            fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+                assert!(!self.leaf, "The Pointers of a Leaf should not be loaded");
+                out.push(self.next.load(atomic::Ordering::Acquire));
+            }
+            fn is_leaf(&self) -> bool {
+                self.leaf
+            }
+            fn untag_ptr(ptr: *mut Self) -> *mut Self {
+                ptr
+            }
+        }
+
+        let new_node = |leaf| LeafNode {
+            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            leaf,
+        };
+
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::builder(SingleGlobal(root.clone()))
+            .page_size(8)
+            .build();
+
+        let inner = allocator.allocate(new_node(false));
+        let leaf = allocator.allocate(new_node(true));
+        inner.next.store(leaf.ptr(), atomic::Ordering::Release);
+        root.store(inner.ptr(), atomic::Ordering::Release);
+
+        allocator.force_gc();
+
+        assert!(inner.marks().marked);
+        assert!(leaf.marks().marked);
+        assert_eq!(2, allocator.collect_stats().live_nodes);
+    }
+
+    #[test]
+    fn thread_dies_mid_trace() {
+        use std::sync::Arc;
+
+        struct PoisonedNode {
+            next: atomic::AtomicPtr<Self>,
+            poisoned: atomic::AtomicBool,
+        }
+        impl DataStructureNode for PoisonedNode {
+            fn pointer_count() -> usize {
+                1
+            }
+            fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+                if self.poisoned.swap(false, atomic::Ordering::SeqCst) {
+                    panic!("Poisoned Node");
+                }
+                out.push(self.next.load(atomic::Ordering::Acquire));
+            }
+            fn untag_ptr(ptr: *mut Self) -> *mut Self {
+                ptr
+            }
+        }
+
+        let root = Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Arc::new(
+            Allocator::builder(SingleGlobal(root.clone()))
+                .page_size(8)
+                .build(),
+        );
+
+        let child = allocator.allocate(PoisonedNode {
+            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            poisoned: atomic::AtomicBool::new(false),
+        });
+        let node = allocator.allocate(PoisonedNode {
+            next: atomic::AtomicPtr::new(child.ptr()),
+            poisoned: atomic::AtomicBool::new(true),
+        });
+        root.store(node.ptr(), atomic::Ordering::Release);
+
+        // The Thread panics while tracing the Root and exits, before it could
+        // push the Child
+        let result = {
+            let allocator = allocator.clone();
+            std::thread::spawn(move || {
+                allocator.force_gc();
+            })
+            .join()
+        };
+        assert!(result.is_err());
+
+        // Its Tracing-State is left behind, so the others can finish it
+        assert!(allocator
+            .local
+            .iter()
+            .any(|local| local.cur_traced.load(atomic::Ordering::Acquire) == node.ptr()));
+
+        // The Collection it started can still be finished by another Thread
+        allocator.collect_blocking();
+        assert!(node.marks().marked);
+        assert!(child.marks().marked);
+        assert_eq!(2, allocator.collect_stats().live_nodes);
+    }
+
+    #[test]
+    fn drop_allocator() {
+        let counters = std::sync::Arc::new(NodeCounters::default());
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::new(SingleGlobal(root.clone()));
+
+        let first = allocator.allocate(CountedNode::new(&counters));
+        let second = allocator.allocate(CountedNode::new(&counters));
+        first.next.store(second.ptr(), atomic::Ordering::Release);
+        root.store(first.ptr(), atomic::Ordering::Release);
+
+        allocator.force_gc();
+        assert_eq!(0, counters.dropped.load(atomic::Ordering::SeqCst));
+
+        drop(allocator);
+        assert_eq!(2, counters.dropped.load(atomic::Ordering::SeqCst));
+    }
+
+    /// Reclaims two unreachable Nodes next to a reachable Root, sweeps them
+    /// again once they are free and finally deallocates a third Node, checking
+    /// the given Counter after every Step
+    fn reclaim_unreachable<F>(
+        count: F,
+    ) -> (
+        Allocator<CountedNode, SingleGlobal<CountedNode>>,
+        std::sync::Arc<NodeCounters>,
+    )
+    where
+        F: Fn(&NodeCounters) -> usize,
+    {
+        let counters = std::sync::Arc::new(NodeCounters::default());
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::new(SingleGlobal(root.clone()));
+
+        let first = allocator.allocate(CountedNode::new(&counters));
+        root.store(first.ptr(), atomic::Ordering::Release);
+        allocator.allocate(CountedNode::new(&counters));
+        allocator.allocate(CountedNode::new(&counters));
+
+        allocator.force_gc();
+        assert_eq!(2, count(&counters));
+
+        // The free Nodes are swept again, but never counted twice
+        allocator.force_gc();
+        assert_eq!(2, count(&counters));
+
+        let deallocated = allocator.allocate(CountedNode::new(&counters));
+        unsafe { allocator.deallocate(deallocated) };
+        assert_eq!(3, count(&counters));
+
+        (allocator, counters)
+    }
+
+    #[test]
+    fn drop_reclaimed() {
+        let (allocator, counters) =
+            reclaim_unreachable(|counters| counters.dropped.load(atomic::Ordering::SeqCst));
+
+        // Only the Root still holds Data
+        allocator.allocate(CountedNode::new(&counters));
+        drop(allocator);
+        assert_eq!(5, counters.dropped.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn on_reclaim() {
+        let (allocator, counters) =
+            reclaim_unreachable(|counters| counters.reclaimed.load(atomic::Ordering::SeqCst));
+
+        allocator.force_gc();
+        assert_eq!(3, counters.reclaimed.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn iter_live_nodes() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::new(SingleGlobal(root.clone()));
+
+        let first = allocator.allocate(TestNode::new());
+        let second = allocator.allocate(TestNode::new());
+        let _unreachable = allocator.allocate(TestNode::new());
+        first.next.store(second.ptr(), atomic::Ordering::Release);
+        root.store(first.ptr(), atomic::Ordering::Release);
+
+        allocator.force_gc();
+
+        let mut live: Vec<_> = allocator.iter_live_nodes().collect();
+        live.sort();
+        let mut expected = vec![first.ptr(), second.ptr()];
+        expected.sort();
+        assert_eq!(expected, live);
+    }
+
+    #[test]
+    fn allocate_raw_marks() {
+        let allocator = Allocator::new(NoGlobals);
+        let phase = allocator.phase_index.load(atomic::Ordering::Acquire);
+
+        let (allocated, marks) = allocator.allocate_raw(TestNode::new());
+        assert_eq!(
+            NodeMarks {
+                phase,
+                marked: false,
+            },
+            marks
+        );
+        assert_eq!(marks, allocated.marks());
+
+        let marked = NodeMarks {
+            phase,
+            marked: true,
+        };
+        assert_eq!(Ok(()), allocated.set_marks(marks.clone(), marked.clone()));
+        assert_eq!(marked, allocated.marks());
+
+        assert_eq!(Err(marked.clone()), allocated.set_marks(marks, marked));
+    }
+
+    /// Forces a Collection on a fresh Allocator and records the Order in which
+    /// the reclaimed Nodes are handed out again, as Indices into the Page
+    fn deterministic_reclaim_order() -> Vec<usize> {
+        let allocator = Allocator::with_config(
+            NoGlobals,
+            AllocatorConfig {
+                deterministic_sweep: true,
+                ..AllocatorConfig::default()
+            },
+        );
+
+        allocator.force_gc();
+
+        let ptrs: Vec<usize> = (0..100)
+            .map(|_| allocator.allocate(TestNode::new()).ptr() as usize)
+            .collect();
+
+        let base = *ptrs.iter().min().unwrap();
+        let node_size = std::mem::size_of::<allocator::PageNode<TestNode>>();
+        ptrs.into_iter().map(|p| (p - base) / node_size).collect()
+    }
+
+    #[test]
+    fn deterministic_sweep() {
+        use std::sync::{Arc, Mutex};
+
+        let first = deterministic_reclaim_order();
+        let second = deterministic_reclaim_order();
+
+        assert_eq!(first, second);
+
+        /// Records the swept Pages together with the Thread sweeping them
+        #[derive(Default)]
+        struct PageRecorder(Mutex<Vec<(std::thread::ThreadId, u64)>>);
+
+        impl GcObserver for Arc<PageRecorder> {
+            fn on_sweep_page(&self, index: u64) {
+                let thread = std::thread::current().id();
+                self.0.lock().unwrap().push((thread, index));
+            }
+        }
+
+        let recorder = Arc::new(PageRecorder::default());
+        let allocator: Allocator<TestNode, _> = Allocator::builder(NoGlobals)
+            .config(AllocatorConfig {
+                page_size: 256,
+                initial_pages: 32,
+                deterministic_sweep: true,
+                ..AllocatorConfig::default()
+            })
+            .observer(recorder.clone())
+            .build();
+
+        // Every Collection sweeps all the Pages on a single Thread in the
+        // Order of the List, even if other Threads help with the Collection
+        let expected: Vec<u64> = (0..32).collect();
+        for _ in 0..20 {
+            recorder.0.lock().unwrap().clear();
+            let done = atomic::AtomicBool::new(false);
+            std::thread::scope(|scope| {
+                for _ in 0..3 {
+                    scope.spawn(|| {
+                        while !done.load(atomic::Ordering::Acquire) {
+                            allocator.restart(&mut []);
+                        }
+                    });
+                }
+
+                allocator.force_gc();
+                done.store(true, atomic::Ordering::Release);
+            });
+
+            let swept = recorder.0.lock().unwrap();
+            let pages: Vec<u64> = swept.iter().map(|(_, index)| *index).collect();
+            assert_eq!(expected, pages);
+            assert!(swept.iter().all(|(thread, _)| *thread == swept[0].0));
+        }
+    }
+
+    struct BigNode {
+        next: atomic::AtomicPtr<Self>,
+        _data: [u8; allocator::MAX_PAGE_NODE_SIZE * 2],
+    }
+
+    impl DataStructureNode for BigNode {
+        fn pointer_count() -> usize {
+            1
+        }
+        fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+            out.push(self.next.load(atomic::Ordering::Acquire));
+        }
+
+        fn untag_ptr(ptr: *mut Self) -> *mut Self {
+            ptr
+        }
+    }
+
+    struct SingleGlobal<N>(std::sync::Arc<atomic::AtomicPtr<N>>);
+
+    impl<N> DataStructureGlobals<N> for SingleGlobal<N> {
+        fn get_globals(&self) -> Vec<*mut N> {
+            vec![self.0.load(atomic::Ordering::Acquire)]
+        }
+    }
+
+    /// A single Global that can only be visited through the Callback
+    struct CallbackGlobal<N>(std::sync::Arc<atomic::AtomicPtr<N>>);
+
+    impl<N> DataStructureGlobals<N> for CallbackGlobal<N> {
+        fn get_globals(&self) -> Vec<*mut N> {
+            unreachable!("The Roots should be gathered using for_each_global")
+        }
+
+        fn for_each_global(&self, f: &mut dyn FnMut(*mut N)) {
+            f(self.0.load(atomic::Ordering::Acquire));
+        }
+    }
+
+    #[test]
+    fn globals_from_callback() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::<TestNode, _>::builder(CallbackGlobal(root.clone()))
+            .page_size(8)
+            .build();
+
+        let node = allocator.allocate(TestNode::new());
+        root.store(node.ptr(), atomic::Ordering::Release);
+        let _unreachable = allocator.allocate(TestNode::new());
+
+        allocator.force_gc();
+
+        assert_eq!(1, allocator.collect_stats().live_nodes);
+    }
+
+    /// A growable Set of Roots that can only be changed through
+    /// [`Allocator::with_globals_mut`]
+    struct VecGlobals<N>(Vec<*mut N>);
+
+    impl<N> DataStructureGlobals<N> for VecGlobals<N> {
+        fn get_globals(&self) -> Vec<*mut N> {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    fn globals_mut() {
+        let allocator = Allocator::<TestNode, _>::builder(VecGlobals(Vec::new()))
+            .page_size(8)
+            .build();
+
+        let first = allocator.allocate(TestNode::new());
+        let second = allocator.allocate(TestNode::new());
+        allocator.with_globals_mut(|globals| globals.0.extend([first.ptr(), second.ptr()]));
+        assert_eq!(2, allocator.with_globals(|globals| globals.0.len()));
+
+        allocator.force_gc();
+        assert_eq!(2, allocator.collect_stats().live_nodes);
+
+        // Removing a Root makes its Node unreachable
+        allocator.with_globals_mut(|globals| globals.0.retain(|ptr| *ptr == first.ptr()));
+
+        allocator.force_gc();
+        assert_eq!(1, allocator.collect_stats().live_nodes);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Allocating inside of `with_globals_mut`")]
+    fn globals_mut_allocating() {
+        let allocator = Allocator::<TestNode, _>::builder(VecGlobals(Vec::new()))
+            .page_size(8)
+            .build();
+
+        allocator.with_globals_mut(|globals| {
+            let node = allocator.allocate(TestNode::new());
+            globals.0.push(node.ptr());
+        });
+    }
+
+    #[test]
+    fn trace_step_budget() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::<TestNode, _>::builder(SingleGlobal(root.clone()))
+            .page_size(8)
+            .build();
+
+        let first = allocator.allocate(TestNode::new());
+        let second = allocator.allocate(TestNode::new());
+        let third = allocator.allocate(TestNode::new());
+        first.next.store(second.ptr(), atomic::Ordering::Release);
+        second.next.store(third.ptr(), atomic::Ordering::Release);
+        root.store(first.ptr(), atomic::Ordering::Release);
+
+        allocator.init_reclaimation();
+        allocator.update_marks();
+        for root in allocator.gather_roots() {
+            allocator.get_local().mark_stack.push(root);
+        }
+
+        // Every Step only marks a single Node of the Chain
+        for marked in 1..=3 {
+            assert!(!allocator.trace_step(1));
+            assert_eq!(marked, allocator.collect_stats().live_nodes);
+        }
+
+        assert!(allocator.trace_step(1));
+        assert_eq!(3, allocator.collect_stats().live_nodes);
+    }
+
+    #[test]
+    fn sweep_same_page_twice() {
+        let allocator = std::sync::Arc::new(
+            Allocator::<TestNode, _>::builder(NoGlobals)
+                .page_size(64)
+                .build(),
+        );
+        allocator.init_reclaimation();
+        allocator.update_marks();
+        let phase = allocator.phase_index.load(atomic::Ordering::Acquire);
+
+        // Both Threads sweep the same Page at once, but every Node must only
+        // be freed by one of them
+        let barrier = std::sync::Arc::new(std::sync::Barrier::new(2));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let allocator = allocator.clone();
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    let local = allocator.get_local();
+                    local.reset_phase(phase);
+                    let page = allocator.pages.iter().next().unwrap();
+
+                    barrier.wait();
+                    let freed = local.sweep_page(page, &allocator.allocation_pool, |_, _| {});
+                    allocator.flush_local();
+                    freed
+                })
+            })
+            .collect();
+
+        let freed: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        assert_eq!(64, freed);
+    }
+
+    #[test]
+    fn force_gc_report() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::<TestNode, _>::builder(SingleGlobal(root.clone()))
+            .page_size(16)
+            .build();
+
+        let first = allocator.allocate(TestNode::new());
+        let second = allocator.allocate(TestNode::new());
+        first.next.store(second.ptr(), atomic::Ordering::Release);
+        root.store(first.ptr(), atomic::Ordering::Release);
+
+        assert_eq!(
+            CollectionReport {
+                reclaimed_nodes: 14,
+                traced_nodes: 2,
+                pages_swept: 1,
+            },
+            allocator.force_gc()
+        );
+
+        // Once the Chain is unreachable, all of its Nodes are reclaimed as well
+        root.store(std::ptr::null_mut(), atomic::Ordering::Release);
+        assert_eq!(
+            CollectionReport {
+                reclaimed_nodes: 16,
+                traced_nodes: 0,
+                pages_swept: 1,
+            },
+            allocator.force_gc()
+        );
+    }
+
+    #[test]
+    fn trace_cycle() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::<TestNode, _>::builder(SingleGlobal(root.clone()))
+            .page_size(8)
+            .build();
+
+        let first = allocator.allocate(TestNode::new());
+        let second = allocator.allocate(TestNode::new());
+        let third = allocator.allocate(TestNode::new());
+        first.next.store(second.ptr(), atomic::Ordering::Release);
+        second.next.store(third.ptr(), atomic::Ordering::Release);
+        third.next.store(first.ptr(), atomic::Ordering::Release);
+        root.store(first.ptr(), atomic::Ordering::Release);
+
+        allocator.init_reclaimation();
+        allocator.update_marks();
+        for root in allocator.gather_roots() {
+            allocator.get_local().mark_stack.push(root);
+        }
+
+        // Every Node is expanded once and the Edge back to the first Node is
+        // only popped again, so the Trace terminates after a few Steps
+        let mut steps = 0;
+        while !allocator.trace_step(1) {
+            assert!(allocator.collect_stats().mark_stack_depth <= 1);
+            steps += 1;
+            assert!(steps <= 4, "The Trace did not terminate");
+        }
+        assert_eq!(3, allocator.collect_stats().live_nodes);
+
+        // The Sweep keeps the entire Cycle
+        allocator.sweep();
+        assert_eq!(3, allocator.iter_live_nodes().count());
+    }
+
+    #[test]
+    fn observer() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default)]
+        struct Recorder {
+            phases: Mutex<Vec<u64>>,
+            pages: Mutex<Vec<u64>>,
+            reclaimed: Mutex<Vec<usize>>,
+        }
+        impl GcObserver for Arc<Recorder> {
+            fn on_phase_start(&self, phase: u64) {
+                self.phases.lock().unwrap().push(phase);
+            }
+            fn on_sweep_page(&self, index: u64) {
+                self.pages.lock().unwrap().push(index);
+            }
+            fn on_reclaim(&self, ptr: *const ()) {
+                self.reclaimed.lock().unwrap().push(ptr as usize);
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let root = Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::builder(SingleGlobal(root.clone()))
+            .page_size(8)
+            .initial_pages(2)
+            .observer(recorder.clone())
+            .build();
+
+        let reachable = allocator.allocate(TestNode::new());
+        root.store(reachable.ptr(), atomic::Ordering::Release);
+        let unreachable = allocator.allocate(TestNode::new());
+
+        // The initial Sweep, when creating the Allocator, is also reported
+        assert_eq!(vec![0, 1], *recorder.pages.lock().unwrap());
+        recorder.pages.lock().unwrap().clear();
+        recorder.reclaimed.lock().unwrap().clear();
+
+        let report = allocator.force_gc();
+
+        assert_eq!(vec![1], *recorder.phases.lock().unwrap());
+        assert_eq!(vec![0, 1], *recorder.pages.lock().unwrap());
+
+        let reclaimed = recorder.reclaimed.lock().unwrap();
+        assert!(reclaimed.contains(&(unreachable.ptr() as usize)));
+        assert!(!reclaimed.contains(&(reachable.ptr() as usize)));
+        assert_eq!(report.reclaimed_nodes, reclaimed.len());
+    }
+
+    #[test]
+    fn collection_policy() {
+        // One more Allocation than fits into a Buffer, to consult the Policy
+        let phase_after = |allocator: Allocator<TestNode, NoGlobals>| {
+            for _ in 0..5 {
+                allocator.allocate(TestNode::new());
+            }
+            allocator.phase_index.load(atomic::Ordering::Acquire)
+        };
+
+        let never = Allocator::builder(NoGlobals)
+            .page_size(16)
+            .allocation_buffer_size(4)
+            .collection_policy(NeverCollect)
+            .build();
+        assert_eq!(0, phase_after(never));
+
+        let always = Allocator::builder(NoGlobals)
+            .page_size(16)
+            .allocation_buffer_size(4)
+            .collection_policy(ThresholdPolicy::new(usize::MAX))
+            .build();
+        assert_ne!(0, phase_after(always));
+    }
+
+    #[test]
+    #[should_panic(expected = "allocation_buffer_size must be at least 1")]
+    fn zero_allocation_buffer_size() {
+        let _ = Allocator::builder(NoGlobals).allocation_buffer_size(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "allocation_buffer_size must be at least 1")]
+    fn zero_allocation_buffer_size_config() {
+        let config = AllocatorConfig {
+            allocation_buffer_size: 0,
+            ..AllocatorConfig::default()
+        };
+        let _: Allocator<TestNode, _> = Allocator::with_config(NoGlobals, config);
+    }
+
+    #[test]
+    fn policy_stats() {
+        let allocator = Allocator::<TestNode, _>::builder(NoGlobals)
+            .page_size(16)
+            .allocation_buffer_size(4)
+            .build();
+
+        // The first Buffer is filled by the initial Sweep, the next one is
+        // taken from the global Pool
+        for _ in 0..5 {
+            allocator.allocate(TestNode::new());
+        }
+        let stats = allocator.policy_stats();
+        assert_eq!(4, stats.buffer_size);
+        assert_eq!(4, stats.allocated_nodes);
+        assert_eq!(2, stats.pooled_buffers);
+
+        allocator.force_gc();
+        let stats = allocator.policy_stats();
+        assert_eq!(0, stats.allocated_nodes);
+        assert_eq!(
+            allocator.allocation_pool.available(stats.phase_index),
+            stats.pooled_buffers
+        );
+    }
+
+    #[test]
+    fn flush_local() {
+        let allocator = std::sync::Arc::new(
+            Allocator::<TestNode, _>::builder(NoGlobals)
+                .page_size(16)
+                .allocation_buffer_size(8)
+                .build(),
+        );
+        let pooled = allocator.allocation_pool.available(0);
+
+        let thread_allocator = allocator.clone();
+        std::thread::spawn(move || {
+            thread_allocator.allocate(TestNode::new());
+            assert!(!thread_allocator.get_local().alloc.is_empty());
+
+            thread_allocator.flush_local();
+            assert!(thread_allocator.get_local().alloc.is_empty());
+        })
+        .join()
+        .unwrap();
+
+        // The Thread took one Buffer and returned the rest of it
+        assert_eq!(pooled, allocator.allocation_pool.available(0));
+    }
+
+    #[test]
+    fn allocating_node_is_kept() {
+        let allocator = Allocator::<TestNode, _>::new(NoGlobals);
+        let ptr = allocator.allocate(TestNode::new()).into_raw();
+
+        // Pretend the Thread is still writing into the Node
+        let local = allocator.get_local();
+        local.allocating.store(ptr, atomic::Ordering::SeqCst);
+        allocator.force_gc();
+        assert_eq!(1, allocator.collect_stats().live_nodes);
+
+        local
+            .allocating
+            .store(std::ptr::null_mut(), atomic::Ordering::SeqCst);
+        allocator.force_gc();
+        assert_eq!(0, allocator.collect_stats().live_nodes);
+    }
+
+    #[test]
+    fn restart_joins_collection() {
+        use std::sync::Arc;
+
+        let root = Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Arc::new(Allocator::new(SingleGlobal(root.clone())));
+
+        let node = allocator.allocate(TestNode::new());
+        root.store(node.ptr(), atomic::Ordering::Release);
+        allocator.allocate(TestNode::new());
+
+        // Another Thread starts a Collection, but does not get to gather the
+        // Roots before this Thread catches up
+        {
+            let allocator = allocator.clone();
+            std::thread::spawn(move || allocator.init_reclaimation())
+                .join()
+                .unwrap();
+        }
+        allocator.restart(&mut []);
+
+        assert!(node.marks().marked);
+        assert_eq!(1, allocator.collect_stats().live_nodes);
+    }
+
+    #[test]
+    fn late_sweep_skips_swept_pages() {
+        let allocator = Allocator::<TestNode, _>::builder(NoGlobals)
+            .page_size(4)
+            .build();
+        for _ in 0..3 {
+            allocator.grow(allocator.get_local());
+        }
+
+        assert_eq!(4, allocator.force_gc().pages_swept);
+
+        // A Thread that only starts sweeping once the Sweep of the Phase is
+        // done, does not sweep any of the Pages again
+        assert_eq!(0, allocator.sweep().pages);
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Allocator<TestNode, NoGlobals>>();
+
+        let allocator = std::sync::Arc::new(
+            Allocator::<TestNode, _>::builder(NoGlobals)
+                .page_size(16)
+                .build(),
+        );
+
+        // Both Threads stay alive until the other one obtained its Local-State
+        let barrier = std::sync::Arc::new(std::sync::Barrier::new(2));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let allocator = allocator.clone();
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    allocator.allocate(TestNode::new());
+                    let local = allocator.get_local() as *const Local<TestNode> as usize;
+                    barrier.wait();
+
+                    allocator.force_gc();
+                    local
+                })
+            })
+            .collect();
+        let locals: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        // Every Thread got its own Local-State, including the Thread that
+        // created the Allocator and ran the initial Sweep
+        assert_ne!(locals[0], locals[1]);
+        assert_eq!(3, allocator.local.iter().count());
+    }
+
+    #[test]
+    fn aoa_box_raw() {
+        let allocator: Allocator<TestNode, _> = Allocator::new(NoGlobals);
+
+        let node = allocator.allocate(TestNode::new());
+        let marks = node.marks();
+        let ptr = node.into_raw();
+
+        let node = unsafe { AoaBox::from_raw(ptr) };
+        assert_eq!(ptr, node.ptr());
+        assert_eq!(marks, node.marks());
+        assert!(node.next.load(atomic::Ordering::Acquire).is_null());
+    }
+
+    #[test]
+    fn finish_ignores_newer_phase_marks() {
+        let allocator: Allocator<TestNode, _> = Allocator::new(NoGlobals);
+        let node = allocator.allocate(TestNode::new());
+        allocator.set_phase(1);
+
+        let local = allocator.get_local();
+        local
+            .cur_traced
+            .store(node.ptr(), atomic::Ordering::Release);
+
+        // Another Thread already moved the Node into a newer Phase
+        node.set_marks(
+            node.marks(),
+            NodeMarks {
+                phase: 2,
+                marked: false,
+            },
+        )
+        .unwrap();
+
+        assert!(allocator.finish_or_progress());
+        assert!(local.mark_stack.is_empty());
+    }
+
+    #[test]
+    fn overflow_alloc() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::with_config(
+            SingleGlobal(root.clone()),
+            AllocatorConfig {
+                overflow_alloc: true,
+                ..AllocatorConfig::default()
+            },
+        );
+
+        let new_node = || BigNode {
+            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            _data: [0; allocator::MAX_PAGE_NODE_SIZE * 2],
+        };
+
+        let reachable = allocator.allocate(new_node());
+        let _unreachable = allocator.allocate(new_node());
+        root.store(reachable.ptr(), atomic::Ordering::Release);
+        assert_eq!(2, allocator.overflow.len());
+
+        allocator.force_gc();
+        assert_eq!(1, allocator.overflow.len());
+        assert!(reachable.marks().marked);
+
+        root.store(std::ptr::null_mut(), atomic::Ordering::Release);
+        allocator.force_gc();
+        assert_eq!(0, allocator.overflow.len());
+    }
+
+    #[test]
+    fn overflow_alloc_concurrent() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = std::sync::Arc::new(Allocator::with_config(
+            SingleGlobal(root.clone()),
+            AllocatorConfig {
+                overflow_alloc: true,
+                ..AllocatorConfig::default()
+            },
+        ));
+
+        // Every Thread sweeps the overflow Nodes of the others, while they
+        // may still be clearing their Marks or sweeping them as well
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let allocator = allocator.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        allocator.allocate(BigNode {
+                            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+                            _data: [0; allocator::MAX_PAGE_NODE_SIZE * 2],
+                        });
+                        allocator.force_gc();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        allocator.force_gc();
+        allocator.force_gc();
+        assert_eq!(0, allocator.overflow.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "wrapping around")]
+    fn phase_wraparound() {
+        let allocator = Allocator::new(NoGlobals);
+        allocator.set_phase(u64::MAX);
+
+        allocator.force_gc();
+    }
+
+    #[test]
+    #[should_panic(expected = "wrapping around")]
+    fn phase_max() {
+        let allocator = Allocator::new(NoGlobals);
+        allocator.set_phase(Phase::MAX - 1);
+
+        // The last Phase can still be used normally
+        allocator.force_gc();
+        assert_eq!(
+            Phase::MAX,
+            allocator.phase_index.load(atomic::Ordering::Acquire)
+        );
+
+        allocator.force_gc();
+    }
+
+    struct Reader {
+        allocator: Allocator<TestNode, NoGlobals>,
+        value: atomic::AtomicUsize,
+    }
+
+    impl Reader {
+        #[freeaccess(allocator = "self.allocator")]
+        fn read(&self, early: bool) -> usize {
+            let value = self.value.load(atomic::Ordering::Acquire);
+            if early {
+                return value + 1;
+            }
+            value
+        }
+    }
+
+    struct LimitedReader {
+        allocator: Allocator<TestNode, NoGlobals>,
+        attempts: atomic::AtomicUsize,
+    }
+
+    impl LimitedReader {
+        /// Marks the Thread as dirty during the first `dirty_reads` Attempts
+        /// and returns the Number of the current Attempt
+        fn mark_dirty(&self, dirty_reads: usize) -> usize {
+            let attempt = self.attempts.fetch_add(1, atomic::Ordering::SeqCst);
+            if attempt < dirty_reads {
+                let local = self.allocator.get_local();
+                local.dirty.mark_dirty(local.dirty.get().phase + 1);
+            }
+            attempt
+        }
+
+        /// Like `mark_dirty`, but bails out during the dirty Attempts
+        fn bail_dirty(&self, dirty_reads: usize) -> Option<usize> {
+            let attempt = self.mark_dirty(dirty_reads);
+            if attempt < dirty_reads {
+                None
+            } else {
+                Some(attempt)
+            }
+        }
+
+        #[freeaccess(allocator = "self.allocator")]
+        fn try_read(&self, dirty_reads: usize) -> Option<usize> {
+            let attempt = self.bail_dirty(dirty_reads)?;
+            Some(attempt)
+        }
+
+        #[freeaccess(allocator = "self.allocator", restart_limit = 2, on_limit = "error")]
+        fn read(&self, dirty_reads: usize) -> Result<usize, RestartLimitExceeded> {
+            let _attempt = self.mark_dirty(dirty_reads);
+            Ok(13)
+        }
+
+        #[freeaccess(allocator = "self.allocator", restart_limit = 2)]
+        fn read_or_panic(&self, dirty_reads: usize) -> usize {
+            let _attempt = self.mark_dirty(dirty_reads);
+            13
+        }
+    }
+
+    #[test]
+    fn freeaccess_restart_limit() {
+        let reader = LimitedReader {
+            allocator: Allocator::new(NoGlobals),
+            attempts: atomic::AtomicUsize::new(0),
+        };
+
+        // Two Restarts are still allowed
+        assert_eq!(Ok(13), reader.read(2));
+        assert_eq!(3, reader.attempts.swap(0, atomic::Ordering::SeqCst));
+
+        assert_eq!(Err(RestartLimitExceeded { limit: 2 }), reader.read(3));
+        assert_eq!(3, reader.attempts.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn freeaccess_restart_on_try() {
+        let reader = LimitedReader {
+            allocator: Allocator::new(NoGlobals),
+            attempts: atomic::AtomicUsize::new(0),
+        };
+
+        // The first Attempt bails out through the `?` while being dirty, so
+        // it is restarted instead of returning
+        assert_eq!(Some(1), reader.try_read(1));
+        assert_eq!(2, reader.attempts.load(atomic::Ordering::SeqCst));
+        assert!(reader.allocator.validate_read().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "The Read-Region of `read_or_panic` was restarted more than 2 Times")]
+    fn freeaccess_restart_limit_panic() {
+        let reader = LimitedReader {
+            allocator: Allocator::new(NoGlobals),
+            attempts: atomic::AtomicUsize::new(0),
+        };
+
+        reader.read_or_panic(usize::MAX);
+    }
+
+    #[test]
+    fn freeaccess_read() {
+        let reader = Reader {
+            allocator: Allocator::new(NoGlobals),
+            value: atomic::AtomicUsize::new(13),
+        };
+
+        assert_eq!(13, reader.read(false));
+        assert_eq!(14, reader.read(true));
+    }
+
+    #[test]
+    fn write_only_block() {
+        let allocator = Allocator::new(NoGlobals);
+        let node = allocator.allocate(TestNode::new());
+        let ptrs = [node.ptr()];
+
+        let result = write_only! { allocator, [ptrs[0]];
+            let next = node.next.load(atomic::Ordering::Acquire);
+            next.is_null()
+        };
+        assert_eq!(Ok(true), result);
+        assert_eq!(node.ptr(), ptrs[0]);
+    }
+
+    #[test]
+    fn write_only_races_collection() {
+        use std::sync::Arc;
+
+        let allocator = Arc::new(Allocator::new(NoGlobals));
+        let node = allocator.allocate(TestNode::new());
+        let mut ptr = node.ptr();
+
+        let collector = {
+            let allocator = allocator.clone();
+            std::thread::spawn(move || {
+                for _ in 0..50 {
+                    allocator.force_gc();
+                }
+            })
+        };
+
+        let mut failed = 0;
+        while !collector.is_finished() || failed == 0 {
+            let arbiter = allocator.get_local().arbiter.get();
+            match allocator.begin_write_only(&[ptr]) {
+                Ok(_) => {}
+                Err(_) => {
+                    // A failed Attempt never switches the Frames
+                    assert_eq!(arbiter, allocator.get_local().arbiter.get());
+                    failed += 1;
+
+                    ptr = node.ptr();
+                    allocator.restart(&mut [&mut ptr]);
+                    ptr = node.ptr();
+                }
+            };
+
+            if collector.is_finished() && failed == 0 {
+                // Make sure the Error-Path is taken at least once
+                allocator.force_gc();
+                allocator.force_gc();
+            }
+        }
+
+        collector.join().unwrap();
+    }
+
+    #[test]
+    fn write_only_alternates_frames() {
+        let allocator = Allocator::new(NoGlobals);
+        let first = allocator.allocate(TestNode::new());
+        let second = allocator.allocate(TestNode::new());
+        let local = allocator.get_local();
+
+        assert_eq!(Ok(()), allocator.begin_write_only(&[first.ptr()]));
+        let first_arbiter = local.arbiter.get();
+        assert_eq!(
+            vec![first.ptr()],
+            local.hazard_ptr_frames[first_arbiter as usize].roots()
+        );
+
+        assert_eq!(Ok(()), allocator.begin_write_only(&[second.ptr()]));
+        let second_arbiter = local.arbiter.get();
+        assert_ne!(first_arbiter, second_arbiter);
+        assert_eq!(
+            vec![second.ptr()],
+            local.hazard_ptr_frames[second_arbiter as usize].roots()
+        );
+
+        // The previous Period stays protected until the next one starts
+        assert_eq!(
+            vec![first.ptr()],
+            local.hazard_ptr_frames[first_arbiter as usize].roots()
+        );
+
+        // The Protections from two Periods ago are released
+        let third = allocator.allocate(TestNode::new());
+        assert_eq!(Ok(()), allocator.begin_write_only(&[third.ptr()]));
+        assert_eq!(first_arbiter, local.arbiter.get());
+        assert_eq!(
+            vec![third.ptr()],
+            local.hazard_ptr_frames[first_arbiter as usize].roots()
+        );
+        assert_eq!(
+            vec![second.ptr()],
+            local.hazard_ptr_frames[second_arbiter as usize].roots()
+        );
+    }
+
+    #[test]
+    fn write_only_failure_keeps_frame_empty() {
+        let allocator = Allocator::new(NoGlobals);
+        let node = allocator.allocate(TestNode::new());
+
+        let local = allocator.get_local();
+        local.dirty.mark_dirty(local.dirty.get().phase + 1);
+
+        assert_eq!(Err(()), allocator.begin_write_only(&[node.ptr()]));
+
+        let next_arbiter = local.arbiter.next();
+        assert!(local.hazard_ptr_frames[next_arbiter as usize]
+            .roots()
+            .is_empty());
+    }
+
+    #[test]
+    fn read_checked() {
+        struct Counted(std::sync::Arc<atomic::AtomicUsize>);
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, atomic::Ordering::SeqCst);
+            }
+        }
+
+        let allocator = Allocator::<TestNode, _>::new(NoGlobals);
+        let drops = std::sync::Arc::new(atomic::AtomicUsize::new(0));
+
+        let value = allocator.read_checked(|| Counted(drops.clone()));
+        assert!(value.is_ok());
+        drop(value);
+        assert_eq!(1, drops.load(atomic::Ordering::SeqCst));
+
+        let local = allocator.get_local();
+        local.dirty.mark_dirty(local.dirty.get().phase + 1);
+
+        assert!(allocator.read_checked(|| Counted(drops.clone())).is_err());
+        assert_eq!(2, drops.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn protect_guard() {
+        let allocator = Allocator::new(NoGlobals);
+        let node = allocator.allocate(TestNode::new());
+
+        {
+            let guard = allocator.protect(node.ptr());
+            assert_eq!(node.ptr(), guard.ptr());
+            assert_eq!(vec![node.ptr()], allocator.local_roots());
+
+            allocator.force_gc();
+            assert!(node.marks().marked);
+        }
+
+        assert!(allocator.local_roots().is_empty());
+    }
+
+    #[test]
+    fn protect_guard_swap() {
+        let allocator = Allocator::new(NoGlobals);
+        let first = allocator.allocate(TestNode::new());
+        let second = allocator.allocate(TestNode::new());
+
+        let mut guard = allocator.protect(first.ptr());
+        assert_eq!(first.ptr(), guard.swap(second.ptr()));
+        assert_eq!(second.ptr(), guard.ptr());
+        assert_eq!(vec![second.ptr()], allocator.local_roots());
+
+        drop(guard);
+        assert!(allocator.local_roots().is_empty());
+    }
+
+    #[test]
+    fn protect_guard_across_restart() {
+        let allocator = Allocator::new(NoGlobals);
+        let node = allocator.allocate(TestNode::new());
+
+        let guard = allocator.protect(node.ptr());
+        allocator.force_gc();
+        assert!(node.marks().marked);
+
+        // The Guard is still alive, so the Node stays protected
+        allocator.restart(&mut []);
+        assert_eq!(vec![node.ptr()], allocator.local_roots());
+
+        // Dropping the Guard keeps the same Ptr protected by the Operation
+        assert_eq!(Ok(()), allocator.begin_write_only(&[node.ptr()]));
+        drop(guard);
+        assert_eq!(vec![node.ptr()], allocator.local_roots());
+    }
+
+    #[test]
+    fn deallocate_reuse() {
+        let allocator = Allocator::new(NoGlobals);
+
+        let first = allocator.allocate(TestNode::new());
+        let first_ptr = first.ptr();
+        unsafe { allocator.deallocate(first) };
+
+        let second = allocator.allocate(TestNode::new());
+        assert_eq!(first_ptr, second.ptr());
+    }
+
+    #[test]
+    fn allocate_grows_heap() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::with_config(
+            SingleGlobal(root.clone()),
+            AllocatorConfig {
+                page_size: 4,
+                ..AllocatorConfig::default()
+            },
+        );
+
+        // Keep every Node reachable by linking them all into a List
+        let mut ptrs = Vec::new();
+        for _ in 0..10 {
+            let node = allocator.allocate(TestNode::new());
+            node.next.store(
+                root.load(atomic::Ordering::Acquire),
+                atomic::Ordering::Release,
+            );
+            root.store(node.ptr(), atomic::Ordering::Release);
+            ptrs.push(node.ptr());
+        }
+
+        assert!(allocator.pages.page_count() >= 3);
+
+        ptrs.sort();
+        ptrs.dedup();
+        assert_eq!(10, ptrs.len());
+    }
+
+    #[test]
+    fn new_with_pages() {
+        let pages = PageList::new(4);
+        pages.grow();
+
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::new_with(SingleGlobal(root.clone()), pages);
+        assert_eq!(2, allocator.page_count());
+        assert_eq!(8, allocator.node_capacity());
+
+        // Keep every Node reachable by linking them all into a List
+        let allocate = || {
+            let node = allocator.allocate(TestNode::new());
+            node.next.store(
+                root.load(atomic::Ordering::Acquire),
+                atomic::Ordering::Release,
+            );
+            root.store(node.ptr(), atomic::Ordering::Release);
+            node.ptr()
+        };
+
+        let mut ptrs: Vec<_> = (0..8).map(|_| allocate()).collect();
+        assert_eq!(2, allocator.page_count());
+
+        // The Heap is exhausted, so the next Allocation has to grow it
+        ptrs.push(allocate());
+        assert_eq!(3, allocator.page_count());
+        assert_eq!(12, allocator.node_capacity());
+
+        ptrs.sort();
+        ptrs.dedup();
+        assert_eq!(9, ptrs.len());
+    }
+
+    #[test]
+    fn allocate_n() {
+        let allocator = Allocator::<TestNode, _>::builder(NoGlobals)
+            .page_size(64)
+            .allocation_buffer_size(4)
+            .build();
+
+        // The Buffer of the Thread is drained multiple Times during the Batch
+        let allocated = allocator.allocate_n((1..=20).map(|i| {
+            let node = TestNode::new();
+            node.next
+                .store(i as *mut TestNode, atomic::Ordering::Release);
+            node
+        }));
+
+        assert_eq!(20, allocated.len());
+        for (i, node) in allocated.iter().enumerate() {
+            assert_eq!(
+                (i + 1) as *mut TestNode,
+                node.next.load(atomic::Ordering::Acquire)
+            );
+        }
+
+        let mut ptrs: Vec<_> = allocated.iter().map(|n| n.ptr()).collect();
+        ptrs.sort();
+        ptrs.dedup();
+        assert_eq!(20, ptrs.len());
+    }
+
+    #[test]
+    fn project() {
+        let allocator = Allocator::<TestNode, _>::builder(NoGlobals).build();
+
+        let node = allocator.allocate(TestNode::new());
+        let field = node.project(|n| &n.next);
+
+        assert_eq!(
+            unsafe { (node.ptr() as *const u8).add(core::mem::offset_of!(TestNode, next)) },
+            field as *const u8
+        );
+
+        node.next
+            .store(0x10 as *mut TestNode, atomic::Ordering::Release);
+        assert_eq!(0x10 as *mut TestNode, unsafe {
+            (*field).load(atomic::Ordering::Acquire)
+        });
+    }
+
+    #[test]
+    fn defer() {
+        let allocator = Allocator::<TestNode, _>::new(NoGlobals);
+        let ran = std::sync::Arc::new(atomic::AtomicUsize::new(0));
+
+        let counter = ran.clone();
+        allocator.defer(move || {
+            counter.fetch_add(1, atomic::Ordering::SeqCst);
+        });
+        assert_eq!(0, ran.load(atomic::Ordering::SeqCst));
+
+        allocator.force_gc();
+        assert_eq!(1, ran.load(atomic::Ordering::SeqCst));
+
+        // Functions only run once
+        allocator.force_gc();
+        assert_eq!(1, ran.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn defer_waits_for_sweepers() {
+        let allocator = Allocator::<TestNode, _>::new(NoGlobals);
+        let ran = std::sync::Arc::new(atomic::AtomicBool::new(false));
+
+        let flag = ran.clone();
+        allocator.defer(move || flag.store(true, atomic::Ordering::SeqCst));
+
+        // Pretend another Thread is still sweeping its Pages
+        allocator.sweepers.fetch_add(1, atomic::Ordering::SeqCst);
+        allocator.force_gc();
+        assert!(!ran.load(atomic::Ordering::SeqCst));
+
+        allocator.sweepers.fetch_sub(1, atomic::Ordering::SeqCst);
+        allocator.force_gc();
+        assert!(ran.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn defer_waits_for_budgeted_sweep() {
+        let counters = std::sync::Arc::new(NodeCounters::default());
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::<CountedNode, _>::builder(SingleGlobal(root))
+            .config(AllocatorConfig {
+                page_size: 8,
+                initial_pages: 4,
+                sweep_budget_pages: Some(1),
+                ..Default::default()
+            })
+            .build();
+
+        for _ in 0..32 {
+            allocator.allocate(CountedNode::new(&counters));
+        }
+        let pages = allocator.page_count();
+        assert_eq!(4, pages);
+
+        // The Number of Nodes that were already dropped when the Function ran
+        let dropped_before = std::sync::Arc::new(atomic::AtomicUsize::new(usize::MAX));
+        let result = dropped_before.clone();
+        let dropped = counters.clone();
+        allocator.defer(move || {
+            result.store(
+                dropped.dropped.load(atomic::Ordering::SeqCst),
+                atomic::Ordering::SeqCst,
+            );
+        });
+
+        // Every Collection only sweeps a single Page
+        for _ in 1..pages {
+            allocator.force_gc();
+            assert_eq!(usize::MAX, dropped_before.load(atomic::Ordering::SeqCst));
+        }
+
+        allocator.force_gc();
+        assert_eq!(32, dropped_before.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn defer_runs_on_drop() {
+        let allocator = Allocator::<TestNode, _>::new(NoGlobals);
+        let ran = std::sync::Arc::new(atomic::AtomicBool::new(false));
+
+        let flag = ran.clone();
+        allocator.defer(move || flag.store(true, atomic::Ordering::SeqCst));
+
+        drop(allocator);
+        assert!(ran.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn quiesce() {
+        let allocator = std::sync::Arc::new(
+            Allocator::<TestNode, _>::builder(NoGlobals)
+                .page_size(16)
+                .allocation_buffer_size(8)
+                .build(),
+        );
+
+        let other = allocator.clone();
+        let parked = std::sync::Arc::new(std::sync::Barrier::new(2));
+        let resume = std::sync::Arc::new(std::sync::Barrier::new(2));
+        let handle = {
+            let (parked, resume) = (parked.clone(), resume.clone());
+            std::thread::spawn(move || {
+                let node = other.allocate(TestNode::new());
+                other.get_local().hazard_ptr_frames[0].store(node.ptr());
+                parked.wait();
+                resume.wait();
+            })
+        };
+
+        parked.wait();
+        allocator.allocate(TestNode::new());
+        let pooled = allocator.allocation_pool.available(0);
+
+        unsafe { allocator.quiesce() };
+
+        for local in allocator.local.iter() {
+            assert!(local.alloc.is_empty());
+            assert!(local.hazard_ptr_frames[0].roots().is_empty());
+            assert!(local.mark_stack.is_empty());
+        }
+        // The Buffers of both Threads were moved into the global Pool
+        assert_eq!(pooled + 2, allocator.allocation_pool.available(0));
+
+        resume.wait();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn snapshot() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::new(SingleGlobal(root.clone()));
+
+        let child = allocator.allocate(TestNode::new());
+        let parent = allocator.allocate(TestNode::new());
+        parent.next.store(child.ptr(), atomic::Ordering::Release);
+        root.store(parent.ptr(), atomic::Ordering::Release);
+        // Cycles are only recorded once
+        child.next.store(parent.ptr(), atomic::Ordering::Release);
+        let _unreachable = allocator.allocate(TestNode::new());
+
+        allocator.force_gc();
+        let snapshot = unsafe { allocator.snapshot() };
+
+        assert_eq!(
+            allocator.phase_index.load(atomic::Ordering::Acquire),
+            snapshot.phase
+        );
+        assert_eq!(vec![parent.ptr() as usize], snapshot.roots);
+
+        let mut nodes = snapshot.nodes.clone();
+        nodes.sort_by_key(|n| n.address);
+        let mut expected = vec![
+            SnapshotNode {
+                address: parent.ptr() as usize,
+                marked: true,
+                phase: snapshot.phase,
+                edges: vec![child.ptr() as usize],
+            },
+            SnapshotNode {
+                address: child.ptr() as usize,
+                marked: true,
+                phase: snapshot.phase,
+                edges: vec![parent.ptr() as usize],
+            },
+        ];
+        expected.sort_by_key(|n| n.address);
+        assert_eq!(expected, nodes);
+    }
+
+    #[test]
+    fn shrink() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::builder(SingleGlobal(root.clone()))
+            .page_size(4)
+            .allocation_buffer_size(4)
+            .build();
+
+        // Grow the Heap with a Chain of reachable Nodes
+        for _ in 0..16 {
+            let node = allocator.allocate(TestNode::new());
+            node.next.store(
+                root.load(atomic::Ordering::Acquire),
+                atomic::Ordering::Release,
+            );
+            root.store(node.ptr(), atomic::Ordering::Release);
+        }
+        let grown = allocator.page_count();
+        assert!(grown > 2);
+
+        // Only keep the oldest Node, which is stored in the first Page
+        let mut oldest = root.load(atomic::Ordering::Acquire);
+        while !unsafe { &*oldest }
+            .next
+            .load(atomic::Ordering::Acquire)
+            .is_null()
+        {
+            oldest = unsafe { &*oldest }.next.load(atomic::Ordering::Acquire);
+        }
+        root.store(oldest, atomic::Ordering::Release);
+        allocator.force_gc();
+
+        let freed = unsafe { allocator.shrink() };
+        assert!(freed > 0);
+        assert_eq!(grown - freed as u64, allocator.page_count());
+
+        // The remaining free Nodes can still be allocated, without handing
+        // out any Node twice
+        let mut ptrs = Vec::new();
+        for _ in 0..8 {
+            let node = allocator.allocate(TestNode::new());
+            node.next.store(
+                root.load(atomic::Ordering::Acquire),
+                atomic::Ordering::Release,
+            );
+            root.store(node.ptr(), atomic::Ordering::Release);
+            ptrs.push(node.ptr());
+        }
+        assert!(!ptrs.contains(&oldest));
+        ptrs.sort();
+        ptrs.dedup();
+        assert_eq!(8, ptrs.len());
+    }
+
+    #[test]
+    fn allocate_n_grows_heap() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::builder(SingleGlobal(root.clone()))
+            .page_size(4)
+            .build();
+
+        // Fill the first Page with reachable Nodes
+        let mut ptrs = Vec::new();
+        for _ in 0..4 {
+            let node = allocator.allocate(TestNode::new());
+            node.next.store(
+                root.load(atomic::Ordering::Acquire),
+                atomic::Ordering::Release,
+            );
+            root.store(node.ptr(), atomic::Ordering::Release);
+            ptrs.push(node.ptr());
+        }
+
+        let allocated = allocator.allocate_n((0..4).map(|_| TestNode::new()));
+        assert_eq!(4, allocated.len());
+        assert!(allocator.page_count() >= 2);
+
+        ptrs.extend(allocated.iter().map(|n| n.ptr()));
+        ptrs.sort();
+        ptrs.dedup();
+        assert_eq!(8, ptrs.len());
+    }
+
+    #[test]
+    fn restart_shrinks_frames() {
+        let allocator = Allocator::<TestNode, _>::new(NoGlobals);
+        let ptrs: Vec<_> = (1..=4 * KEPT_HAZARD_PTRS)
+            .map(|i| (i * 8) as *mut TestNode)
+            .collect();
+
+        assert_eq!(Ok(()), allocator.begin_write_only(&ptrs));
+        let local = allocator.get_local();
+        let frame = &local.hazard_ptr_frames[local.arbiter.get() as usize];
+        assert_eq!(4 * KEPT_HAZARD_PTRS, frame.high_water_mark());
+
+        allocator.restart(&mut []);
+        assert_eq!(KEPT_HAZARD_PTRS, frame.high_water_mark());
+        assert!(allocator.local_roots().is_empty());
+    }
+
+    #[test]
+    fn restart_catches_up() {
+        use std::sync::{mpsc, Arc};
+
+        let allocator = Arc::new(Allocator::new(NoGlobals));
+        let node_ptr = allocator.allocate(TestNode::new()).ptr() as usize;
+
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (gc_tx, gc_rx) = mpsc::channel();
+
+        let handle = {
+            let allocator = allocator.clone();
+            std::thread::spawn(move || {
+                let node_ptr = node_ptr as *mut TestNode;
+                allocator.begin_write_only(&[node_ptr]).unwrap();
+                ready_tx.send(()).unwrap();
+
+                gc_rx.recv().unwrap();
+                assert_eq!(Err(()), allocator.validate_read());
+
+                let mut local_ptr = node_ptr;
+                allocator.restart(&mut [&mut local_ptr]);
+
+                let local = allocator.get_local();
+                assert_eq!(
+                    allocator.phase_index.load(atomic::Ordering::Acquire),
+                    local.phase_index.load(atomic::Ordering::Acquire)
+                );
+                assert!(local.hazard_ptr_frames[0].roots().is_empty());
+                assert!(local.hazard_ptr_frames[1].roots().is_empty());
+                assert!(local_ptr.is_null());
+                assert_eq!(Ok(()), allocator.validate_read());
+            })
+        };
+
+        ready_rx.recv().unwrap();
+        for _ in 0..3 {
+            allocator.force_gc();
+        }
+        gc_tx.send(()).unwrap();
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn collect_blocking() {
+        use std::sync::Arc;
+
+        let root = Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Arc::new(
+            Allocator::builder(SingleGlobal(root.clone()))
+                .page_size(8)
+                .build(),
+        );
+
+        let reachable = allocator.allocate(TestNode::new());
+        root.store(reachable.ptr(), atomic::Ordering::Release);
+        let _unreachable = allocator.allocate(TestNode::new());
+
+        // Nothing is running, so a new Collection is started
+        let phase = allocator.phase_index.load(atomic::Ordering::Acquire);
+        allocator.collect_blocking();
+        assert_eq!(
+            phase + 1,
+            allocator.phase_index.load(atomic::Ordering::Acquire)
+        );
+        assert_eq!(1, allocator.collect_stats().live_nodes);
+
+        let _unreachable = allocator.allocate(TestNode::new());
+
+        // Another Thread only started a Collection, which is then finished by
+        // this Thread, without starting another one
+        {
+            let allocator = allocator.clone();
+            std::thread::spawn(move || {
+                // Synchronizes the Phase of the new Thread first
+                allocator.restart(&mut []);
+                allocator.init_reclaimation();
+            })
+            .join()
+            .unwrap();
+        }
+        let phase = allocator.phase_index.load(atomic::Ordering::Acquire);
+        allocator.collect_blocking();
+        assert_eq!(phase, allocator.phase_index.load(atomic::Ordering::Acquire));
+        assert_eq!(
+            phase,
+            allocator
+                .get_local()
+                .phase_index
+                .load(atomic::Ordering::Acquire)
+        );
+        assert_eq!(1, allocator.collect_stats().live_nodes);
+    }
+
+    #[test]
+    fn help_after_phase_change() {
+        let allocator = Allocator::new(NoGlobals);
+        let local = allocator.get_local();
+
+        let node = allocator.allocate(TestNode::new());
+        local.mark_stack.push(0x12 as *mut TestNode);
+        local.mark_stack.push(0x23 as *mut TestNode);
+
+        let phase = allocator.phase_index.load(atomic::Ordering::Acquire);
+        allocator
+            .phase_index
+            .store(phase + 1, atomic::Ordering::Release);
+
+        allocator.help(local, node.ptr());
+
+        assert_eq!(phase + 1, local.phase_index.load(atomic::Ordering::Acquire));
+        assert_eq!(
+            vec![node.ptr()],
+            local.mark_stack.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn box_deref() {
+        let allocator = Allocator::new(NoGlobals);
+
+        let mut node = allocator.allocate(TestNode::new());
+        assert!(node.next.load(atomic::Ordering::Acquire).is_null());
+
+        node.next = atomic::AtomicPtr::new(0x12 as *mut TestNode);
+        assert_eq!(0x12 as *mut TestNode, node.pointers()[0]);
+    }
+
+    #[test]
+    fn collect_stats() {
+        let root = std::sync::Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let allocator = Allocator::<TestNode, _>::builder(SingleGlobal(root.clone()))
+            .page_size(8)
+            .initial_pages(2)
+            .build();
+
+        assert_eq!(2, allocator.page_count());
+        assert_eq!(16, allocator.node_capacity());
+
+        let stats = allocator.collect_stats();
+        assert_eq!(2, stats.page_count);
+        assert_eq!(16, stats.total_nodes);
+        assert_eq!(0, stats.overflow_nodes);
+        assert_eq!(0, stats.mark_stack_depth);
+
+        let first = allocator.allocate(TestNode::new());
+        let second = allocator.allocate(TestNode::new());
+        first.next.store(second.ptr(), atomic::Ordering::Release);
+        root.store(first.ptr(), atomic::Ordering::Release);
+        let _unreachable = allocator.allocate(TestNode::new());
+
+        allocator.force_gc();
+
+        let stats = allocator.collect_stats();
+        assert_eq!(
+            allocator.phase_index.load(atomic::Ordering::Acquire),
+            stats.phase_index
+        );
+        assert_eq!(2, stats.live_nodes);
+        assert_eq!(14, stats.free_nodes);
+        assert_eq!(0, stats.mark_stack_depth);
+        assert_eq!(0, stats.max_mark_stack_depth);
+
+        let mark_stack = &allocator.get_local().mark_stack;
+        mark_stack.push(first.ptr());
+        mark_stack.push(second.ptr());
+        let stats = allocator.collect_stats();
+        assert_eq!(2, stats.mark_stack_depth);
+        assert_eq!(2, stats.max_mark_stack_depth);
+        mark_stack.clear();
+    }
+
+    #[test]
+    fn builder_initial_pages() {
+        let allocator: Allocator<TestNode, _> = Allocator::builder(NoGlobals)
+            .page_size(8)
+            .initial_pages(4)
+            .build();
+
+        assert_eq!(4, allocator.pages.page_count());
+
+        // Every Node of the initial Pages was swept and can be allocated
+        // without growing the Heap
+        let mut ptrs: Vec<_> = (0..32)
+            .map(|_| allocator.allocate(TestNode::new()).ptr())
+            .collect();
+        assert_eq!(4, allocator.pages.page_count());
+
+        ptrs.sort();
+        ptrs.dedup();
+        assert_eq!(32, ptrs.len());
+    }
 }
@@ -1,47 +1,169 @@
-use std::sync::atomic;
+use crate::sync::atomic;
 
 use crate::{
     allocator::{NodeMarks, Page},
     DataStructureNode,
 };
 
-use super::{allocator, markstack, Arbiter, HazardPtrFrame, Udirty};
+use super::{allocator, dirty::Udirty, markstack, Arbiter, HazardPtrFrame};
 
 pub struct Local<T> {
+    /// The Thread that created this Local, which is not necessarily the one
+    /// using it right now, as the Local of an exited Thread is reused by the
+    /// next new Thread. It still uniquely identifies this Local
     pub thread_id: std::thread::ThreadId,
+    /// Whether the Local is currently used by a registered Thread, this is
+    /// always set if the Threads don't need to register explicitly
+    pub registered: atomic::AtomicBool,
     pub phase_index: atomic::AtomicU64,
     pub dirty: Udirty,
     pub hazard_ptr_frames: [HazardPtrFrame<T>; 2],
+    /// The Ptrs pinned using [`Allocator::pin`](crate::Allocator::pin), which
+    /// unlike the other Frames is not cleared when restarting an Operation
+    pub pinned: HazardPtrFrame<T>,
+    /// The Ptrs protected using
+    /// [`Allocator::protect`](crate::Allocator::protect), which are kept
+    /// separate from the other Frames, so restarting an Operation does not
+    /// release the Protection of a Guard that is still alive
+    pub guarded: HazardPtrFrame<T>,
     // Either 0 or 1
     pub(crate) arbiter: Arbiter,
     pub alloc: allocator::LocalAllocator<T>,
+    /// The Node that is currently being allocated by this Thread, which is
+    /// marked by every Collection so it can not be freed again before the
+    /// Data was written into it
+    pub allocating: atomic::AtomicPtr<T>,
+    /// Whether the Thread is currently inside of
+    /// [`Allocator::with_globals_mut`](crate::Allocator::with_globals_mut),
+    /// where it must not allocate or collect
+    #[cfg(debug_assertions)]
+    pub in_globals_mut: atomic::AtomicBool,
 
     // Marking stuff
     pub cur_traced: atomic::AtomicPtr<T>,
     pub mark_stack: markstack::MarkStack<T>,
+    /// The Buffer the Ptrs of the currently traced Node are loaded into, which
+    /// is reused for every Node traced by this Thread
+    pub children: ChildBuffer<T>,
 }
 
-impl<T> Default for Local<T> {
-    fn default() -> Self {
+/// A Buffer that is only ever accessed by the Thread owning the Local it
+/// belongs to
+pub struct ChildBuffer<T> {
+    buffer: core::cell::UnsafeCell<Vec<*mut T>>,
+}
+
+// Safety:
+// The Buffer only contains Ptrs, which are never dereferenced through it, and
+// it is only ever accessed by the owning Thread, see `ChildBuffer::with`
+unsafe impl<T> Send for ChildBuffer<T> {}
+unsafe impl<T> Sync for ChildBuffer<T> {}
+
+impl<T> ChildBuffer<T> {
+    pub fn new() -> Self {
+        Self {
+            buffer: core::cell::UnsafeCell::new(Vec::new()),
+        }
+    }
+
+    /// Calls the given Function with the Buffer, which is always empty when
+    /// passed to it
+    ///
+    /// # Safety
+    /// Must only be called by the Thread owning the Local and never from
+    /// within the given Function
+    pub unsafe fn with<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Vec<*mut T>) -> R,
+    {
+        let buffer = &mut *self.buffer.get();
+        // The Function may have unwound the last time, without emptying the
+        // Buffer
+        buffer.clear();
+        let result = f(buffer);
+        buffer.clear();
+        result
+    }
+}
+
+impl<T> Local<T> {
+    /// Creates the Local-State for the current Thread
+    pub fn new(buffer_size: usize, registered: bool) -> Self {
         Self {
             thread_id: std::thread::current().id(),
+            registered: atomic::AtomicBool::new(registered),
             phase_index: atomic::AtomicU64::new(0),
             dirty: Udirty::new(),
             hazard_ptr_frames: [HazardPtrFrame::new(), HazardPtrFrame::new()],
+            pinned: HazardPtrFrame::new(),
+            guarded: HazardPtrFrame::new(),
             arbiter: Arbiter::new(),
-            alloc: allocator::LocalAllocator::new(),
+            alloc: allocator::LocalAllocator::new(buffer_size),
+            allocating: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            #[cfg(debug_assertions)]
+            in_globals_mut: atomic::AtomicBool::new(false),
             cur_traced: atomic::AtomicPtr::new(std::ptr::null_mut()),
             mark_stack: markstack::MarkStack::new(),
+            children: ChildBuffer::new(),
         }
     }
 }
 
+#[cfg(debug_assertions)]
+impl<T> Local<T> {
+    /// Marks the Thread as being inside of `with_globals_mut`, until the
+    /// returned Guard is dropped, which also happens if the Thread unwinds
+    pub fn enter_globals_mut(&self) -> GlobalsMutGuard<'_, T> {
+        self.in_globals_mut.store(true, atomic::Ordering::Relaxed);
+        GlobalsMutGuard { local: self }
+    }
+
+    /// Panics if the Thread is inside of `with_globals_mut`, where the given
+    /// Operation would deadlock once it starts a Collection
+    pub fn assert_outside_globals_mut(&self, operation: &str) {
+        assert!(
+            !self.in_globals_mut.load(atomic::Ordering::Relaxed),
+            "{} inside of `with_globals_mut`, which deadlocks once a Collection needs the Globals",
+            operation
+        );
+    }
+}
+
+/// See [`Local::enter_globals_mut`]
+#[cfg(debug_assertions)]
+pub struct GlobalsMutGuard<'a, T> {
+    local: &'a Local<T>,
+}
+
+#[cfg(debug_assertions)]
+impl<'a, T> Drop for GlobalsMutGuard<'a, T> {
+    fn drop(&mut self) {
+        self.local
+            .in_globals_mut
+            .store(false, atomic::Ordering::Relaxed);
+    }
+}
+
 #[must_use]
 pub enum MarkNodeState {
     Done,
+    /// The Node was marked by this Thread, but there may be more to do
+    Marked,
     NotDone,
 }
 
+impl<T> Local<T> {
+    /// Moves this Thread into the given Phase, which also drops all the
+    /// Entries on the MarkStack, as they belong to the older Phase and must
+    /// not be marked in the new one
+    pub fn reset_phase(&self, n_phase: u64) {
+        self.mark_stack.clear();
+        debug_assert!(self.mark_stack.is_empty());
+
+        self.phase_index.store(n_phase, atomic::Ordering::Release);
+    }
+}
+
 impl<T> Local<T>
 where
     T: DataStructureNode,
@@ -52,6 +174,10 @@ where
             Some(o) => o,
             None => {
                 tracing::debug!("Marking Done");
+                // Nothing is being traced anymore, otherwise the last traced
+                // Node would be treated as a root by the next Phase
+                self.cur_traced
+                    .store(std::ptr::null_mut(), atomic::Ordering::Release);
                 return MarkNodeState::Done;
             }
         };
@@ -60,6 +186,9 @@ where
 
         let obj_node = unsafe { allocator::PageNode::from_data_ptr(obj_ptr) };
         let marks = obj_node.load_marks();
+        // Nodes are pushed again for every Ptr to them, which includes the
+        // Cycles in the Graph, but they are only ever expanded once, so the
+        // MarkStack stays bounded by the Number of Ptrs
         if marks.marked || marks.phase != local_phase {
             tracing::debug!("Already marked or wrong phase: {:?}", marks);
 
@@ -70,16 +199,31 @@ where
         self.cur_traced.store(obj_ptr, atomic::Ordering::Release);
         let _ = self.mark_stack.pop();
 
-        let mut pushed_children = 0;
         let obj = unsafe { &*obj_ptr };
-        let child_ptrs = obj.pointers();
-        for c_ptr in child_ptrs {
-            if c_ptr.is_null() {
-                continue;
+        let pushed_children = if obj.is_leaf() {
+            0
+        } else {
+            // Safety:
+            // Only the Thread owning this Local ever marks using it
+            unsafe {
+                self.children.with(|children| {
+                    obj.pointers_into(children);
+                    debug_assert!(
+                        children.len() <= T::pointer_count(),
+                        "The Node reported {} Ptrs, but its pointer_count is only {}",
+                        children.len(),
+                        T::pointer_count()
+                    );
+
+                    let mut pushed = 0;
+                    for c_ptr in children.iter().filter(|c| !c.is_null()) {
+                        self.mark_stack.push(*c_ptr);
+                        pushed += 1;
+                    }
+                    pushed
+                })
             }
-            self.mark_stack.push(c_ptr);
-            pushed_children += 1;
-        }
+        };
 
         let expected_marks = NodeMarks {
             phase: local_phase,
@@ -90,7 +234,7 @@ where
             marked: true,
         };
         match obj_node.update_marks(expected_marks, new_marks) {
-            Ok(_) => MarkNodeState::NotDone,
+            Ok(_) => MarkNodeState::Marked,
             Err(_) => {
                 for _ in 0..pushed_children {
                     let _ = self.mark_stack.pop();
@@ -100,28 +244,72 @@ where
         }
     }
 
-    #[tracing::instrument(skip(self, page, global_alloc))]
-    pub fn sweep_page(&self, page: &Page<T>, global_alloc: &allocator::GlobalAllocPool<T>) {
+    /// Frees all the unmarked Nodes of the given Page, `on_reclaim` is called
+    /// for every Node that is freed, together with whether or not the Node
+    /// still held Data. The Data of such Nodes is dropped right afterwards,
+    /// before the Node is handed to the Allocator again.
+    ///
+    /// # Returns
+    /// The Number of Nodes that were freed
+    #[tracing::instrument(skip(self, page, global_alloc, on_reclaim))]
+    pub fn sweep_page<F>(
+        &self,
+        page: &Page<T>,
+        global_alloc: &allocator::GlobalAllocPool<T>,
+        mut on_reclaim: F,
+    ) -> usize
+    where
+        F: FnMut(*mut T, bool),
+    {
         let local_phase = self.phase_index.load(atomic::Ordering::Acquire);
 
         tracing::debug!(local_phase, "Sweeping Page");
 
+        let mut freed = 0;
         for node in page.nodes.iter() {
-            let marks = node.load_marks();
-            if marks.marked {
-                continue;
-            }
+            // Only one Thread can reclaim a Node, even if the Page is swept by
+            // multiple Threads
+            let data_ptr = match node.try_reclaim(local_phase) {
+                Some(ptr) => ptr,
+                None => continue,
+            };
 
-            let data_ptr = unsafe { node.get_data_ptr() };
-            match self.alloc.insert(data_ptr) {
-                Ok(_) => {}
-                Err(data_ptr) => {
-                    let old = self.alloc.take();
-                    let _ = global_alloc.insert(local_phase, old);
+            // Free Nodes are reclaimed again in every Phase, but their Data
+            // was already dropped or never written
+            let occupied = node.take_occupied();
+            on_reclaim(data_ptr, occupied);
+            if occupied {
+                unsafe { core::ptr::drop_in_place(data_ptr) };
+            }
 
-                    self.alloc.insert(data_ptr).expect("");
-                }
-            };
+            self.free_node(data_ptr, local_phase, global_alloc);
+            freed += 1;
         }
+
+        freed
+    }
+
+    /// Inserts the given Node into the Local-Allocator, so that it can be
+    /// reused by the next Allocation. If the Local-Allocator is full, its
+    /// current Buffer is moved into the Global-Allocation-Pool first and if
+    /// the current Buffer belongs to an older Phase, it is dropped
+    pub fn free_node(
+        &self,
+        data_ptr: *mut T,
+        local_phase: u64,
+        global_alloc: &allocator::GlobalAllocPool<T>,
+    ) {
+        // The Nodes in an older Buffer are freed again by this Sweep
+        self.alloc.discard_outdated(local_phase);
+
+        match self.alloc.insert(data_ptr) {
+            Ok(_) => {}
+            Err(data_ptr) => {
+                let old = self.alloc.take();
+                let _ = global_alloc.insert(local_phase, old);
+
+                self.alloc.insert(data_ptr).expect("");
+            }
+        };
     }
 }
@@ -8,9 +8,35 @@
 //! # Memory-Managment
 //! The individual Nodes will never be freed/reclaimed and only be reused, this
 //! allows us to not worry about whether or not the currently visited Note is
-//! still allocated/alive
+//! still allocated/alive.
+//! Only once the MarkStack itself is dropped, all of its Nodes are freed.
+//!
+//! # Ordering-Contract
+//! Only the owning Thread ever calls `push` and `pop`, all other Threads only
+//! use `iter` (and `peek`) to find Nodes they could help with.
+//!
+//! The owner treats the MarkStack as a normal LIFO-Stack, `pop` always
+//! returns the most recently pushed Entry that is still on the Stack. To make
+//! this possible the Entries are always stored contiguously, starting at the
+//! first StackNode up to (and including) `head`, and all the StackNodes after
+//! `head` are empty. `iter` walks the Entries from the oldest to the newest
+//! one and skips over any empty StackNode it encounters, instead of stopping
+//! there, so even if a hole were to appear in the middle of the Stack, the
+//! Entries after it are still visible to the helping Threads.
+//!
+//! ## Visibility
+//! Every Ptr that was pushed stays visible to a concurrent `iter` until the
+//! owner pops it. The owner in turn only pops an Entry after publishing it in
+//! its `cur_traced` field and only marks it afterwards, so a helper that
+//! checks both the MarkStack and `cur_traced` will always see every Ptr that
+//! has not been marked yet.
+//! A helper may still observe an Entry that the owner has marked in the
+//! meantime, which is why a helper always needs to check the Marks of a Node
+//! before acting on it.
 
-use std::sync::atomic;
+use alloc::boxed::Box;
+
+use crate::sync::atomic;
 
 struct StackNode<T> {
     data: atomic::AtomicPtr<T>,
@@ -23,20 +49,31 @@ impl<T> StackNode<T> {
         Self {
             data: atomic::AtomicPtr::new(data),
             previous,
-            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            next: atomic::AtomicPtr::new(core::ptr::null_mut()),
         }
     }
 
     pub fn empty() -> Self {
-        Self::new(std::ptr::null_mut(), std::ptr::null_mut())
+        Self::new(core::ptr::null_mut(), core::ptr::null_mut())
     }
 }
 
+/// The Stack of Ptrs that still need to be marked by a single Thread.
+///
+/// Only the owning Thread pushes and pops Entries, all other Threads only
+/// iterate over them to help with the Marking
 pub struct MarkStack<T> {
     head: atomic::AtomicPtr<StackNode<T>>,
 }
 
+impl<T> Default for MarkStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> MarkStack<T> {
+    /// Creates a new empty MarkStack
     pub fn new() -> Self {
         let initial_ptr = Box::into_raw(Box::new(StackNode::empty()));
 
@@ -45,19 +82,37 @@ impl<T> MarkStack<T> {
         }
     }
 
+    /// Pushes the given Ptr onto the Stack, this should only be called by the
+    /// owning Thread.
+    ///
+    /// Null-Ptrs are skipped, as an empty StackNode is marked by storing a
+    /// Null-Ptr and they could therefore never be popped again
     pub fn push(&self, data: *mut T) {
+        if data.is_null() {
+            return;
+        }
+
         let head_ptr = self.head.load(atomic::Ordering::Acquire);
         let mut current = unsafe { &*head_ptr };
 
         loop {
             if current.data.load(atomic::Ordering::Acquire).is_null() {
                 match current.data.compare_exchange(
-                    std::ptr::null_mut(),
+                    core::ptr::null_mut(),
                     data,
                     atomic::Ordering::SeqCst,
                     atomic::Ordering::SeqCst,
                 ) {
-                    Ok(_) => return,
+                    Ok(_) => {
+                        // Move the Head forward to keep the Entries
+                        // contiguous, otherwise the next `pop` would not
+                        // return this Entry and leave a hole behind
+                        let current_ptr = current as *const StackNode<T> as *mut StackNode<T>;
+                        if current_ptr != head_ptr {
+                            self.head.store(current_ptr, atomic::Ordering::Release);
+                        }
+                        return;
+                    }
                     Err(_) => {}
                 };
             }
@@ -76,7 +131,7 @@ impl<T> MarkStack<T> {
 
         loop {
             match current.next.compare_exchange(
-                std::ptr::null_mut(),
+                core::ptr::null_mut(),
                 next_node_ptr,
                 atomic::Ordering::SeqCst,
                 atomic::Ordering::SeqCst,
@@ -93,6 +148,8 @@ impl<T> MarkStack<T> {
         }
     }
 
+    /// Pops the most recently pushed Entry from the Stack, this should only be
+    /// called by the owning Thread
     pub fn pop(&self) -> Option<*mut T> {
         let head_ptr = self.head.load(atomic::Ordering::Acquire);
         let mut current = unsafe { &*head_ptr };
@@ -102,7 +159,7 @@ impl<T> MarkStack<T> {
             if !data_ptr.is_null() {
                 match current.data.compare_exchange(
                     data_ptr,
-                    std::ptr::null_mut(),
+                    core::ptr::null_mut(),
                     atomic::Ordering::SeqCst,
                     atomic::Ordering::SeqCst,
                 ) {
@@ -126,6 +183,8 @@ impl<T> MarkStack<T> {
         }
     }
 
+    /// Returns the most recently pushed Entry, without removing it from the
+    /// Stack
     pub fn peek(&self) -> Option<*mut T> {
         let head_ptr = self.head.load(atomic::Ordering::Acquire);
         let mut current = unsafe { &*head_ptr };
@@ -144,6 +203,79 @@ impl<T> MarkStack<T> {
         }
     }
 
+    /// Pushes all the given Ptrs onto the Stack, as if they were pushed one
+    /// after the other, this should only be called by the owning Thread.
+    ///
+    /// Unlike calling `push` for every Ptr, the Chain is only walked once and
+    /// the Head is only moved a single Time after all the Entries were
+    /// stored. Null-Ptrs are skipped, as they can not be stored as Entries.
+    ///
+    /// # Concurrency
+    /// Every Entry is visible to a concurrent `iter` as soon as it is stored,
+    /// just like with `push`. Appending a StackNode uses the same CAS on
+    /// `next` as `push`, so if another StackNode was appended in the
+    /// meantime, our own one is freed again and the other one is used instead
+    pub fn push_batch(&self, data: &[*mut T]) {
+        let head_ptr = self.head.load(atomic::Ordering::Acquire);
+        let mut current_ptr = head_ptr;
+        let mut last_ptr = head_ptr;
+
+        for ptr in data.iter().copied().filter(|p| !p.is_null()) {
+            loop {
+                let current = unsafe { &*current_ptr };
+                if current.data.load(atomic::Ordering::Acquire).is_null()
+                    && current
+                        .data
+                        .compare_exchange(
+                            core::ptr::null_mut(),
+                            ptr,
+                            atomic::Ordering::SeqCst,
+                            atomic::Ordering::SeqCst,
+                        )
+                        .is_ok()
+                {
+                    last_ptr = current_ptr;
+                    break;
+                }
+
+                current_ptr = Self::next_or_append(current_ptr);
+            }
+        }
+
+        // Move the Head forward to the newest Entry, to keep the Entries
+        // contiguous for the next `pop`
+        if last_ptr != head_ptr {
+            self.head.store(last_ptr, atomic::Ordering::Release);
+        }
+    }
+
+    /// Returns the StackNode following the given one, appending a new empty
+    /// StackNode if there is none yet
+    fn next_or_append(current_ptr: *mut StackNode<T>) -> *mut StackNode<T> {
+        let current = unsafe { &*current_ptr };
+
+        let next = current.next.load(atomic::Ordering::Acquire);
+        if !next.is_null() {
+            return next;
+        }
+
+        let next_node_ptr =
+            Box::into_raw(Box::new(StackNode::new(current_ptr, core::ptr::null_mut())));
+        match current.next.compare_exchange(
+            core::ptr::null_mut(),
+            next_node_ptr,
+            atomic::Ordering::SeqCst,
+            atomic::Ordering::SeqCst,
+        ) {
+            Ok(_) => next_node_ptr,
+            Err(next) => {
+                drop(unsafe { Box::from_raw(next_node_ptr) });
+                next
+            }
+        }
+    }
+
+    /// Whether or not the Stack currently contains no Entries
     pub fn is_empty(&self) -> bool {
         let head_ptr = self.head.load(atomic::Ordering::Acquire);
         let mut current = unsafe { &*head_ptr };
@@ -163,6 +295,51 @@ impl<T> MarkStack<T> {
         }
     }
 
+    /// The Number of Entries currently on the Stack, this can be used by any
+    /// Thread.
+    ///
+    /// The StackNodes are walked from the first one and every StackNode
+    /// holding an Entry is counted, so with concurrent Pushes or Pops this is
+    /// only a racy Snapshot
+    pub fn depth(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Removes all the Entries from the Stack at once, this should only be
+    /// called by the owning Thread.
+    ///
+    /// # Concurrency
+    /// The Entries are cleared from the oldest to the newest one, while the
+    /// StackNodes themselves stay allocated, so a concurrent `iter` or `peek`
+    /// only ever observes a shrinking Suffix of the previous Entries, the most
+    /// recently pushed ones, and never accesses freed Memory. Only once every
+    /// Entry is cleared, the Head is moved back to the first StackNode (with
+    /// Release-Ordering), so the Entries of the next `push` are again stored
+    /// contiguously.
+    pub fn clear(&self) {
+        let mut start_ptr = self.head.load(atomic::Ordering::Acquire);
+        loop {
+            let previous = unsafe { &*start_ptr }.previous;
+            if previous.is_null() {
+                break;
+            }
+            start_ptr = previous;
+        }
+
+        let mut current_ptr = start_ptr;
+        while !current_ptr.is_null() {
+            let current = unsafe { &*current_ptr };
+            current
+                .data
+                .store(core::ptr::null_mut(), atomic::Ordering::Release);
+            current_ptr = current.next.load(atomic::Ordering::Acquire);
+        }
+
+        self.head.store(start_ptr, atomic::Ordering::Release);
+    }
+
+    /// Creates an Iterator over all the Entries currently on the Stack, from
+    /// the oldest to the newest one. This can be used by any Thread
     pub fn iter(&self) -> MarkStackIter<T> {
         let mut current = unsafe { &*self.head.load(atomic::Ordering::Acquire) };
         loop {
@@ -179,6 +356,26 @@ impl<T> MarkStack<T> {
     }
 }
 
+impl<T> Drop for MarkStack<T> {
+    fn drop(&mut self) {
+        // The Head may be anywhere in the Chain, so first find the first Node
+        let mut start_ptr = self.head.load(atomic::Ordering::Acquire);
+        loop {
+            let previous = unsafe { &*start_ptr }.previous;
+            if previous.is_null() {
+                break;
+            }
+            start_ptr = previous;
+        }
+
+        let mut current_ptr = start_ptr;
+        while !current_ptr.is_null() {
+            let current = unsafe { Box::from_raw(current_ptr) };
+            current_ptr = current.next.load(atomic::Ordering::Acquire);
+        }
+    }
+}
+
 pub struct MarkStackIter<T> {
     current: *mut StackNode<T>,
 }
@@ -187,20 +384,19 @@ impl<T> Iterator for MarkStackIter<T> {
     type Item = *mut T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current.is_null() {
-            return None;
-        }
+        while !self.current.is_null() {
+            let current = unsafe { &*self.current };
+            let data = current.data.load(atomic::Ordering::Acquire);
 
-        let current = unsafe { &*self.current };
-        let data = current.data.load(atomic::Ordering::Acquire);
-        if data.is_null() {
-            return None;
-        }
+            let next = current.next.load(atomic::Ordering::Acquire);
+            self.current = next;
 
-        let next = current.next.load(atomic::Ordering::Acquire);
-        self.current = next;
+            if !data.is_null() {
+                return Some(data);
+            }
+        }
 
-        Some(data)
+        None
     }
 }
 
@@ -214,6 +410,21 @@ mod tests {
         drop(stack);
     }
 
+    #[test]
+    fn drop_long_chain() {
+        let stack = MarkStack::<usize>::new();
+
+        for tmp in 1..10_000 {
+            stack.push(tmp as *mut usize);
+        }
+        // Move the Head back into the middle of the Chain
+        for _ in 0..5_000 {
+            stack.pop().unwrap();
+        }
+
+        drop(stack);
+    }
+
     #[test]
     fn is_empty() {
         let stack = MarkStack::<usize>::new();
@@ -227,6 +438,45 @@ mod tests {
         assert_eq!(true, stack.is_empty());
     }
 
+    #[test]
+    fn clear() {
+        let stack = MarkStack::<usize>::new();
+
+        for tmp in 1..100 {
+            stack.push(tmp as *mut usize);
+        }
+        stack.clear();
+
+        assert_eq!(true, stack.is_empty());
+        assert_eq!(None, stack.peek());
+        assert_eq!(0, stack.iter().count());
+
+        // The cleared StackNodes are reused in the right Order
+        stack.push(0x12 as *mut usize);
+        stack.push(0x23 as *mut usize);
+        let entries: Vec<_> = stack.iter().collect();
+        assert_eq!(vec![0x12 as *mut usize, 0x23 as *mut usize], entries);
+        assert_eq!(Some(0x23 as *mut usize), stack.pop());
+    }
+
+    #[test]
+    fn depth() {
+        let stack = MarkStack::<usize>::new();
+        assert_eq!(0, stack.depth());
+
+        for tmp in 1..=10 {
+            stack.push(tmp as *mut usize);
+        }
+        assert_eq!(10, stack.depth());
+
+        stack.pop().unwrap();
+        stack.pop().unwrap();
+        assert_eq!(8, stack.depth());
+
+        stack.clear();
+        assert_eq!(0, stack.depth());
+    }
+
     #[test]
     fn pop_empty() {
         let stack = MarkStack::<usize>::new();
@@ -249,9 +499,89 @@ mod tests {
         for tmp in 0..10 {
             stack.push(tmp as *mut usize);
         }
-        for tmp in 10..0 {
+
+        // The Null-Ptr pushed first is skipped, everything else is popped in
+        // the reverse Order it was pushed in
+        for tmp in (1..10).rev() {
             assert_eq!(Some(tmp as *mut usize), stack.pop());
         }
+        assert_eq!(None, stack.pop());
+        assert_eq!(true, stack.is_empty());
+    }
+
+    #[test]
+    fn push_batch() {
+        let stack = MarkStack::<usize>::new();
+
+        stack.push(0x10 as *mut usize);
+        let batch: Vec<_> = (1..50).map(|i| (i * 0x10 + 0x10) as *mut usize).collect();
+        stack.push_batch(&batch);
+        stack.push(0x1000 as *mut usize);
+
+        let mut expected = vec![0x10 as *mut usize];
+        expected.extend(batch.iter().copied());
+        expected.push(0x1000 as *mut usize);
+        assert_eq!(expected, stack.iter().collect::<Vec<_>>());
+
+        // Popped in the reverse Order, like with individual Pushes
+        for ptr in expected.into_iter().rev() {
+            assert_eq!(Some(ptr), stack.pop());
+        }
+        assert_eq!(None, stack.pop());
+    }
+
+    #[test]
+    fn push_batch_reuses_nodes() {
+        let stack = MarkStack::<usize>::new();
+
+        for tmp in 1..10 {
+            stack.push(tmp as *mut usize);
+        }
+        stack.clear();
+
+        stack.push_batch(&[
+            0x12 as *mut usize,
+            core::ptr::null_mut(),
+            0x23 as *mut usize,
+        ]);
+        assert_eq!(
+            vec![0x12 as *mut usize, 0x23 as *mut usize],
+            stack.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(0x23 as *mut usize), stack.peek());
+
+        // An empty Batch changes nothing
+        stack.push_batch(&[]);
+        assert_eq!(Some(0x23 as *mut usize), stack.pop());
+        assert_eq!(Some(0x12 as *mut usize), stack.pop());
+        assert_eq!(None, stack.pop());
+    }
+
+    #[test]
+    fn push_batch_concurrent_iter() {
+        let stack = std::sync::Arc::new(MarkStack::<usize>::new());
+        let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        let reader = {
+            let stack = stack.clone();
+            let done = done.clone();
+            std::thread::spawn(move || {
+                while !done.load(std::sync::atomic::Ordering::Acquire) {
+                    // The Entries are only ever added in increasing Order
+                    let entries: Vec<_> = stack.iter().map(|p| p as usize).collect();
+                    assert!(entries.windows(2).all(|w| w[0] < w[1]));
+                }
+            })
+        };
+
+        for round in 0..100 {
+            let batch: Vec<_> = (1..=8).map(|i| (round * 8 + i) as *mut usize).collect();
+            stack.push_batch(&batch);
+        }
+        done.store(true, std::sync::atomic::Ordering::Release);
+        reader.join().unwrap();
+
+        assert_eq!(800, stack.iter().count());
     }
 
     #[test]
@@ -281,4 +611,152 @@ mod tests {
         assert_eq!(Some(0x23 as *mut usize), iter.next());
         assert_eq!(None, iter.next());
     }
+
+    #[test]
+    fn iterator_skips_holes() {
+        let stack = MarkStack::<usize>::new();
+
+        stack.push(0x12 as *mut usize);
+        stack.push(0x23 as *mut usize);
+        stack.push(0x34 as *mut usize);
+
+        // Remove the middle Entry
+        let head = unsafe { &*stack.head.load(atomic::Ordering::Acquire) };
+        let middle = unsafe { &*head.previous };
+        middle
+            .data
+            .store(std::ptr::null_mut(), atomic::Ordering::Release);
+
+        let entries: Vec<_> = stack.iter().collect();
+        assert_eq!(vec![0x12 as *mut usize, 0x34 as *mut usize], entries);
+    }
+
+    #[test]
+    fn push_after_pop_stays_contiguous() {
+        let stack = MarkStack::<usize>::new();
+
+        stack.push(0x12 as *mut usize);
+        stack.push(0x23 as *mut usize);
+        assert_eq!(Some(0x23 as *mut usize), stack.pop());
+        assert_eq!(Some(0x12 as *mut usize), stack.pop());
+
+        stack.push(0x34 as *mut usize);
+        stack.push(0x45 as *mut usize);
+
+        let entries: Vec<_> = stack.iter().collect();
+        assert_eq!(vec![0x34 as *mut usize, 0x45 as *mut usize], entries);
+
+        assert_eq!(Some(0x45 as *mut usize), stack.pop());
+        assert_eq!(Some(0x34 as *mut usize), stack.pop());
+        assert_eq!(None, stack.pop());
+    }
+
+    #[test]
+    fn concurrent_iter_pop() {
+        use std::sync::{atomic::AtomicBool, Arc};
+
+        const NODES: usize = 512;
+
+        // The "Marks" for every Node, a Ptr on the Stack points to its Mark
+        let marks: &'static [AtomicBool] = Box::leak(
+            (0..NODES)
+                .map(|_| AtomicBool::new(false))
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+        );
+
+        let stack = Arc::new(MarkStack::<AtomicBool>::new());
+        let cur_traced = Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+        let done = Arc::new(AtomicBool::new(false));
+
+        for mark in marks.iter() {
+            stack.push(mark as *const AtomicBool as *mut AtomicBool);
+        }
+
+        let helper = {
+            let stack = stack.clone();
+            let cur_traced = cur_traced.clone();
+            let done = done.clone();
+            std::thread::spawn(move || {
+                while !done.load(atomic::Ordering::SeqCst) {
+                    let unmarked: Vec<usize> = (0..NODES)
+                        .filter(|i| !marks[*i].load(atomic::Ordering::SeqCst))
+                        .collect();
+
+                    let mut seen = Vec::new();
+                    for entry in stack.iter() {
+                        let entry_mark = unsafe { &*entry };
+                        // A Helper would only ever act on an unmarked Node
+                        if !entry_mark.load(atomic::Ordering::SeqCst) {
+                            seen.push(entry as usize);
+                        }
+                    }
+                    let traced = cur_traced.load(atomic::Ordering::SeqCst) as usize;
+
+                    for index in unmarked {
+                        let mark = &marks[index];
+                        let addr = mark as *const AtomicBool as usize;
+
+                        assert!(
+                            seen.contains(&addr)
+                                || traced == addr
+                                || mark.load(atomic::Ordering::SeqCst),
+                            "Missed unmarked Node {}",
+                            index
+                        );
+                    }
+                }
+            })
+        };
+
+        // The owner processes its Stack the same way `mark_node` does
+        while let Some(entry) = stack.peek() {
+            cur_traced.store(entry, atomic::Ordering::SeqCst);
+            assert_eq!(Some(entry), stack.pop());
+            unsafe { &*entry }.store(true, atomic::Ordering::SeqCst);
+        }
+        done.store(true, atomic::Ordering::SeqCst);
+
+        helper.join().unwrap();
+
+        assert!(marks.iter().all(|m| m.load(atomic::Ordering::SeqCst)));
+    }
+}
+
+#[cfg(all(test, feature = "loom"))]
+mod loom_tests {
+    use super::*;
+
+    use loom::sync::Arc;
+
+    #[test]
+    fn loom_push_pop_iter() {
+        loom::model(|| {
+            let stack = Arc::new(MarkStack::<usize>::new());
+
+            let helper = {
+                let stack = stack.clone();
+                loom::thread::spawn(move || {
+                    // A Helper only ever sees Entries that were actually
+                    // pushed, which may be any of them depending on the
+                    // Interleaving
+                    for entry in stack.iter() {
+                        let entry = entry as usize;
+                        assert!(entry == 0x12 || entry == 0x23 || entry == 0x34);
+                    }
+                    let _ = stack.peek();
+                })
+            };
+
+            stack.push(0x12 as *mut usize);
+            stack.push(0x23 as *mut usize);
+            assert_eq!(Some(0x23 as *mut usize), stack.pop());
+            stack.push(0x34 as *mut usize);
+            assert_eq!(Some(0x34 as *mut usize), stack.pop());
+            assert_eq!(Some(0x12 as *mut usize), stack.pop());
+            assert_eq!(None, stack.pop());
+
+            helper.join().unwrap();
+        });
+    }
 }
@@ -0,0 +1,26 @@
+//! Hooks to observe the Progress of the Collections
+
+/// Gets notified by the Allocator about the Progress of every Collection.
+///
+/// This allows building custom Instrumentation, like tracking the Pauses or
+/// logging the reclaimed Nodes, without relying on the `tracing` Output. All
+/// the Methods do nothing by default, so only the interesting ones need to be
+/// implemented.
+///
+/// # Note
+/// The Methods are called from within the Collection by whichever Thread is
+/// currently doing the Work, so they should return quickly and must not use
+/// the same Allocator themselves
+pub trait GcObserver: Send + Sync {
+    /// A new Collection was started, which moved the Allocator into the given
+    /// Phase
+    fn on_phase_start(&self, _phase: u64) {}
+
+    /// The Page at the given Index in the Heap is about to be swept
+    fn on_sweep_page(&self, _index: u64) {}
+
+    /// The Node with the given Data-Ptr was reclaimed and will be reused by
+    /// one of the next Allocations, for overflow Nodes this is called right
+    /// before their Data is dropped
+    fn on_reclaim(&self, _ptr: *const ()) {}
+}
@@ -0,0 +1,139 @@
+//! The Phase-Counter of the Collector
+//!
+//! # Range
+//! The Phase is stored as a `u64`, but it is also packed together with other
+//! Data in several Places:
+//! * The [`NodeMarks`](crate::NodeMarks) store it in their upper 61 Bits
+//! * The Dirty-Flags store it in their upper 56 Bits
+//! * The Sweep-Cursor of the PageList stores it in its upper 32 Bits
+//!
+//! The smallest of these is the Limit for the entire Collector, which is
+//! [`Phase::MAX`]. Starting a Collection beyond that Phase panics, as the
+//! packed Phases would otherwise wrap around and silently corrupt the Marks.
+//! Starting a new Phase every Millisecond, this Limit is reached after about
+//! 49 Days of continuous Collections.
+
+use crate::allocator::NodeMarks;
+
+/// A single Phase of the Collector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Phase(u64);
+
+impl Phase {
+    /// The largest Phase that can be stored in every packed Encoding
+    pub const MAX: u64 = u32::MAX as u64;
+
+    /// The raw Value of the Phase
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// The Phase following this one, unless that would exceed [`Phase::MAX`]
+    #[cfg(feature = "std")]
+    pub const fn checked_next(self) -> Option<Self> {
+        if self.0 >= Self::MAX {
+            return None;
+        }
+        Some(Self(self.0 + 1))
+    }
+
+    /// The Phase following this one
+    ///
+    /// # Panics
+    /// If this is already the last Phase, see [`Phase::MAX`]
+    #[cfg(feature = "std")]
+    pub fn next(self) -> Self {
+        match self.checked_next() {
+            Some(next) => next,
+            None => panic!(
+                "The Phase {} can not be incremented without exceeding the largest Phase {} and wrapping around, which is not supported",
+                self.0,
+                Self::MAX
+            ),
+        }
+    }
+
+    /// Whether or not the raw Phase fits into every packed Encoding
+    #[cfg(feature = "std")]
+    pub const fn fits(raw: u64) -> bool {
+        raw <= Self::MAX
+    }
+
+    /// Stores the Phase in the upper Bits of a packed Encoding, leaving the
+    /// lowest `shift` Bits empty for the other Data of the Encoding
+    pub fn pack(self, shift: u32) -> u64 {
+        debug_assert!(
+            self.0 <= u64::MAX >> shift,
+            "The Phase {} does not fit into the upper {} Bits",
+            self.0,
+            u64::BITS - shift
+        );
+        self.0 << shift
+    }
+
+    /// The inverse of [`pack`](Self::pack), which ignores the lowest `shift`
+    /// Bits of the Encoding
+    pub const fn unpack(raw: u64, shift: u32) -> Self {
+        Self(raw >> shift)
+    }
+}
+
+impl From<u64> for Phase {
+    fn from(raw: u64) -> Self {
+        Self(raw)
+    }
+}
+
+// Every Encoding has to be able to store every valid Phase
+const _: () = assert!(Phase::MAX <= NodeMarks::MAX_PHASE);
+#[cfg(feature = "std")]
+const _: () = assert!(Phase::MAX <= crate::dirty::DirtyValue::MAX_PHASE);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next() {
+        assert_eq!(Phase(1), Phase(0).next());
+        assert_eq!(Phase(Phase::MAX), Phase(Phase::MAX - 1).next());
+    }
+
+    #[test]
+    fn checked_next_at_max() {
+        assert_eq!(None, Phase(Phase::MAX).checked_next());
+        assert_eq!(None, Phase(u64::MAX).checked_next());
+    }
+
+    #[test]
+    #[should_panic(expected = "wrapping around")]
+    fn next_at_max() {
+        Phase(Phase::MAX).next();
+    }
+
+    #[test]
+    fn pack_unpack() {
+        for shift in [3, 8, 32] {
+            let packed = Phase(13).pack(shift);
+            assert_eq!(0, packed & ((1 << shift) - 1));
+            assert_eq!(Phase(13), Phase::unpack(packed | 1, shift));
+            assert_eq!(
+                Phase(Phase::MAX),
+                Phase::unpack(Phase(Phase::MAX).pack(shift), shift)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "does not fit")]
+    fn pack_too_large() {
+        Phase(Phase::MAX + 1).pack(32);
+    }
+
+    #[test]
+    fn fits() {
+        assert!(Phase::fits(Phase::MAX));
+        assert!(!Phase::fits(Phase::MAX + 1));
+    }
+}
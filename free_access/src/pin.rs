@@ -0,0 +1,72 @@
+//! Pinning single Nodes across Collections, see [`Allocator::pin`]
+
+use core::marker::PhantomData;
+
+use crate::{Allocator, HazardGuard, HazardPtrFrame};
+
+/// Keeps a Node alive for as long as the Guard exists, obtained using
+/// [`Allocator::pin`].
+///
+/// Once the Guard is dropped, the Node is unpinned and will be reclaimed by
+/// the next Collection, if it is not reachable otherwise
+pub struct PinGuard<'a, N, G> {
+    inner: HazardGuard<'a, N>,
+    _allocator: PhantomData<&'a Allocator<N, G>>,
+}
+
+impl<'a, N, G> PinGuard<'a, N, G> {
+    pub(crate) fn new(frame: &'a HazardPtrFrame<N>, ptr: *mut N) -> Self {
+        Self {
+            inner: HazardGuard::new(frame, ptr),
+            _allocator: PhantomData,
+        }
+    }
+
+    /// The Ptr pinned by this Guard
+    pub fn ptr(&self) -> *mut N {
+        self.inner.ptr()
+    }
+}
+
+impl<'a, N, G> core::ops::Deref for PinGuard<'a, N, G> {
+    type Target = N;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.ptr() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sync::atomic;
+    use crate::tests::{NoGlobals, TestNode};
+
+    #[test]
+    fn pin_across_collections() {
+        let allocator = Allocator::<TestNode, _>::builder(NoGlobals)
+            .page_size(8)
+            .build();
+
+        let child = allocator.allocate(TestNode::new());
+        let node = allocator.allocate(TestNode::new());
+        node.next.store(child.ptr(), atomic::Ordering::Release);
+        let guard = unsafe { allocator.pin(node.ptr()) };
+        assert_eq!(node.ptr(), guard.ptr());
+
+        allocator.force_gc();
+        // Restarting releases the Hazard-Ptrs, but not the pinned ones
+        allocator.restart(&mut []);
+        allocator.force_gc();
+
+        // The Children of a pinned Node are kept alive as well
+        assert_eq!(2, allocator.collect_stats().live_nodes);
+        assert_eq!(child.ptr(), guard.next.load(atomic::Ordering::Acquire));
+
+        drop(guard);
+        allocator.force_gc();
+        assert_eq!(0, allocator.collect_stats().live_nodes);
+        assert!(allocator.get_local().pinned.roots().is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+//! Policies to decide when the Allocator should start a Collection on its own
+
+use crate::PolicyStats;
+
+/// Decides whether the Allocator should start a new Collection.
+///
+/// The Policy is consulted whenever a Thread has used up its local
+/// Allocation-Buffer and needs to obtain a new one, before it takes its next
+/// Node. The Collection itself is run on the allocating Thread, just like the
+/// Collection that is started when the global Pool is empty.
+///
+/// # Note
+/// The Policy is called from within the Allocation, so it must not allocate
+/// from the same Allocator itself
+pub trait CollectionPolicy: Send + Sync {
+    /// Decides whether a Collection should be started, based on the given
+    /// Counters of the Allocator
+    fn should_collect(&self, stats: &PolicyStats) -> bool;
+}
+
+/// Never starts a Collection, so Collections only happen when the Allocator
+/// runs out of free Nodes or [`force_gc`](crate::Allocator::force_gc) is
+/// called. This is the default Policy
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NeverCollect;
+
+impl CollectionPolicy for NeverCollect {
+    fn should_collect(&self, _: &PolicyStats) -> bool {
+        false
+    }
+}
+
+/// Starts a Collection once the Number of free Nodes drops below the given
+/// Threshold.
+///
+/// The free Nodes are counted like in [`PolicyStats::pooled_nodes`], so only
+/// the Nodes in the global Pool are considered and not the ones still left
+/// in the local Buffers of the Threads
+#[derive(Debug, Clone, Copy)]
+pub struct ThresholdPolicy {
+    min_free_nodes: usize,
+}
+
+impl ThresholdPolicy {
+    /// Creates a new Policy that collects while there are less than
+    /// `min_free_nodes` free Nodes
+    pub fn new(min_free_nodes: usize) -> Self {
+        Self { min_free_nodes }
+    }
+}
+
+impl CollectionPolicy for ThresholdPolicy {
+    fn should_collect(&self, stats: &PolicyStats) -> bool {
+        stats.pooled_nodes() < self.min_free_nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(pooled_buffers: usize) -> PolicyStats {
+        PolicyStats {
+            phase_index: 0,
+            pooled_buffers,
+            buffer_size: 2,
+            allocated_nodes: 0,
+        }
+    }
+
+    #[test]
+    fn never_collect() {
+        assert!(!NeverCollect.should_collect(&stats(0)));
+        assert!(!NeverCollect.should_collect(&stats(8)));
+    }
+
+    #[test]
+    fn threshold() {
+        let policy = ThresholdPolicy::new(4);
+
+        assert!(policy.should_collect(&stats(1)));
+        assert!(!policy.should_collect(&stats(2)));
+        assert!(!policy.should_collect(&stats(8)));
+    }
+}
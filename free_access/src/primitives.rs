@@ -0,0 +1,9 @@
+//! The lock-free Building-Blocks the Allocator is made of.
+//!
+//! These only need `core` and `alloc`, so they are also available without
+//! the `std` Feature, for example to build a different Collector on top of
+//! them
+
+pub use crate::allocator::{AllocationBuffer, PageNode, Pool, PopError};
+pub use crate::hazard_ptrs::HazardPtr;
+pub use crate::markstack::MarkStack;
@@ -0,0 +1,147 @@
+//! The explicit Registration of the Threads using an Allocator, see
+//! [`Allocator::register`]
+
+use core::marker::PhantomData;
+
+use crate::{Allocator, DataStructureGlobals, DataStructureNode};
+
+/// Keeps the current Thread registered with the Allocator, obtained using
+/// [`Allocator::register`].
+///
+/// The Handle is bound to the Thread that registered itself, so it can not be
+/// sent to another Thread
+pub struct LocalHandle<'a, N, G>
+where
+    N: DataStructureNode,
+    G: DataStructureGlobals<N>,
+{
+    allocator: &'a Allocator<N, G>,
+    _thread: PhantomData<*mut ()>,
+}
+
+impl<'a, N, G> LocalHandle<'a, N, G>
+where
+    N: DataStructureNode,
+    G: DataStructureGlobals<N>,
+{
+    pub(crate) fn new(allocator: &'a Allocator<N, G>) -> Self {
+        Self {
+            allocator,
+            _thread: PhantomData,
+        }
+    }
+}
+
+impl<'a, N, G> Drop for LocalHandle<'a, N, G>
+where
+    N: DataStructureNode,
+    G: DataStructureGlobals<N>,
+{
+    fn drop(&mut self) {
+        self.allocator.unregister();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sync::atomic;
+    use crate::tests::{NoGlobals, TestNode};
+
+    fn explicit_allocator() -> Allocator<TestNode, NoGlobals> {
+        Allocator::builder(NoGlobals)
+            .page_size(8)
+            .explicit_registration(true)
+            .build()
+    }
+
+    #[test]
+    #[should_panic(expected = "registered")]
+    fn unregistered_use() {
+        let allocator = explicit_allocator();
+        allocator.allocate(TestNode::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "already registered")]
+    fn register_twice() {
+        let allocator = explicit_allocator();
+        let _first = allocator.register();
+        let _second = allocator.register();
+    }
+
+    #[test]
+    fn unregister_releases_local() {
+        let allocator = std::sync::Arc::new(explicit_allocator());
+        // The Nodes from the initial Sweep were handed to the global Pool
+        assert_eq!(1, allocator.allocation_pool.available(0));
+
+        let node_ptr = {
+            let allocator = allocator.clone();
+            std::thread::spawn(move || {
+                let _handle = allocator.register();
+                let node = allocator.allocate(TestNode::new());
+                allocator.begin_write_only(&[node.ptr()]).unwrap();
+                node.ptr() as usize
+            })
+            .join()
+            .unwrap()
+        };
+
+        // The Thread exited without releasing its Hazard-Ptr, but it is no
+        // longer registered, so its Node is reclaimed anyway
+        let handle = allocator.register();
+        allocator.force_gc();
+        assert_eq!(0, allocator.collect_stats().live_nodes);
+        assert!(allocator.local_roots().is_empty());
+
+        let node = unsafe { crate::AoaBox::from_raw(node_ptr as *mut TestNode) };
+        assert!(!node.marks().marked);
+        drop(handle);
+
+        // The same Thread can register again afterwards
+        let _handle = allocator.register();
+        let local = allocator.get_local();
+        assert!(local.registered.load(atomic::Ordering::Acquire));
+        assert!(local.cur_traced.load(atomic::Ordering::Acquire).is_null());
+    }
+
+    #[test]
+    fn implicit_registration() {
+        let allocator = Allocator::<TestNode, _>::builder(NoGlobals)
+            .page_size(8)
+            .build();
+
+        // Without explicit Registration the Handle only flushes the Local
+        let handle = allocator.register();
+        allocator.allocate(TestNode::new());
+        drop(handle);
+
+        assert_eq!(1, allocator.allocation_pool.available(0));
+        allocator.allocate(TestNode::new());
+    }
+
+    #[test]
+    fn unregister_mid_trace() {
+        let allocator = explicit_allocator();
+        let handle = allocator.register();
+
+        let first = allocator.allocate(TestNode::new());
+        let second = allocator.allocate(TestNode::new());
+        first.next.store(second.ptr(), atomic::Ordering::Release);
+
+        allocator.init_reclaimation();
+        allocator.update_marks();
+        allocator.get_local().mark_stack.push(first.ptr());
+
+        // The Child is still left on the MarkStack after a single Step
+        assert!(!allocator.trace_step(1));
+        drop(handle);
+
+        // The Thread finished its Part of the Trace before unregistering
+        assert!(first.marks().marked);
+        assert!(second.marks().marked);
+        assert!(allocator.get_or_create_local().mark_stack.is_empty());
+    }
+}
@@ -0,0 +1,229 @@
+//! Snapshots of the Node-Graph, which can be used by external Tools to render
+//! the Heap and find Leaks or unexpected Retention.
+//!
+//! With the `serde` Feature enabled, the Snapshots can be serialized to and
+//! deserialized from any Format supported by `serde`, like JSON. Only the
+//! Topology of the Graph and the Marks of the Nodes are recorded, never the
+//! Data stored in the Nodes themselves.
+
+use alloc::vec::Vec;
+
+/// The Graph of all the Nodes reachable from the Roots of an
+/// [`Allocator`](crate::Allocator), obtained using
+/// [`Allocator::snapshot`](crate::Allocator::snapshot)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HeapSnapshot {
+    /// The global Phase when the Snapshot was taken
+    pub phase: u64,
+    /// The Addresses of all the Roots, from the Globals and the Hazard-Ptrs
+    /// of all the Threads
+    pub roots: Vec<usize>,
+    /// Every Node reachable from the Roots, each only recorded once
+    pub nodes: Vec<SnapshotNode>,
+}
+
+/// A single Node in a [`HeapSnapshot`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotNode {
+    /// The Address of the Data of the Node
+    pub address: usize,
+    /// Whether or not the Node is marked
+    pub marked: bool,
+    /// The Phase in which the Marks of the Node were last updated
+    pub phase: u64,
+    /// The untagged Addresses of all the Nodes this Node points to, excluding
+    /// Null-Ptrs
+    pub edges: Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+mod serialization {
+    use super::*;
+
+    use alloc::string::String;
+    use core::fmt;
+    use serde::{
+        de::{self, MapAccess, SeqAccess, Visitor},
+        ser::SerializeStruct,
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    const SNAPSHOT_FIELDS: &[&str] = &["phase", "roots", "nodes"];
+    const NODE_FIELDS: &[&str] = &["address", "marked", "phase", "edges"];
+
+    impl Serialize for HeapSnapshot {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut state = serializer.serialize_struct("HeapSnapshot", 3)?;
+            state.serialize_field("phase", &self.phase)?;
+            state.serialize_field("roots", &self.roots)?;
+            state.serialize_field("nodes", &self.nodes)?;
+            state.end()
+        }
+    }
+
+    impl Serialize for SnapshotNode {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut state = serializer.serialize_struct("SnapshotNode", 4)?;
+            state.serialize_field("address", &self.address)?;
+            state.serialize_field("marked", &self.marked)?;
+            state.serialize_field("phase", &self.phase)?;
+            state.serialize_field("edges", &self.edges)?;
+            state.end()
+        }
+    }
+
+    struct SnapshotVisitor;
+
+    impl<'de> Visitor<'de> for SnapshotVisitor {
+        type Value = HeapSnapshot;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("struct HeapSnapshot")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let missing = |i| de::Error::invalid_length(i, &self);
+            Ok(HeapSnapshot {
+                phase: seq.next_element()?.ok_or_else(|| missing(0))?,
+                roots: seq.next_element()?.ok_or_else(|| missing(1))?,
+                nodes: seq.next_element()?.ok_or_else(|| missing(2))?,
+            })
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let (mut phase, mut roots, mut nodes) = (None, None, None);
+            while let Some(key) = map.next_key::<String>()? {
+                match key.as_str() {
+                    "phase" => phase = Some(map.next_value()?),
+                    "roots" => roots = Some(map.next_value()?),
+                    "nodes" => nodes = Some(map.next_value()?),
+                    other => return Err(de::Error::unknown_field(other, SNAPSHOT_FIELDS)),
+                };
+            }
+
+            Ok(HeapSnapshot {
+                phase: phase.ok_or_else(|| de::Error::missing_field("phase"))?,
+                roots: roots.ok_or_else(|| de::Error::missing_field("roots"))?,
+                nodes: nodes.ok_or_else(|| de::Error::missing_field("nodes"))?,
+            })
+        }
+    }
+
+    impl<'de> Deserialize<'de> for HeapSnapshot {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_struct("HeapSnapshot", SNAPSHOT_FIELDS, SnapshotVisitor)
+        }
+    }
+
+    struct NodeVisitor;
+
+    impl<'de> Visitor<'de> for NodeVisitor {
+        type Value = SnapshotNode;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("struct SnapshotNode")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let missing = |i| de::Error::invalid_length(i, &self);
+            Ok(SnapshotNode {
+                address: seq.next_element()?.ok_or_else(|| missing(0))?,
+                marked: seq.next_element()?.ok_or_else(|| missing(1))?,
+                phase: seq.next_element()?.ok_or_else(|| missing(2))?,
+                edges: seq.next_element()?.ok_or_else(|| missing(3))?,
+            })
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let (mut address, mut marked, mut phase, mut edges) = (None, None, None, None);
+            while let Some(key) = map.next_key::<String>()? {
+                match key.as_str() {
+                    "address" => address = Some(map.next_value()?),
+                    "marked" => marked = Some(map.next_value()?),
+                    "phase" => phase = Some(map.next_value()?),
+                    "edges" => edges = Some(map.next_value()?),
+                    other => return Err(de::Error::unknown_field(other, NODE_FIELDS)),
+                };
+            }
+
+            Ok(SnapshotNode {
+                address: address.ok_or_else(|| de::Error::missing_field("address"))?,
+                marked: marked.ok_or_else(|| de::Error::missing_field("marked"))?,
+                phase: phase.ok_or_else(|| de::Error::missing_field("phase"))?,
+                edges: edges.ok_or_else(|| de::Error::missing_field("edges"))?,
+            })
+        }
+    }
+
+    impl<'de> Deserialize<'de> for SnapshotNode {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_struct("SnapshotNode", NODE_FIELDS, NodeVisitor)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use alloc::vec;
+
+        #[test]
+        fn json_round_trip() {
+            let snapshot = HeapSnapshot {
+                phase: 3,
+                roots: vec![0x10],
+                nodes: vec![
+                    SnapshotNode {
+                        address: 0x10,
+                        marked: true,
+                        phase: 3,
+                        edges: vec![0x20],
+                    },
+                    SnapshotNode {
+                        address: 0x20,
+                        marked: false,
+                        phase: 2,
+                        edges: vec![],
+                    },
+                ],
+            };
+
+            let json = serde_json::to_string(&snapshot).unwrap();
+            assert_eq!(
+                r#"{"phase":3,"roots":[16],"nodes":[{"address":16,"marked":true,"phase":3,"edges":[32]},{"address":32,"marked":false,"phase":2,"edges":[]}]}"#,
+                json
+            );
+            assert_eq!(snapshot, serde_json::from_str(&json).unwrap());
+        }
+
+        #[test]
+        fn json_missing_field() {
+            let result = serde_json::from_str::<HeapSnapshot>(r#"{"phase":3,"roots":[]}"#);
+            assert!(result.is_err());
+        }
+    }
+}
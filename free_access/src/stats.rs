@@ -0,0 +1,153 @@
+#[cfg(feature = "std")]
+use crate::sync::atomic;
+
+/// A Snapshot of the current State of an [`Allocator`](crate::Allocator),
+/// obtained using [`Allocator::collect_stats`](crate::Allocator::collect_stats).
+///
+/// # Concurrency
+/// The individual Counts are gathered one after another, while other Threads
+/// may still allocate Nodes or run a Collection, so they are only racy
+/// Snapshots and may not be consistent with each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcStats {
+    /// The current global Phase
+    pub phase_index: u64,
+    /// The Number of Pages in the Heap
+    pub page_count: u64,
+    /// The total Number of Nodes in all the Pages
+    pub total_nodes: usize,
+    /// The Number of Nodes that are marked in the current Phase, which are
+    /// the Nodes that were found to be reachable by the last Collection.
+    ///
+    /// Nodes that were allocated since then are not marked yet, so this is a
+    /// lower Bound for the Number of Nodes in use
+    pub live_nodes: usize,
+    /// The Number of Nodes that are not marked in the current Phase, these are
+    /// either free or were allocated since the last Collection
+    pub free_nodes: usize,
+    /// The Number of Nodes that were allocated using the System-Allocator
+    pub overflow_nodes: usize,
+    /// The Number of Entries on the MarkStacks of all Threads combined
+    pub mark_stack_depth: usize,
+    /// The Number of Entries on the deepest MarkStack of a single Thread,
+    /// which shows whether a single Thread is carrying most of the Marking
+    pub max_mark_stack_depth: usize,
+    /// The Number of free Allocation-Buffers stored in the global Pool for
+    /// the current Phase
+    pub pooled_buffers: usize,
+}
+
+/// The cheap Counters a [`CollectionPolicy`](crate::CollectionPolicy) bases its
+/// Decision on.
+///
+/// Unlike [`GcStats`], these are maintained while allocating and do not
+/// require walking the entire Heap, so they can be gathered every Time a
+/// Thread obtains a new Allocation-Buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyStats {
+    /// The current global Phase
+    pub phase_index: u64,
+    /// The Number of free Allocation-Buffers stored in the global Pool for
+    /// the current Phase
+    pub pooled_buffers: usize,
+    /// The Number of Nodes a single Allocation-Buffer can hold
+    pub buffer_size: usize,
+    /// The Number of Nodes handed out to the Threads since the last
+    /// Collection started.
+    ///
+    /// This is counted whenever a Thread obtains a new Buffer of free Nodes,
+    /// so the Nodes that are still left in the local Buffers are counted as
+    /// allocated as well
+    pub allocated_nodes: usize,
+}
+
+impl PolicyStats {
+    /// The Number of free Nodes stored in the global Pool, assuming every
+    /// pooled Buffer is full, so this is an upper Bound
+    pub fn pooled_nodes(&self) -> usize {
+        self.pooled_buffers * self.buffer_size
+    }
+}
+
+/// The Work done by all the Threads during the latest Collection, returned by
+/// [`Allocator::force_gc`](crate::Allocator::force_gc).
+///
+/// # Concurrency
+/// The Counts are reset once a new Collection starts and every Thread adds
+/// its own Work to them, once it is done with it. If other Threads are still
+/// taking Part in the Collection, their Work may therefore not be included
+/// yet
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CollectionReport {
+    /// The Number of Nodes that were reclaimed, including overflow Nodes
+    pub reclaimed_nodes: usize,
+    /// The Number of Nodes that were marked as reachable
+    pub traced_nodes: usize,
+    /// The Number of Pages that were swept
+    pub pages_swept: usize,
+}
+
+/// The Counters backing the [`CollectionReport`] of the current Collection
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub(crate) struct CollectionCounters {
+    reclaimed_nodes: atomic::AtomicUsize,
+    traced_nodes: atomic::AtomicUsize,
+    pages_swept: atomic::AtomicUsize,
+    allocated_nodes: atomic::AtomicUsize,
+}
+
+#[cfg(feature = "std")]
+impl CollectionCounters {
+    /// Resets all the Counters for a new Collection
+    pub fn reset(&self) {
+        self.reclaimed_nodes.store(0, atomic::Ordering::Release);
+        self.traced_nodes.store(0, atomic::Ordering::Release);
+        self.pages_swept.store(0, atomic::Ordering::Release);
+        self.allocated_nodes.store(0, atomic::Ordering::Release);
+    }
+
+    /// Adds the Number of free Nodes a Thread obtained for its Allocations
+    pub fn add_allocated(&self, nodes: usize) {
+        self.allocated_nodes
+            .fetch_add(nodes, atomic::Ordering::AcqRel);
+    }
+
+    /// The Number of Nodes handed out since the current Collection started,
+    /// see [`PolicyStats::allocated_nodes`]
+    pub fn allocated(&self) -> usize {
+        self.allocated_nodes.load(atomic::Ordering::Acquire)
+    }
+
+    /// Adds the Number of Nodes that were marked by a Thread
+    pub fn add_traced(&self, nodes: usize) {
+        self.traced_nodes.fetch_add(nodes, atomic::Ordering::AcqRel);
+    }
+
+    /// Adds the Work done by a Thread during its Sweep
+    pub fn add_sweep(&self, stats: SweepStats) {
+        self.reclaimed_nodes
+            .fetch_add(stats.nodes, atomic::Ordering::AcqRel);
+        self.pages_swept
+            .fetch_add(stats.pages, atomic::Ordering::AcqRel);
+    }
+
+    /// Creates a Report from the current Counts
+    pub fn report(&self) -> CollectionReport {
+        CollectionReport {
+            reclaimed_nodes: self.reclaimed_nodes.load(atomic::Ordering::Acquire),
+            traced_nodes: self.traced_nodes.load(atomic::Ordering::Acquire),
+            pages_swept: self.pages_swept.load(atomic::Ordering::Acquire),
+        }
+    }
+}
+
+/// The Work done by a single Thread during a Sweep
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SweepStats {
+    /// The Number of Pages swept by the Thread
+    pub pages: usize,
+    /// The Number of Nodes that were reclaimed, including overflow Nodes
+    pub nodes: usize,
+}
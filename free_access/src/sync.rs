@@ -0,0 +1,20 @@
+//! The Synchronization-Primitives used throughout the Crate
+//!
+//! Normally these are simply the ones from the Standard-Library, but when the
+//! `loom` Feature is enabled, they are replaced with the ones from
+//! [loom](https://docs.rs/loom), which allows us to model-check the lock-free
+//! Datastructures under all the possible Interleavings and weak
+//! Memory-Orderings.
+//!
+//! # Running the Loom-Tests
+//! The loom Atomics can only be used inside of a `loom::model`, so only the
+//! Tests written for loom should be run with the Feature enabled
+//! ```text
+//! cargo test --release --features loom loom_
+//! ```
+
+#[cfg(not(feature = "loom"))]
+pub use core::sync::atomic;
+
+#[cfg(feature = "loom")]
+pub use loom::sync::atomic;
@@ -0,0 +1,83 @@
+//! Helpers for storing Tags in the unused low Bits of Ptrs to Nodes
+//!
+//! Every Node is aligned to at least `align_of::<T>()`, so the lowest
+//! [`tag_bits::<T>()`](tag_bits) Bits of a Ptr to a Node are always zero and
+//! can be used to store a Tag, like a "deleted"-Flag for the Node.
+//!
+//! # Interaction with the Allocator
+//! Ptrs handed to the Collector, either through
+//! [`DataStructureNode::pointers`](crate::DataStructureNode::pointers) or as
+//! the Roots, are always untagged using
+//! [`DataStructureNode::untag_ptr`](crate::DataStructureNode::untag_ptr)
+//! before the underlying Node is looked up, because calculating the Position
+//! of the Node from a tagged Ptr would point into the wrong Memory. The
+//! `local_ptrs` passed to
+//! [`Allocator::begin_write_only`](crate::Allocator::begin_write_only)
+//! however should still contain the Tags, so that they can be stored back
+//! into the Datastructure unchanged.
+
+/// The Number of low Bits in a Ptr to `T` that are available for Tags
+pub const fn tag_bits<T>() -> u32 {
+    core::mem::align_of::<T>().trailing_zeros()
+}
+
+/// The Mask covering all the Bits that can be used for Tags in a Ptr to `T`
+pub const fn tag_mask<T>() -> usize {
+    core::mem::align_of::<T>() - 1
+}
+
+/// Stores the given Tag in the low Bits of the Ptr.
+///
+/// # Panics
+/// In Debug-Builds, if the Tag does not fit into the available Bits or if the
+/// Ptr already carries a Tag
+pub fn tag_ptr<T>(ptr: *mut T, tag: usize) -> *mut T {
+    debug_assert!(
+        tag & !tag_mask::<T>() == 0,
+        "The Tag does not fit into the available Bits"
+    );
+    debug_assert!(
+        (ptr as usize) & tag_mask::<T>() == 0,
+        "The Ptr is already tagged"
+    );
+
+    ((ptr as usize) | (tag & tag_mask::<T>())) as *mut T
+}
+
+/// Splits the given Ptr into the actual Ptr and its Tag
+pub fn untag<T>(ptr: *mut T) -> (*mut T, usize) {
+    let raw = ptr as usize;
+    let mask = tag_mask::<T>();
+
+    ((raw & !mask) as *mut T, raw & mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn available_bits() {
+        assert_eq!(0, tag_bits::<u8>());
+        assert_eq!(3, tag_bits::<u64>());
+        assert_eq!(0b111, tag_mask::<u64>());
+    }
+
+    #[test]
+    fn tag_untag() {
+        let ptr = 0x1000 as *mut u64;
+
+        let tagged = tag_ptr(ptr, 0b101);
+        assert_eq!(0x1005 as *mut u64, tagged);
+        assert_eq!((ptr, 0b101), untag(tagged));
+
+        assert_eq!((ptr, 0), untag(ptr));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "does not fit")]
+    fn tag_too_large() {
+        tag_ptr(0x1000 as *mut u64, 0b1000);
+    }
+}
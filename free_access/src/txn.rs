@@ -0,0 +1,117 @@
+//! A structured Way of performing the Read-Only Period of an Operation
+//!
+//! Instead of calling [`Allocator::validate_read`] after every Load and
+//! restarting manually once it fails, the Loads are wrapped in
+//! [`ReadTxn::load`], which validates them right away, and the whole Period
+//! is run by [`ReadTxn::run`], which restarts it until every Load could be
+//! validated.
+//!
+//! ```ignore
+//! let value = allocator.read_txn().run(|txn| {
+//!     let head = txn.load(|| head.load(Ordering::Acquire))?;
+//!     txn.load(|| unsafe { &*head }.value)
+//! });
+//! ```
+
+use crate::{Allocator, DataStructureGlobals, DataStructureNode};
+
+/// Signals that a Load could not be validated, so the Read-Only Period has to
+/// be restarted from the Beginning
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry;
+
+/// Returned by a Function annotated with
+/// `#[freeaccess(restart_limit = N, on_limit = "error")]`, once its
+/// Read-Region was restarted more than `limit` Times
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartLimitExceeded {
+    /// The configured Restart-Limit
+    pub limit: usize,
+}
+
+/// A Read-Only Period on the Datastructure, obtained using
+/// [`Allocator::read_txn`]
+pub struct ReadTxn<'a, N, G> {
+    allocator: &'a Allocator<N, G>,
+}
+
+impl<'a, N, G> ReadTxn<'a, N, G>
+where
+    N: DataStructureNode,
+    G: DataStructureGlobals<N>,
+{
+    pub(crate) fn new(allocator: &'a Allocator<N, G>) -> Self {
+        Self { allocator }
+    }
+
+    /// Performs the Load in the given Function and validates it afterwards.
+    ///
+    /// # Returns
+    /// * Ok(value): The loaded Value, which can be used safely
+    /// * Err(Retry): The Value may have been read from a reclaimed Node, so
+    ///   it must not be used and the Period has to be restarted
+    pub fn load<V, F>(&self, f: F) -> Result<V, Retry>
+    where
+        F: FnOnce() -> V,
+    {
+        self.allocator.read_checked(f)
+    }
+
+    /// Runs the given Read-Only Period until it completes without any of its
+    /// Loads failing to validate.
+    ///
+    /// Whenever the Function returns [`Retry`], the current Operation is
+    /// restarted using [`Allocator::restart`], which releases all the
+    /// Hazard-Ptrs of this Thread, and the Function is called again. So all
+    /// the Ptrs have to be loaded again inside of the Function
+    pub fn run<R, F>(self, mut f: F) -> R
+    where
+        F: FnMut(&Self) -> Result<R, Retry>,
+    {
+        loop {
+            match f(&self) {
+                Ok(result) => return result,
+                Err(Retry) => self.allocator.restart(&mut []),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tests::{NoGlobals, TestNode};
+
+    #[test]
+    fn load_validates() {
+        let allocator = Allocator::<TestNode, _>::new(NoGlobals);
+        let txn = allocator.read_txn();
+
+        assert_eq!(Ok(13), txn.load(|| 13));
+
+        // A Collection invalidates every Read of this Thread
+        allocator.force_gc();
+        assert_eq!(Err(Retry), txn.load(|| 13));
+    }
+
+    #[test]
+    fn run_retries() {
+        let allocator = Allocator::<TestNode, _>::new(NoGlobals);
+
+        let mut attempts = 0;
+        let result = allocator.read_txn().run(|txn| {
+            attempts += 1;
+            if attempts == 1 {
+                // Start a Collection in the middle of the first Attempt
+                allocator.force_gc();
+            }
+
+            txn.load(|| attempts)
+        });
+
+        assert_eq!(2, result);
+        assert_eq!(2, attempts);
+        assert_eq!(Ok(()), allocator.validate_read());
+    }
+}
@@ -0,0 +1,109 @@
+//! Drives the entire Cycle of allocating Nodes, collecting them and reusing
+//! them again, using a single Thread
+
+use std::{
+    collections::HashSet,
+    sync::{atomic, Arc},
+};
+
+use free_access::{Allocator, AllocatorConfig, DataStructureGlobals, DataStructureNode};
+
+const PAGE_SIZE: usize = 16;
+
+struct Node {
+    next: atomic::AtomicPtr<Self>,
+}
+
+impl Node {
+    fn new() -> Self {
+        Self {
+            next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+        }
+    }
+}
+
+impl DataStructureNode for Node {
+    fn pointer_count() -> usize {
+        1
+    }
+
+    fn pointers_into(&self, out: &mut Vec<*mut Self>) {
+        out.push(self.next.load(atomic::Ordering::Acquire));
+    }
+
+    fn untag_ptr(ptr: *mut Self) -> *mut Self {
+        ptr
+    }
+}
+
+struct Root(Arc<atomic::AtomicPtr<Node>>);
+
+impl DataStructureGlobals<Node> for Root {
+    fn get_globals(&self) -> Vec<*mut Node> {
+        vec![self.0.load(atomic::Ordering::Acquire)]
+    }
+}
+
+fn allocator() -> (Allocator<Node, Root>, Arc<atomic::AtomicPtr<Node>>) {
+    let root = Arc::new(atomic::AtomicPtr::new(std::ptr::null_mut()));
+    let allocator = Allocator::builder(Root(root.clone()))
+        .config(AllocatorConfig {
+            deterministic_sweep: true,
+            ..AllocatorConfig::default()
+        })
+        .page_size(PAGE_SIZE)
+        .build();
+
+    (allocator, root)
+}
+
+fn allocate_n(allocator: &Allocator<Node, Root>, n: usize) -> Vec<*mut Node> {
+    (0..n)
+        .map(|_| allocator.allocate(Node::new()).into_raw())
+        .collect()
+}
+
+#[test]
+fn unreachable_nodes_are_reused() {
+    let (allocator, _root) = allocator();
+
+    let first: HashSet<_> = allocate_n(&allocator, PAGE_SIZE).into_iter().collect();
+    assert_eq!(PAGE_SIZE, first.len());
+
+    let report = allocator.force_gc();
+    assert_eq!(PAGE_SIZE, report.reclaimed_nodes);
+    assert_eq!(0, report.traced_nodes);
+
+    let stats = allocator.collect_stats();
+    assert_eq!(0, stats.live_nodes);
+    assert_eq!(1, stats.page_count);
+
+    // The Heap did not grow, so every Node must have been reclaimed
+    let second: HashSet<_> = allocate_n(&allocator, PAGE_SIZE).into_iter().collect();
+    assert_eq!(first, second);
+    assert_eq!(1, allocator.page_count());
+}
+
+#[test]
+fn reachable_nodes_are_kept() {
+    let (allocator, root) = allocator();
+
+    let nodes = allocate_n(&allocator, PAGE_SIZE);
+    unsafe { &*nodes[0] }
+        .next
+        .store(nodes[1], atomic::Ordering::Release);
+    root.store(nodes[0], atomic::Ordering::Release);
+
+    allocator.force_gc();
+    assert_eq!(2, allocator.collect_stats().live_nodes);
+
+    let reused: HashSet<_> = allocate_n(&allocator, PAGE_SIZE - 2).into_iter().collect();
+    let expected: HashSet<_> = nodes[2..].iter().copied().collect();
+    assert_eq!(expected, reused);
+
+    // The Chain is still intact
+    assert_eq!(
+        nodes[1],
+        unsafe { &*nodes[0] }.next.load(atomic::Ordering::Acquire)
+    );
+}
@@ -4,6 +4,27 @@ use syn::{parse_macro_input, AttributeArgs};
 mod wrapper;
 mod write_only;
 
+/// Turns the annotated Function into a Read-Only Period.
+///
+/// The Allocator that should be used needs to be passed as a String
+/// containing the Expression to obtain it, like
+/// `#[freeaccess(allocator = "self.allocator")]`.
+///
+/// The Reads are validated at the Start, after every `let`-Statement and
+/// before any Value is returned from the Function, including the early
+/// Returns through `?`. If any of these
+/// Validations fails, the Operation is restarted using `Allocator::restart`
+/// and the Function runs again from the Top of its Body, so the Body should
+/// not have any Side-Effects other than Reads.
+///
+/// # Restart-Limit
+/// Under heavy Contention the Body may be restarted over and over again. With
+/// `restart_limit = N`, the Function gives up once it was restarted more than
+/// `N` Times. By default it then panics, with `on_limit = "error"` it instead
+/// returns `Err(RestartLimitExceeded { .. }.into())`, which requires the
+/// Function to return a `Result` whose Error implements
+/// `From<RestartLimitExceeded>`. Passing `on_limit` without a `restart_limit`
+/// is rejected, as it would have no Effect
 #[proc_macro_attribute]
 pub fn freeaccess(attr: TokenStream, input: TokenStream) -> TokenStream {
     let input_impl: syn::ItemFn = parse_macro_input!(input);
@@ -12,9 +33,15 @@ pub fn freeaccess(attr: TokenStream, input: TokenStream) -> TokenStream {
     wrapper::wrapper(attributes, input_impl).into()
 }
 
+/// Attempts to start a Write-Only Period and only runs the given Statements
+/// if that succeeds, like `write_only! { allocator, [ptr_a, ptr_b]; ... }`.
+///
+/// The Macro evaluates to a `Result`, which contains the Value of the
+/// Statements if the Write-Only Period could be started, or `Err(())` if the
+/// caller should restart at the previous Read-Only Period
 #[proc_macro]
 pub fn write_only(input: TokenStream) -> TokenStream {
-    let input_scope: syn::ExprBlock = parse_macro_input!(input);
+    let input: write_only::WriteOnlyInput = parse_macro_input!(input);
 
-    write_only::write_only(input_scope).into()
+    write_only::write_only(input).into()
 }
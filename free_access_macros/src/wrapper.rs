@@ -1,8 +1,300 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
+use syn::fold::Fold;
+
+/// The Arguments that can be passed to the Attribute
+struct Arguments {
+    /// The Expression to obtain the Allocator
+    allocator: syn::Expr,
+    /// The maximum Number of Restarts, before giving up
+    restart_limit: Option<usize>,
+    /// What to do once the `restart_limit` is exceeded
+    on_limit: OnLimit,
+}
+
+/// The Behaviour once the `restart_limit` of the Read-Region is exceeded
+enum OnLimit {
+    /// Panic with a Message naming the Function
+    Panic,
+    /// Return `Err(RestartLimitExceeded.into())` from the Function
+    Error,
+}
+
+impl Arguments {
+    fn parse(attributes: syn::AttributeArgs) -> Result<Self, syn::Error> {
+        let mut allocator = None;
+        let mut restart_limit = None;
+        let mut on_limit = OnLimit::Panic;
+        // Only used to point at the Argument, if it was given on its own
+        let mut on_limit_argument = None;
+
+        for attribute in attributes {
+            match attribute {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(name_value))
+                    if name_value.path.is_ident("restart_limit") =>
+                {
+                    let raw = match &name_value.lit {
+                        syn::Lit::Int(raw) => raw,
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                other,
+                                "Expected the Restart-Limit as an Integer",
+                            ))
+                        }
+                    };
+                    restart_limit = Some(raw.base10_parse::<usize>()?);
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(name_value))
+                    if name_value.path.is_ident("on_limit") =>
+                {
+                    on_limit = match &name_value.lit {
+                        syn::Lit::Str(raw) if raw.value() == "panic" => OnLimit::Panic,
+                        syn::Lit::Str(raw) if raw.value() == "error" => OnLimit::Error,
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                other,
+                                "Expected either \"panic\" or \"error\"",
+                            ))
+                        }
+                    };
+                    on_limit_argument = Some(name_value);
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(name_value))
+                    if name_value.path.is_ident("allocator") =>
+                {
+                    let raw = match &name_value.lit {
+                        syn::Lit::Str(raw) => raw,
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                other,
+                                "Expected the Allocator-Expression as a String",
+                            ))
+                        }
+                    };
+                    allocator = Some(raw.parse::<syn::Expr>()?);
+                }
+                other => {
+                    return Err(syn::Error::new_spanned(other, "Unknown Argument"));
+                }
+            };
+        }
+
+        if let (Some(on_limit_argument), None) = (on_limit_argument, restart_limit) {
+            return Err(syn::Error::new_spanned(
+                on_limit_argument,
+                "`on_limit` only has an Effect together with `restart_limit = N`",
+            ));
+        }
+
+        match allocator {
+            Some(allocator) => Ok(Self {
+                allocator,
+                restart_limit,
+                on_limit,
+            }),
+            None => Err(syn::Error::new(
+                Span::call_site(),
+                "Missing the `allocator = \"...\"` Argument",
+            )),
+        }
+    }
+}
+
+fn restart_label() -> syn::Lifetime {
+    syn::Lifetime::new("'__free_access_restart", Span::call_site())
+}
+
+fn allocator_ident() -> syn::Ident {
+    syn::Ident::new("__free_access_allocator", Span::call_site())
+}
+
+/// Generates the Code to validate the previous Reads and restart the
+/// Read-Region if that fails
+fn validate() -> TokenStream {
+    let label = restart_label();
+    let allocator = allocator_ident();
 
-pub fn wrapper(_attributes: syn::AttributeArgs, input: syn::ItemFn) -> TokenStream {
     quote! {
-        #input
+        if #allocator.validate_read().is_err() {
+            #allocator.restart(&mut []);
+            continue #label;
+        }
+    }
+}
+
+/// Rewrites every `return` and `?` in the Body to validate the returned Value
+/// before it escapes the Read-Region
+struct ReturnValidator;
+
+impl Fold for ReturnValidator {
+    fn fold_expr(&mut self, expr: syn::Expr) -> syn::Expr {
+        match expr {
+            syn::Expr::Return(ret) => {
+                let value = ret
+                    .expr
+                    .map(|e| self.fold_expr(*e))
+                    .map(|e| quote! { #e })
+                    .unwrap_or_else(|| quote! { () });
+                let validate = validate();
+
+                syn::parse_quote! {
+                    {
+                        let __free_access_result = #value;
+                        #validate
+                        return __free_access_result;
+                    }
+                }
+            }
+            // A `?` may return early as well, so the Value is validated before
+            // it is checked
+            syn::Expr::Try(expr_try) => {
+                let value = self.fold_expr(*expr_try.expr);
+                let validate = validate();
+
+                syn::parse_quote! {
+                    {
+                        let __free_access_result = #value;
+                        #validate
+                        __free_access_result?
+                    }
+                }
+            }
+            // Closures have their own returns, which do not leave the
+            // Read-Region
+            syn::Expr::Closure(closure) => syn::Expr::Closure(closure),
+            other => syn::fold::fold_expr(self, other),
+        }
+    }
+
+    fn fold_item(&mut self, item: syn::Item) -> syn::Item {
+        item
+    }
+}
+
+/// Turns the Body of the annotated Function into a Read-Only Period.
+///
+/// # Generated Code
+/// ```ignore
+/// let mut __free_access_restarts: usize = 0;
+/// '__free_access_restart: loop {
+///     let __free_access_allocator = &(allocator);
+///
+///     // Only with `restart_limit = N`
+///     if __free_access_restarts > N {
+///         panic!(...); // or `return Err(RestartLimitExceeded { .. }.into());`
+///     }
+///     __free_access_restarts += 1;
+///
+///     if __free_access_allocator.validate_read().is_err() {
+///         __free_access_allocator.restart(&mut []);
+///         continue '__free_access_restart;
+///     }
+///
+///     // For every `let`-Statement (a Load)
+///     let x = ...;
+///     if __free_access_allocator.validate_read().is_err() {
+///         __free_access_allocator.restart(&mut []);
+///         continue '__free_access_restart;
+///     }
+///
+///     // Every other Statement stays untouched
+///     ...;
+///
+///     // The final Expression, every `return` and every `?` validate before
+///     // returning
+///     let __free_access_result = ...;
+///     if __free_access_allocator.validate_read().is_err() {
+///         __free_access_allocator.restart(&mut []);
+///         continue '__free_access_restart;
+///     }
+///     return __free_access_result;
+/// }
+/// ```
+pub fn wrapper(attributes: syn::AttributeArgs, input: syn::ItemFn) -> TokenStream {
+    let arguments = match Arguments::parse(attributes) {
+        Ok(a) => a,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let syn::ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = input;
+
+    let block = ReturnValidator.fold_block(*block);
+    let mut stmts = block.stmts;
+
+    let tail = match stmts.last() {
+        Some(syn::Stmt::Expr(_)) => match stmts.pop() {
+            Some(syn::Stmt::Expr(e)) => quote! { #e },
+            _ => unreachable!(),
+        },
+        _ => quote! { () },
+    };
+
+    let body = stmts.into_iter().map(|stmt| match stmt {
+        syn::Stmt::Local(_) => {
+            let validate = validate();
+            quote! {
+                #stmt
+                #validate
+            }
+        }
+        other => quote! { #other },
+    });
+
+    let label = restart_label();
+    let allocator_ident = allocator_ident();
+    let allocator = arguments.allocator;
+    let validate = validate();
+
+    let (counter, limit_check) = match arguments.restart_limit {
+        Some(limit) => {
+            let exceeded = match arguments.on_limit {
+                OnLimit::Panic => {
+                    let message = format!(
+                        "The Read-Region of `{}` was restarted more than {} Times",
+                        sig.ident, limit
+                    );
+                    quote! { panic!(#message); }
+                }
+                OnLimit::Error => quote! {
+                    return ::core::result::Result::Err(::core::convert::From::from(
+                        ::free_access::RestartLimitExceeded { limit: #limit },
+                    ));
+                },
+            };
+
+            (
+                quote! { let mut __free_access_restarts: usize = 0; },
+                quote! {
+                    if __free_access_restarts > #limit {
+                        #exceeded
+                    }
+                    __free_access_restarts += 1;
+                },
+            )
+        }
+        None => (quote! {}, quote! {}),
+    };
+
+    quote! {
+        #(#attrs)*
+        #vis #sig {
+            #counter
+            #label: loop {
+                let #allocator_ident = &(#allocator);
+                #limit_check
+                #validate
+
+                #(#body)*
+
+                let __free_access_result = #tail;
+                #validate
+                return __free_access_result;
+            }
+        }
     }
 }
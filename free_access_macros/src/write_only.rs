@@ -1,8 +1,63 @@
 use proc_macro2::TokenStream;
 use quote::quote;
+use syn::parse::{Parse, ParseStream};
+
+/// The Input to the `write_only!` Macro, in the Form
+/// `allocator, [ptrs...]; statements...`
+pub struct WriteOnlyInput {
+    allocator: syn::Expr,
+    ptrs: syn::punctuated::Punctuated<syn::Expr, syn::Token![,]>,
+    stmts: Vec<syn::Stmt>,
+}
+
+impl Parse for WriteOnlyInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let allocator = input.parse()?;
+        input.parse::<syn::Token![,]>()?;
+
+        let content;
+        syn::bracketed!(content in input);
+        let ptrs = content.parse_terminated(syn::Expr::parse)?;
+
+        input.parse::<syn::Token![;]>()?;
+        let stmts = syn::Block::parse_within(input)?;
+
+        Ok(Self {
+            allocator,
+            ptrs,
+            stmts,
+        })
+    }
+}
+
+/// Generates the Code to start a Write-Only Period and only runs the given
+/// Statements if that succeeds.
+///
+/// # Generated Code
+/// ```ignore
+/// {
+///     let __free_access_ptrs = [ptrs...];
+///     match (allocator).begin_write_only(&__free_access_ptrs) {
+///         Ok(()) => Ok({ statements... }),
+///         Err(()) => Err(()),
+///     }
+/// }
+/// ```
+pub fn write_only(input: WriteOnlyInput) -> TokenStream {
+    let WriteOnlyInput {
+        allocator,
+        ptrs,
+        stmts,
+    } = input;
+    let ptrs = ptrs.into_iter();
 
-pub fn write_only(block: syn::ExprBlock) -> TokenStream {
     quote! {
-        #block
+        {
+            let __free_access_ptrs = [#(#ptrs),*];
+            match (#allocator).begin_write_only(&__free_access_ptrs) {
+                Ok(()) => Ok({ #(#stmts)* }),
+                Err(()) => Err(()),
+            }
+        }
     }
 }